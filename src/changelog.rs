@@ -0,0 +1,711 @@
+#![cfg(feature = "changelog")]
+use std::ops::Range;
+
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+use semver::Version;
+
+use crate::error::{Error, Result};
+use crate::helpers::{line_column, parse_version, read_path, source_lines};
+use crate::reporter::{CheckKind, Reporter, StdoutReporter};
+
+/// A calendar date given as `YYYY-MM-DD` in a heading.
+type Date = (u32, u32, u32);
+
+/// A heading for a released version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Release {
+    version: Version,
+    /// The version as written in the heading, such as `v1.2.0`.
+    text: String,
+    /// Byte offset of the version in the changelog.
+    offset: usize,
+    /// The date as written in the heading and its byte offset.
+    date: Option<(String, usize)>,
+}
+
+/// A link reference definition, such as `[1.2.0]: https://...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LinkRef {
+    label: String,
+    dest: String,
+    /// Byte offset of the definition in the changelog.
+    offset: usize,
+}
+
+/// The release headings and link references of a changelog.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Changelog {
+    releases: Vec<Release>,
+    /// Byte offset of the text of the `Unreleased` heading, if any.
+    unreleased: Option<usize>,
+    links: Vec<LinkRef>,
+}
+
+/// Check if `text` is the `Unreleased` heading, with or without
+/// brackets.
+fn is_unreleased(text: &str) -> bool {
+    let text = text.trim();
+    let text = text
+        .strip_prefix('[')
+        .and_then(|text| text.strip_suffix(']'))
+        .unwrap_or(text);
+    text.eq_ignore_ascii_case("unreleased")
+}
+
+/// Parse a version as written in a heading or link label, such as
+/// `1.2.0` or `v1.2.0`.
+fn parse_release(text: &str) -> Option<Version> {
+    let text = text.strip_prefix(['v', 'V']).unwrap_or(text);
+    Version::parse(text).ok()
+}
+
+/// Check if `word` looks like a `YYYY-MM-DD` date.
+fn looks_like_date(word: &str) -> bool {
+    word.len() == 10
+        && word.char_indices().all(|(idx, c)| match idx {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+/// Parse a `YYYY-MM-DD` date, rejecting days which do not exist.
+fn parse_date(text: &str) -> Option<Date> {
+    let mut parts = text.splitn(3, '-').map(|part| part.parse::<u32>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days).contains(&day).then_some((year, month, day))
+}
+
+/// Find the version and date in the text of a heading which spans
+/// `range` in `text`.
+fn parse_heading(text: &str, range: Range<usize>, heading: &str) -> Option<Release> {
+    let words = heading
+        .split(|c: char| c.is_whitespace() || "[]()".contains(c))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+    let idx = words
+        .iter()
+        .position(|word| parse_release(word).is_some())?;
+    let source = &text[range.clone()];
+    let find = |word: &str| {
+        source
+            .find(word)
+            .map_or(range.start, |idx| range.start + idx)
+    };
+
+    let word = words[idx];
+    let date = words[idx + 1..]
+        .iter()
+        .find(|word| looks_like_date(word))
+        .map(|date| (String::from(*date), find(date)));
+    Some(Release {
+        version: parse_release(word)?,
+        text: String::from(word),
+        offset: find(word),
+        date,
+    })
+}
+
+/// Find the release headings and link references in a Markdown text.
+///
+/// The releases are the headings with a version number at the level
+/// of the first such heading. Other headings are ignored.
+fn parse_changelog(text: &str) -> Changelog {
+    let parser = Parser::new(text).into_offset_iter();
+    let mut links = parser
+        .reference_definitions()
+        .iter()
+        .map(|(label, def)| LinkRef {
+            label: String::from(label),
+            dest: def.dest.to_string(),
+            offset: def.span.start,
+        })
+        .collect::<Vec<_>>();
+    links.sort_by_key(|link| link.offset);
+
+    let mut changelog = Changelog {
+        links,
+        ..Changelog::default()
+    };
+    let mut level: Option<HeadingLevel> = None;
+    let mut unreleased: Vec<(HeadingLevel, usize)> = Vec::new();
+    let mut heading: Option<(HeadingLevel, Range<usize>, String)> = None;
+    for (event, range) in parser {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                heading = Some((level, range, String::new()));
+            }
+            Event::Text(content) | Event::Code(content) => {
+                if let Some((_, _, text)) = &mut heading {
+                    text.push_str(&content);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                let (heading_level, range, heading_text) = match heading.take() {
+                    Some(heading) => heading,
+                    None => continue,
+                };
+                if is_unreleased(&heading_text) {
+                    let start = text[range.clone()].find(char::is_alphabetic).unwrap_or(0);
+                    unreleased.push((heading_level, range.start + start));
+                } else if level.is_none_or(|level| level == heading_level) {
+                    if let Some(release) = parse_heading(text, range, &heading_text) {
+                        level = Some(heading_level);
+                        changelog.releases.push(release);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    changelog.unreleased = unreleased
+        .into_iter()
+        .find(|(unreleased_level, _)| level.is_none_or(|level| level == *unreleased_level))
+        .map(|(_, offset)| offset);
+    changelog
+}
+
+/// Check if `tag` is a Git tag for `version`, such as `v1.2.0` or
+/// `foo-1.2.0`.
+fn tag_matches(tag: &str, version: &Version) -> bool {
+    tag.strip_suffix(version.to_string().as_str())
+        .is_some_and(|prefix| !prefix.ends_with(|c: char| c.is_ascii_digit() || c == '.'))
+}
+
+/// Find the tags in a link to a release or a comparison of two
+/// releases.
+///
+/// Returns the byte ranges in `dest` of the base tag, if any, and of
+/// the tag of the release, such as the ranges of `v1.1.0` and `v1.2.0`
+/// in `https://github.com/foo/bar/compare/v1.1.0...v1.2.0`.
+fn link_tags(dest: &str) -> (Option<Range<usize>>, Range<usize>) {
+    let end = dest.trim_end_matches('/').len();
+    match dest[..end].rfind("...") {
+        Some(dots) => {
+            let start = dest[..dots].rfind('/').map_or(0, |idx| idx + 1);
+            (Some(start..dots), dots + 3..end)
+        }
+        None => {
+            let start = dest[..end].rfind('/').map_or(0, |idx| idx + 1);
+            (None, start..end)
+        }
+    }
+}
+
+/// Construct the tag for `version` from `tag`, keeping any prefix such
+/// as `v`.
+fn replace_tag(tag: &str, version: &Version) -> String {
+    let prefix = &tag[..tag.find(|c: char| c.is_ascii_digit()).unwrap_or(tag.len())];
+    format!("{prefix}{version}")
+}
+
+/// Check the changelog `text` from `path` and report each release
+/// heading and link reference to `reporter`.
+fn check_text(
+    path: &str,
+    text: &str,
+    version: &Version,
+    unreleased_required: bool,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let changelog = parse_changelog(text);
+    if !changelog
+        .releases
+        .iter()
+        .any(|release| release.version == *version)
+    {
+        return Err(Error::NoMatch {
+            path: String::from(path),
+            pattern: version.to_string(),
+        });
+    }
+    if unreleased_required && changelog.unreleased.is_none() {
+        return Err(Error::NoMatch {
+            path: String::from(path),
+            pattern: String::from("[Unreleased]"),
+        });
+    }
+
+    let mismatch = |offset: usize, found: &str, expected: String, message: String| {
+        let (line, column) = line_column(text, offset);
+        Error::Mismatch {
+            path: String::from(path),
+            line,
+            column,
+            found: String::from(found),
+            expected,
+            message,
+        }
+    };
+    // Every location is reported with the errors found in it.
+    let mut locations = Vec::new();
+
+    if let Some(offset) = changelog.unreleased {
+        let mut errors = Vec::new();
+        let first = &changelog.releases[0];
+        if offset > first.offset {
+            errors.push(mismatch(
+                offset,
+                "Unreleased",
+                String::new(),
+                format!("the Unreleased section is below version {}", first.text),
+            ));
+        }
+        locations.push((offset, errors));
+    }
+
+    let mut last_date: Option<(Date, &Release)> = None;
+    for (idx, release) in changelog.releases.iter().enumerate() {
+        let mut errors = Vec::new();
+        if idx == 0 && release.version != *version {
+            errors.push(mismatch(
+                release.offset,
+                &release.text,
+                version.to_string(),
+                format!(
+                    "the topmost entry is for version {}, not version {version}",
+                    release.text
+                ),
+            ));
+        }
+        if let Some(above) = idx.checked_sub(1).map(|idx| &changelog.releases[idx]) {
+            if release.version >= above.version {
+                errors.push(mismatch(
+                    release.offset,
+                    &release.text,
+                    String::new(),
+                    format!(
+                        "version {} is not below version {} above it",
+                        release.text, above.text
+                    ),
+                ));
+            }
+        }
+        if let Some((date_text, offset)) = &release.date {
+            match parse_date(date_text) {
+                None => errors.push(mismatch(
+                    *offset,
+                    date_text,
+                    String::new(),
+                    format!("invalid date {date_text}"),
+                )),
+                Some(date) => {
+                    if let Some((above_date, above)) = last_date {
+                        if date > above_date {
+                            errors.push(mismatch(
+                                *offset,
+                                date_text,
+                                String::new(),
+                                format!(
+                                    "date {date_text} is after the date of version {} above it",
+                                    above.text
+                                ),
+                            ));
+                        }
+                    }
+                    last_date = Some((date, release));
+                }
+            }
+        }
+        locations.push((release.offset, errors));
+    }
+
+    for link in &changelog.links {
+        let (base, tag) = link_tags(&link.dest);
+        let (expected, range) = if is_unreleased(&link.label) {
+            // The unreleased changes are compared to the latest
+            // release.
+            (Some(&changelog.releases[0]), base.unwrap_or(tag))
+        } else {
+            let idx = match parse_release(&link.label).and_then(|label| {
+                changelog
+                    .releases
+                    .iter()
+                    .position(|release| release.version == label)
+            }) {
+                Some(idx) => idx,
+                None => continue,
+            };
+            let release = &changelog.releases[idx];
+            if !tag_matches(&link.dest[tag.clone()], &release.version) {
+                (Some(release), tag)
+            } else {
+                match base {
+                    Some(base) => (changelog.releases.get(idx + 1), base),
+                    None => (None, tag),
+                }
+            }
+        };
+
+        let mut errors = Vec::new();
+        if let Some(expected) = expected {
+            if !tag_matches(&link.dest[range.clone()], &expected.version) {
+                // Only the outdated tag is pointed at, not the whole
+                // destination.
+                let tag_offset = text[link.offset..]
+                    .find(&link.dest)
+                    .map_or(link.offset, |idx| link.offset + idx + range.start);
+                let found = &link.dest[range];
+                errors.push(mismatch(
+                    tag_offset,
+                    found,
+                    replace_tag(found, &expected.version),
+                    format!(
+                        "link for [{}] points at tag {found}, expected version {}",
+                        link.label, expected.text
+                    ),
+                ));
+            }
+        }
+        locations.push((link.offset, errors));
+    }
+
+    let mut errors = Vec::new();
+    for (offset, location_errors) in locations {
+        let (line, _) = line_column(text, offset);
+        if location_errors.is_empty() {
+            reporter.location_ok(path, line);
+        }
+        for err in location_errors {
+            reporter.location_mismatch(path, line, &err, source_lines(text, line, line));
+            errors.push(err);
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
+    }
+    Ok(())
+}
+
+/// Check the changelog in `path` and send status information to
+/// `reporter`.
+fn check_file(
+    path: &str,
+    pkg_version: &str,
+    unreleased_required: bool,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    reporter.check_started(CheckKind::Changelog, path, None);
+    let result = read_path(path).and_then(|text| {
+        let version = parse_version(pkg_version)?;
+        check_text(path, &text, &version, unreleased_required, reporter)
+    });
+    reporter.check_finished(CheckKind::Changelog, path, &result);
+    result
+}
+
+/// Check that the Markdown changelog in `path` has an entry for the
+/// current version.
+///
+/// The entries are the headings with a version number, such as
+///
+/// ```markdown
+/// ## [1.2.0] - 2024-05-17
+/// ```
+///
+/// from [Keep a Changelog] or
+///
+/// ```markdown
+/// ### Version 1.2.0 (2024-05-17)
+/// ```
+///
+/// as in the `README.md` file of this crate. The level of the first
+/// such heading is used for all entries and other headings are
+/// ignored, so the changelog can be part of a larger file. The check
+/// verifies that
+///
+/// * there is an entry for `pkg_version`, and it is the topmost entry,
+/// * the versions are in descending SemVer order,
+/// * the dates in `YYYY-MM-DD` format are valid, and no entry has a
+///   date later than the entry above it,
+/// * an `Unreleased` section, if any, is above the released versions,
+/// * link references, such as
+///   `[1.2.0]: https://github.com/foo/bar/compare/v1.1.0...v1.2.0`,
+///   point at the tag of their version. For comparisons, the base tag
+///   must be the previous version, and the `[Unreleased]` link must
+///   compare with the latest release.
+///
+/// [Keep a Changelog]: https://keepachangelog.com/
+///
+/// # Errors
+///
+/// If there is no entry for `pkg_version`, an [`Error::NoMatch`] is
+/// returned. If any other check fails, an [`Error::Failed`] is
+/// returned with an [`Error::Mismatch`] per problem. Status
+/// information has then already been printed on `stdout`.
+pub fn check_changelog(path: &str, pkg_version: &str) -> Result<()> {
+    check_changelog_with_reporter(path, pkg_version, &mut StdoutReporter)
+}
+
+/// Check the changelog in `path` and send status information to
+/// `reporter`.
+///
+/// This works like [`check_changelog`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If the changelog fails the check, an [`Error::NoMatch`] or
+/// [`Error::Failed`] is returned.
+pub fn check_changelog_with_reporter(
+    path: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    check_file(path, pkg_version, false, reporter)
+}
+
+/// Check the changelog in `path` and require an `Unreleased`
+/// section.
+///
+/// This works like [`check_changelog`], except that the changelog
+/// must have an `Unreleased` section above the released versions, as
+/// recommended by [Keep a Changelog].
+///
+/// [Keep a Changelog]: https://keepachangelog.com/
+///
+/// # Errors
+///
+/// If there is no entry for `pkg_version` or no `Unreleased` section,
+/// an [`Error::NoMatch`] is returned. If any other check fails, an
+/// [`Error::Failed`] is returned with an [`Error::Mismatch`] per
+/// problem. Status information has then already been printed on
+/// `stdout`.
+pub fn check_changelog_unreleased(path: &str, pkg_version: &str) -> Result<()> {
+    check_changelog_unreleased_with_reporter(path, pkg_version, &mut StdoutReporter)
+}
+
+/// Check the changelog in `path`, require an `Unreleased` section,
+/// and send status information to `reporter`.
+///
+/// This works like [`check_changelog_unreleased`], but nothing is
+/// printed on `stdout`.
+///
+/// # Errors
+///
+/// If the changelog fails the check, an [`Error::NoMatch`] or
+/// [`Error::Failed`] is returned.
+pub fn check_changelog_unreleased_with_reporter(
+    path: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    check_file(path, pkg_version, true, reporter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reporter::Silent;
+
+    fn check(text: &str, pkg_version: &str, unreleased_required: bool) -> Result<()> {
+        let version = Version::parse(pkg_version).unwrap();
+        check_text(
+            "CHANGELOG.md",
+            text,
+            &version,
+            unreleased_required,
+            &mut Silent,
+        )
+    }
+
+    /// The line, column, and message of each problem found.
+    fn problems(text: &str, pkg_version: &str) -> Vec<(usize, usize, String)> {
+        match check(text, pkg_version, false) {
+            Ok(()) => Vec::new(),
+            Err(Error::Failed { errors, .. }) => errors
+                .iter()
+                .map(|err| match err {
+                    Error::Mismatch {
+                        line,
+                        column,
+                        message,
+                        ..
+                    } => (*line, *column, message.clone()),
+                    err => panic!("unexpected error: {err:?}"),
+                })
+                .collect(),
+            Err(err) => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    const KEEP_A_CHANGELOG: &str = "# Changelog\n\
+                                    \n\
+                                    ## [Unreleased]\n\
+                                    ### Added\n\
+                                    - Something new in `1.9.9`.\n\
+                                    \n\
+                                    ## [1.2.0] - 2024-05-17\n\
+                                    ### Fixed\n\
+                                    - A bug.\n\
+                                    \n\
+                                    ## [v1.1.0] - 2024-02-29\n\
+                                    \n\
+                                    ## 1.0.0 - 2023-12-01\n\
+                                    \n\
+                                    [unreleased]: https://github.com/foo/bar/compare/v1.2.0...HEAD\n\
+                                    [1.2.0]: https://github.com/foo/bar/compare/v1.1.0...v1.2.0\n\
+                                    [v1.1.0]: https://github.com/foo/bar/compare/v1.0.0...v1.1.0\n";
+
+    #[test]
+    fn keep_a_changelog() {
+        assert_eq!(problems(KEEP_A_CHANGELOG, "1.2.0"), vec![]);
+        assert!(check(KEEP_A_CHANGELOG, "1.2.0", true).is_ok());
+    }
+
+    #[test]
+    fn version_headings() {
+        let text = "## Release History\n\
+                    \n\
+                    ### Version 0.9.5 (2023-09-09)\n\
+                    \n\
+                    ### Upgrading\n\
+                    \n\
+                    ### Version 0.9.4 (2021-12-14)\n";
+        assert_eq!(problems(text, "0.9.5"), vec![]);
+    }
+
+    #[test]
+    fn missing_entry() {
+        let err = check(KEEP_A_CHANGELOG, "1.3.0", false).unwrap_err();
+        assert_eq!(err.to_string(), "could not find \"1.3.0\" in CHANGELOG.md");
+    }
+
+    #[test]
+    fn missing_unreleased() {
+        let text = "## 1.0.0 - 2023-12-01\n";
+        assert!(check(text, "1.0.0", false).is_ok());
+        let err = check(text, "1.0.0", true).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "could not find \"[Unreleased]\" in CHANGELOG.md"
+        );
+    }
+
+    #[test]
+    fn entry_not_topmost() {
+        assert_eq!(
+            problems(KEEP_A_CHANGELOG, "1.1.0"),
+            vec![(
+                7,
+                5,
+                String::from("the topmost entry is for version 1.2.0, not version 1.1.0")
+            )]
+        );
+    }
+
+    #[test]
+    fn version_order() {
+        let text = "## 1.2.0\n\n## 1.3.0\n\n## 1.3.0\n\n## 0.1.0\n";
+        assert_eq!(
+            problems(text, "1.2.0"),
+            vec![
+                (
+                    3,
+                    4,
+                    String::from("version 1.3.0 is not below version 1.2.0 above it")
+                ),
+                (
+                    5,
+                    4,
+                    String::from("version 1.3.0 is not below version 1.3.0 above it")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn dates() {
+        let text = "## 1.3.0 - 2023-02-29\n\
+                    ## 1.2.0 - 2023-01-10\n\
+                    ## 1.1.0 - 2023-03-01\n\
+                    ## 1.0.0\n\
+                    ## 0.9.0 - 2020-02-29\n";
+        assert_eq!(
+            problems(text, "1.3.0"),
+            vec![
+                (1, 12, String::from("invalid date 2023-02-29")),
+                (
+                    3,
+                    12,
+                    String::from("date 2023-03-01 is after the date of version 1.2.0 above it")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unreleased_below_release() {
+        let text = "## 1.0.0\n\n## Unreleased\n";
+        assert_eq!(
+            problems(text, "1.0.0"),
+            vec![(
+                3,
+                4,
+                String::from("the Unreleased section is below version 1.0.0")
+            )]
+        );
+    }
+
+    #[test]
+    fn link_references() {
+        let text = KEEP_A_CHANGELOG
+            .replace("v1.2.0...HEAD", "v1.1.0...HEAD")
+            .replace("v1.0.0...v1.1.0", "v0.9.0...v1.1.0");
+        assert_eq!(
+            problems(&text, "1.2.0"),
+            vec![
+                (
+                    15,
+                    50,
+                    String::from(
+                        "link for [unreleased] points at tag v1.1.0, expected version 1.2.0"
+                    )
+                ),
+                (
+                    17,
+                    46,
+                    String::from("link for [v1.1.0] points at tag v0.9.0, expected version 1.0.0")
+                ),
+            ]
+        );
+
+        let text = KEEP_A_CHANGELOG.replace("v1.1.0...v1.2.0", "v1.1.0...v1.1.1");
+        match check(&text, "1.2.0", false) {
+            Err(Error::Failed { errors, .. }) => match &errors[..] {
+                [Error::Mismatch {
+                    found, expected, ..
+                }] => assert_eq!((found.as_str(), expected.as_str()), ("v1.1.1", "v1.2.0")),
+                errors => panic!("unexpected errors: {errors:?}"),
+            },
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn tags() {
+        let version = Version::new(1, 2, 0);
+        assert!(tag_matches("v1.2.0", &version));
+        assert!(tag_matches("1.2.0", &version));
+        assert!(tag_matches("foo-1.2.0", &version));
+        assert!(!tag_matches("v11.2.0", &version));
+        assert!(!tag_matches("v1.2.01", &version));
+        assert_eq!(
+            link_tags("https://example.net/compare/v1...v2/"),
+            (Some(28..30), 33..35)
+        );
+        assert_eq!(link_tags("https://example.net/tag/v2"), (None, 24..26));
+    }
+}
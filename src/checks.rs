@@ -0,0 +1,497 @@
+use std::fmt::Write;
+
+use crate::error::{Error, Result};
+use crate::report::{run_reported, CheckResult, Status};
+use crate::reporter::{CheckKind, Reporter, StdoutReporter};
+use crate::template::{Placeholders, TemplateOptions};
+
+/// A single check added to [`Checks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Check {
+    #[cfg(feature = "markdown_deps_updated")]
+    MarkdownDeps,
+    #[cfg(feature = "rustdoc_deps_updated")]
+    RustdocDeps,
+    #[cfg(feature = "rustdoc_deps_updated")]
+    RustdocLinks,
+    #[cfg(feature = "html_root_url_updated")]
+    HtmlRootUrl,
+    #[cfg(feature = "html_root_url_updated")]
+    DeprecatedSince,
+    ContainsSubstring(String),
+    #[cfg(feature = "contains_regex")]
+    ContainsRegex(String),
+    #[cfg(feature = "contains_regex")]
+    OnlyContainsRegex(String),
+    #[cfg(feature = "changelog")]
+    Changelog,
+}
+
+impl Check {
+    /// The kind of check reported for this check.
+    fn kind(&self) -> CheckKind {
+        match self {
+            #[cfg(feature = "markdown_deps_updated")]
+            Check::MarkdownDeps => CheckKind::MarkdownDeps,
+            #[cfg(feature = "rustdoc_deps_updated")]
+            Check::RustdocDeps => CheckKind::RustdocDeps,
+            #[cfg(feature = "rustdoc_deps_updated")]
+            Check::RustdocLinks => CheckKind::RustdocLinks,
+            #[cfg(feature = "html_root_url_updated")]
+            Check::HtmlRootUrl => CheckKind::HtmlRootUrl,
+            #[cfg(feature = "html_root_url_updated")]
+            Check::DeprecatedSince => CheckKind::DeprecatedSince,
+            Check::ContainsSubstring(_) => CheckKind::ContainsSubstring,
+            #[cfg(feature = "contains_regex")]
+            Check::ContainsRegex(_) => CheckKind::ContainsRegex,
+            #[cfg(feature = "contains_regex")]
+            Check::OnlyContainsRegex(_) => CheckKind::OnlyContainsRegex,
+            #[cfg(feature = "changelog")]
+            Check::Changelog => CheckKind::Changelog,
+        }
+    }
+}
+
+/// The outcome of running one check on one file with [`Checks`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct CheckOutcome {
+    /// The kind of check.
+    pub kind: CheckKind,
+    /// The file checked.
+    pub path: String,
+    /// The result of the check.
+    pub result: Result<()>,
+}
+
+/// A list of checks which are run together.
+///
+/// The `assert_*` macros panic on the first failing check. With
+/// `Checks`, all checks are run and the failures in all files are
+/// reported at once:
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_version_numbers is compiled.
+/// fn test_version_numbers() {
+///     version_sync::checks!()
+///         .contains_substring("README.md", "### Version {version}")
+///         .contains_substring("README.md", "{name}")
+///         .assert();
+/// }
+///
+/// # fn main() {
+/// #     test_version_numbers();
+/// # }
+/// ```
+///
+/// The [`checks`](crate::checks) macro creates the checks for the
+/// package being compiled. Use [`Checks::new`] to check another
+/// package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checks {
+    pkg_name: String,
+    pkg_version: String,
+    placeholders: Placeholders,
+    checks: Vec<(Check, String)>,
+}
+
+impl Checks {
+    /// Create an empty list of checks for the package `pkg_name` with
+    /// version `pkg_version`.
+    pub fn new(pkg_name: &str, pkg_version: &str) -> Checks {
+        Checks {
+            pkg_name: String::from(pkg_name),
+            pkg_version: String::from(pkg_version),
+            placeholders: Placeholders::new(),
+            checks: Vec::new(),
+        }
+    }
+
+    /// Use `placeholders` in the templates of the
+    /// [`contains_substring`](Checks::contains_substring) and regular
+    /// expression checks.
+    pub fn placeholders(mut self, placeholders: Placeholders) -> Checks {
+        self.placeholders = placeholders;
+        self
+    }
+
+    /// Add a check of `path`.
+    fn add(mut self, check: Check, path: &str) -> Checks {
+        self.checks.push((check, String::from(path)));
+        self
+    }
+
+    /// Check the dependencies in the Markdown file `path`, see
+    /// [`check_markdown_deps`](crate::check_markdown_deps).
+    ///
+    /// This method is enabled by the `markdown_deps_updated` feature.
+    #[cfg(feature = "markdown_deps_updated")]
+    pub fn markdown_deps(self, path: &str) -> Checks {
+        self.add(Check::MarkdownDeps, path)
+    }
+
+    /// Check the dependencies in the doc comments of the Rust file
+    /// `path`, see [`check_rustdoc_deps`](crate::check_rustdoc_deps).
+    ///
+    /// This method is enabled by the `rustdoc_deps_updated` feature.
+    #[cfg(feature = "rustdoc_deps_updated")]
+    pub fn rustdoc_deps(self, path: &str) -> Checks {
+        self.add(Check::RustdocDeps, path)
+    }
+
+    /// Check the links in the doc comments of the Rust file `path`,
+    /// see [`check_rustdoc_links`](crate::check_rustdoc_links).
+    ///
+    /// This method is enabled by the `rustdoc_deps_updated` feature.
+    #[cfg(feature = "rustdoc_deps_updated")]
+    pub fn rustdoc_links(self, path: &str) -> Checks {
+        self.add(Check::RustdocLinks, path)
+    }
+
+    /// Check the `html_root_url` attribute in the Rust file `path`,
+    /// see [`check_html_root_url`](crate::check_html_root_url).
+    ///
+    /// This method is enabled by the `html_root_url_updated` feature.
+    #[cfg(feature = "html_root_url_updated")]
+    pub fn html_root_url(self, path: &str) -> Checks {
+        self.add(Check::HtmlRootUrl, path)
+    }
+
+    /// Check the `deprecated` attributes in the Rust file `path`, see
+    /// [`check_deprecated_since`](crate::check_deprecated_since).
+    ///
+    /// This method is enabled by the `html_root_url_updated` feature.
+    #[cfg(feature = "html_root_url_updated")]
+    pub fn deprecated_since(self, path: &str) -> Checks {
+        self.add(Check::DeprecatedSince, path)
+    }
+
+    /// Check that `path` contains the expanded `template`, see
+    /// [`check_contains_substring`](crate::check_contains_substring).
+    pub fn contains_substring(self, path: &str, template: &str) -> Checks {
+        self.add(Check::ContainsSubstring(String::from(template)), path)
+    }
+
+    /// Check that `path` matches the regular expression `template`,
+    /// see [`check_contains_regex`](crate::check_contains_regex).
+    ///
+    /// This method is enabled by the `contains_regex` feature.
+    #[cfg(feature = "contains_regex")]
+    pub fn contains_regex(self, path: &str, template: &str) -> Checks {
+        self.add(Check::ContainsRegex(String::from(template)), path)
+    }
+
+    /// Check that all matches of `template` in `path` use the current
+    /// version, see
+    /// [`check_only_contains_regex`](crate::check_only_contains_regex).
+    ///
+    /// This method is enabled by the `contains_regex` feature.
+    #[cfg(feature = "contains_regex")]
+    pub fn only_contains_regex(self, path: &str, template: &str) -> Checks {
+        self.add(Check::OnlyContainsRegex(String::from(template)), path)
+    }
+
+    /// Check the release entries in the changelog `path`, see
+    /// [`check_changelog`](crate::check_changelog).
+    ///
+    /// This method is enabled by the `changelog` feature.
+    #[cfg(feature = "changelog")]
+    pub fn changelog(self, path: &str) -> Checks {
+        self.add(Check::Changelog, path)
+    }
+
+    /// Run a single check, fixing the file if `fix` is set and the
+    /// check supports it.
+    #[cfg_attr(
+        not(any(feature = "html_root_url_updated", feature = "markdown_deps_updated")),
+        allow(unused_variables)
+    )]
+    fn run_check(
+        &self,
+        check: &Check,
+        path: &str,
+        fix: bool,
+        reporter: &mut dyn Reporter,
+    ) -> Result<()> {
+        let name = self.pkg_name.as_str();
+        let version = self.pkg_version.as_str();
+        let placeholders = &self.placeholders;
+        match check {
+            #[cfg(feature = "markdown_deps_updated")]
+            Check::MarkdownDeps if fix => {
+                crate::fix_markdown_deps_with_reporter(path, name, version, reporter)
+            }
+            #[cfg(feature = "markdown_deps_updated")]
+            Check::MarkdownDeps => {
+                crate::check_markdown_deps_with_reporter(path, name, version, reporter)
+            }
+            #[cfg(feature = "rustdoc_deps_updated")]
+            Check::RustdocDeps => {
+                crate::check_rustdoc_deps_with_reporter(path, name, version, reporter)
+            }
+            #[cfg(feature = "rustdoc_deps_updated")]
+            Check::RustdocLinks => {
+                crate::check_rustdoc_links_with_reporter(path, name, version, reporter)
+            }
+            #[cfg(feature = "html_root_url_updated")]
+            Check::HtmlRootUrl if fix => {
+                crate::fix_html_root_url_with_reporter(path, name, version, reporter)
+            }
+            #[cfg(feature = "html_root_url_updated")]
+            Check::HtmlRootUrl => {
+                crate::check_html_root_url_with_reporter(path, name, version, reporter)
+            }
+            #[cfg(feature = "html_root_url_updated")]
+            Check::DeprecatedSince => {
+                crate::check_deprecated_since_with_reporter(path, version, reporter)
+            }
+            Check::ContainsSubstring(template) => crate::check_contains_substring_with_options(
+                path,
+                template,
+                name,
+                version,
+                TemplateOptions::new()
+                    .placeholders(placeholders)
+                    .reporter(reporter),
+            ),
+            #[cfg(feature = "contains_regex")]
+            Check::ContainsRegex(template) => crate::check_contains_regex_with_options(
+                path,
+                template,
+                name,
+                version,
+                TemplateOptions::new()
+                    .placeholders(placeholders)
+                    .reporter(reporter),
+            ),
+            #[cfg(feature = "contains_regex")]
+            Check::OnlyContainsRegex(template) => crate::check_only_contains_regex_with_options(
+                path,
+                template,
+                name,
+                version,
+                TemplateOptions::new()
+                    .placeholders(placeholders)
+                    .reporter(reporter),
+            ),
+            #[cfg(feature = "changelog")]
+            Check::Changelog => crate::check_changelog_with_reporter(path, version, reporter),
+        }
+    }
+
+    /// Run all checks in the order they were added.
+    fn run_checks(&self, fix: bool, reporter: &mut dyn Reporter) -> Vec<CheckOutcome> {
+        self.checks
+            .iter()
+            .map(|(check, path)| CheckOutcome {
+                kind: check.kind(),
+                path: path.clone(),
+                result: self.run_check(check, path, fix, reporter),
+            })
+            .collect()
+    }
+
+    /// Run all checks and return their outcomes.
+    ///
+    /// All checks are run, even if some of them fail. Status
+    /// information is printed on `stdout` while the checks run.
+    pub fn run(&self) -> Vec<CheckOutcome> {
+        self.run_with_reporter(&mut StdoutReporter)
+    }
+
+    /// Run all checks, send status information to `reporter`, and
+    /// return their outcomes.
+    ///
+    /// This works like [`Checks::run`], but nothing is printed on
+    /// `stdout`.
+    pub fn run_with_reporter(&self, reporter: &mut dyn Reporter) -> Vec<CheckOutcome> {
+        self.run_checks(false, reporter)
+    }
+
+    /// Run all checks and panic if any of them failed.
+    ///
+    /// Like the `assert_*` macros, the output format and file are
+    /// taken from the `VERSION_SYNC_FORMAT` and `VERSION_SYNC_OUTPUT`
+    /// environment variables, and the Markdown dependencies and
+    /// `html_root_url` attributes are fixed when `VERSION_SYNC_FIX` is
+    /// set.
+    ///
+    /// # Panics
+    ///
+    /// If any check fails, `panic!` is invoked once all checks have
+    /// run. The panic message is a table with every failed location
+    /// in every file.
+    pub fn assert(&self) {
+        #[cfg(any(feature = "html_root_url_updated", feature = "markdown_deps_updated"))]
+        let fix = crate::helpers::fix_requested();
+        #[cfg(not(any(feature = "html_root_url_updated", feature = "markdown_deps_updated")))]
+        let fix = false;
+
+        let mut outcomes = Vec::new();
+        if let Err(err) = run_reported(|reporter| {
+            outcomes = self.run_checks(fix, reporter);
+            Ok(())
+        }) {
+            panic!("{}", err);
+        }
+        if outcomes.iter().any(|outcome| outcome.result.is_err()) {
+            panic!("{}", summary(&outcomes));
+        }
+    }
+}
+
+/// Summarize `outcomes` in a table with a row for every passed check
+/// and every failed location.
+fn summary(outcomes: &[CheckOutcome]) -> String {
+    let mut rows = vec![[
+        String::from("status"),
+        String::from("check"),
+        String::from("location"),
+        String::from("problem"),
+    ]];
+    let mut failed = 0;
+    for outcome in outcomes {
+        let kind = outcome.kind.name();
+        let errors = match &outcome.result {
+            Ok(()) => {
+                let status = String::from("ok");
+                rows.push([status, kind.into(), outcome.path.clone(), String::new()]);
+                continue;
+            }
+            Err(Error::Failed { errors, .. }) => errors.iter().collect::<Vec<_>>(),
+            Err(err) => vec![err],
+        };
+        failed += 1;
+        for err in errors {
+            let result =
+                CheckResult::from_error(outcome.kind, &outcome.path, None, err, Status::Mismatch);
+            let location = match (result.line, result.column) {
+                (Some(line), Some(column)) => format!("{}:{line}:{column}", outcome.path),
+                (Some(line), None) => format!("{}:{line}", outcome.path),
+                _ => outcome.path.clone(),
+            };
+            let message = result.message.unwrap_or_default();
+            rows.push([String::from("FAILED"), kind.into(), location, message]);
+        }
+    }
+
+    let mut widths = [0; 4];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut table = match outcomes.len() {
+        1 => format!("{failed} of 1 check failed:\n\n"),
+        n => format!("{failed} of {n} checks failed:\n\n"),
+    };
+    for row in &rows {
+        let mut line = String::new();
+        for (cell, width) in row.iter().zip(widths) {
+            let _ = write!(line, "{cell:width$}  ");
+        }
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reporter::Silent;
+    use std::fs;
+
+    #[test]
+    fn all_checks_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let readme = dir.path().join("README.md");
+        fs::write(&readme, "Version 1.2.3 of foobar\n").unwrap();
+        let readme = readme.to_str().unwrap();
+        let missing = dir.path().join("CHANGELOG.md");
+        let missing = missing.to_str().unwrap();
+
+        let outcomes = Checks::new("foobar", "1.2.3")
+            .contains_substring(missing, "{version}")
+            .contains_substring(readme, "Version {version}")
+            .contains_substring(readme, "Version 2")
+            .run_with_reporter(&mut Silent);
+        let results = outcomes
+            .iter()
+            .map(|outcome| (outcome.kind, outcome.path.as_str(), outcome.result.is_ok()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            [
+                (CheckKind::ContainsSubstring, missing, false),
+                (CheckKind::ContainsSubstring, readme, true),
+                (CheckKind::ContainsSubstring, readme, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        let readme = dir.path().join("README.md");
+        fs::write(&readme, "Needs Rust 1.70\n").unwrap();
+        let mut placeholders = Placeholders::new();
+        placeholders.insert("msrv", "1.70");
+
+        let outcomes = Checks::new("foobar", "1.2.3")
+            .placeholders(placeholders)
+            .contains_substring(readme.to_str().unwrap(), "Rust {msrv}")
+            .run_with_reporter(&mut Silent);
+        assert!(outcomes[0].result.is_ok());
+    }
+
+    #[test]
+    fn summary_table() {
+        let mismatch = |line, message: &str| Error::Mismatch {
+            path: String::from("README.md"),
+            line,
+            column: 11,
+            found: String::from("1.1"),
+            expected: String::from("1.2.3"),
+            message: String::from(message),
+        };
+        let outcomes = [
+            CheckOutcome {
+                kind: CheckKind::ContainsSubstring,
+                path: String::from("README.md"),
+                result: Ok(()),
+            },
+            CheckOutcome {
+                kind: CheckKind::MarkdownDeps,
+                path: String::from("README.md"),
+                result: Err(Error::Failed {
+                    path: String::from("README.md"),
+                    errors: vec![
+                        mismatch(5, "expected minor version 2, found 1"),
+                        mismatch(12, "expected major version 1, found 0"),
+                    ],
+                }),
+            },
+            CheckOutcome {
+                kind: CheckKind::ContainsRegex,
+                path: String::from("CHANGELOG.md"),
+                result: Err(Error::NoMatch {
+                    path: String::from("CHANGELOG.md"),
+                    pattern: String::from("^## 1.2.3"),
+                }),
+            },
+        ];
+        assert_eq!(
+            summary(&outcomes),
+            "2 of 3 checks failed:\n\
+             \n\
+             status  check           location         problem\n\
+             ok      contains        README.md\n\
+             FAILED  markdown-deps   README.md:5:11   expected minor version 2, found 1\n\
+             FAILED  markdown-deps   README.md:12:11  expected major version 1, found 0\n\
+             FAILED  contains-regex  CHANGELOG.md     could not find \"^## 1.2.3\" in CHANGELOG.md\n"
+        );
+    }
+}
@@ -0,0 +1,491 @@
+#![cfg(feature = "config")]
+use std::path::Path;
+
+use toml_edit::{Item, TableLike};
+
+use crate::error::{Error, Result};
+use crate::helpers::{read_toml, resolve};
+use crate::html_root_url::DEFAULT_URL_TEMPLATE;
+use crate::manifest::Package;
+use crate::reporter::{Reporter, StdoutReporter};
+use crate::template::{Placeholders, TemplateOptions};
+use crate::{
+    check_changelog_with_reporter, check_contains_regex_with_options,
+    check_contains_substring_with_options, check_crate_docs_with_reporter,
+    check_deprecated_since_with_reporter, check_html_root_url_with_options,
+    check_markdown_deps_with_reporter, check_only_contains_regex_with_options,
+    check_rustdoc_deps_with_reporter, check_rustdoc_links_with_reporter,
+    fix_html_root_url_with_options, fix_markdown_deps_with_reporter, UrlOptions,
+};
+
+/// Name of the stand-alone configuration file.
+const CONFIG_FILE: &str = "version-sync.toml";
+
+/// A single configured check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Check {
+    kind: String,
+    files: Vec<String>,
+    template: Option<String>,
+}
+
+/// Find the configuration for the package in `dir`.
+///
+/// Returns the path of the file with the configuration and the
+/// configured checks.
+fn read_config(dir: &str) -> Result<(String, Vec<Check>)> {
+    let config_path = resolve(dir, CONFIG_FILE);
+    if Path::new(&config_path).is_file() {
+        let document = read_toml(&config_path)?;
+        let checks = parse_checks(&config_path, document.get("checks"), "checks")?;
+        return Ok((config_path, checks));
+    }
+
+    let manifest_path = resolve(dir, "Cargo.toml");
+    let document = read_toml(&manifest_path)?;
+    let checks = document
+        .get("package")
+        .and_then(|package| package.get("metadata"))
+        .and_then(|metadata| metadata.get("version-sync"))
+        .and_then(|config| config.get("checks"));
+    let checks = parse_checks(
+        &manifest_path,
+        checks,
+        "package.metadata.version-sync.checks",
+    )?;
+    Ok((manifest_path, checks))
+}
+
+/// Parse the array of check tables found under `key`.
+fn parse_checks(path: &str, item: Option<&Item>, key: &str) -> Result<Vec<Check>> {
+    let config_error = |message: String| Error::Config {
+        path: String::from(path),
+        message,
+    };
+
+    let tables: Vec<&dyn TableLike> = match item {
+        None => return Err(config_error(format!("no checks configured in {key}"))),
+        Some(Item::ArrayOfTables(array)) => array.iter().map(|t| t as &dyn TableLike).collect(),
+        Some(item) => match item.as_array() {
+            Some(array) => array
+                .iter()
+                .map(|value| {
+                    value
+                        .as_inline_table()
+                        .map(|t| t as &dyn TableLike)
+                        .ok_or_else(|| config_error(format!("{key} must be an array of tables")))
+                })
+                .collect::<Result<_>>()?,
+            None => return Err(config_error(format!("{key} must be an array of tables"))),
+        },
+    };
+    if tables.is_empty() {
+        return Err(config_error(format!("no checks configured in {key}")));
+    }
+
+    tables
+        .into_iter()
+        .enumerate()
+        .map(|(idx, table)| {
+            let string = |name: &str| -> Result<Option<String>> {
+                match table.get(name) {
+                    None => Ok(None),
+                    Some(item) => item.as_str().map(|s| Some(String::from(s))).ok_or_else(|| {
+                        config_error(format!("{key}[{idx}].{name} must be a string"))
+                    }),
+                }
+            };
+
+            let kind =
+                string("kind")?.ok_or_else(|| config_error(format!("{key}[{idx}] has no kind")))?;
+            let template = string("template")?;
+            match (kind.as_str(), &template) {
+                (
+                    "markdown-deps" | "rustdoc-deps" | "rustdoc-links" | "crate-docs"
+                    | "html-root-url" | "deprecated-since" | "changelog",
+                    _,
+                ) => {}
+                ("contains" | "contains-regex" | "only-contains-regex", Some(_)) => {}
+                ("contains" | "contains-regex" | "only-contains-regex", None) => {
+                    return Err(config_error(format!("{key}[{idx}] has no template")))
+                }
+                _ => {
+                    return Err(config_error(format!(
+                        "{key}[{idx}] has unknown kind \"{kind}\""
+                    )))
+                }
+            }
+
+            let files = table
+                .get("files")
+                .and_then(|files| files.as_array())
+                .and_then(|files| {
+                    files
+                        .iter()
+                        .map(|file| file.as_str().map(String::from))
+                        .collect::<Option<Vec<_>>>()
+                })
+                .filter(|files| !files.is_empty())
+                .ok_or_else(|| {
+                    config_error(format!("{key}[{idx}].files must be an array of file names"))
+                })?;
+
+            Ok(Check {
+                kind,
+                files,
+                template,
+            })
+        })
+        .collect()
+}
+
+/// Run all checks configured for the package in `dir`.
+fn run_checks(dir: &str, fix: bool, reporter: &mut dyn Reporter) -> Result<()> {
+    let manifest_path = resolve(dir, "Cargo.toml");
+    let manifest = read_toml(&manifest_path)?;
+    let package = Package::from_document(&manifest_path, &manifest)?;
+    let placeholders = Placeholders::from_document(&manifest_path, &manifest)?;
+    let (config_path, checks) = read_config(dir)?;
+    let name = package.name.as_str();
+    let version = package.version.as_str();
+
+    let mut errors = Vec::new();
+    for check in &checks {
+        let template = check.template.as_deref().unwrap_or_default();
+        for file in &check.files {
+            let path = resolve(dir, file);
+            let result = match (check.kind.as_str(), fix) {
+                ("markdown-deps", false) => {
+                    check_markdown_deps_with_reporter(&path, name, version, reporter)
+                }
+                ("markdown-deps", true) => {
+                    fix_markdown_deps_with_reporter(&path, name, version, reporter)
+                }
+                ("rustdoc-deps", _) => {
+                    check_rustdoc_deps_with_reporter(&path, name, version, reporter)
+                }
+                ("rustdoc-links", _) => {
+                    check_rustdoc_links_with_reporter(&path, name, version, reporter)
+                }
+                ("crate-docs", _) => check_crate_docs_with_reporter(&path, name, version, reporter),
+                ("html-root-url", false) => check_html_root_url_with_options(
+                    &path,
+                    name,
+                    version,
+                    UrlOptions::new()
+                        .template(check.template.as_deref().unwrap_or(DEFAULT_URL_TEMPLATE))
+                        .reporter(reporter),
+                ),
+                ("html-root-url", true) => fix_html_root_url_with_options(
+                    &path,
+                    name,
+                    version,
+                    UrlOptions::new()
+                        .template(check.template.as_deref().unwrap_or(DEFAULT_URL_TEMPLATE))
+                        .reporter(reporter),
+                ),
+                ("deprecated-since", _) => {
+                    check_deprecated_since_with_reporter(&path, version, reporter)
+                }
+                ("changelog", _) => check_changelog_with_reporter(&path, version, reporter),
+                ("contains", _) => check_contains_substring_with_options(
+                    &path,
+                    template,
+                    name,
+                    version,
+                    TemplateOptions::new()
+                        .placeholders(&placeholders)
+                        .reporter(reporter),
+                ),
+                ("contains-regex", _) => check_contains_regex_with_options(
+                    &path,
+                    template,
+                    name,
+                    version,
+                    TemplateOptions::new()
+                        .placeholders(&placeholders)
+                        .reporter(reporter),
+                ),
+                _ => check_only_contains_regex_with_options(
+                    &path,
+                    template,
+                    name,
+                    version,
+                    TemplateOptions::new()
+                        .placeholders(&placeholders)
+                        .reporter(reporter),
+                ),
+            };
+            if let Err(err) = result {
+                errors.push(err);
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Failed {
+            path: config_path,
+            errors,
+        })
+    }
+}
+
+/// Run all checks configured for the package in `dir`.
+///
+/// The package name and version are read from `Cargo.toml` in `dir`.
+/// The checks are read from a `version-sync.toml` file in `dir` if it
+/// exists, and otherwise from the `[package.metadata.version-sync]`
+/// table in `Cargo.toml`. Either way, the checks are given as an
+/// array of tables named `checks`:
+///
+/// ```toml
+/// [[package.metadata.version-sync.checks]]
+/// kind = "markdown-deps"
+/// files = ["README.md"]
+///
+/// [[package.metadata.version-sync.checks]]
+/// kind = "contains-regex"
+/// files = ["CHANGELOG.md"]
+/// template = "^## Version {version}"
+/// ```
+///
+/// The `kind` is one of `markdown-deps`, `rustdoc-deps`,
+/// `rustdoc-links`, `crate-docs`, `html-root-url`, `deprecated-since`,
+/// `changelog`, `contains`, `contains-regex`, and
+/// `only-contains-regex`, corresponding to [`check_markdown_deps`],
+/// [`check_rustdoc_deps`], [`check_rustdoc_links`],
+/// [`check_crate_docs`], [`check_html_root_url`],
+/// [`check_deprecated_since`], [`check_changelog`],
+/// [`check_contains_substring`], [`check_contains_regex`], and
+/// [`check_only_contains_regex`]. The last three need a `template`,
+/// which can use the placeholders from `Cargo.toml` described in
+/// [`Placeholders::from_manifest`], such as `{msrv}`.
+/// An `html-root-url` check can have a URL `template` as well, see
+/// [`check_html_root_url_with_options`]. The `files` are relative to
+/// `dir`.
+///
+/// All checks are run, even if some of them fail. This function is
+/// used by [`assert_all_from_config`].
+///
+/// [`check_markdown_deps`]: crate::check_markdown_deps
+/// [`check_rustdoc_deps`]: crate::check_rustdoc_deps
+/// [`check_rustdoc_links`]: crate::check_rustdoc_links
+/// [`check_crate_docs`]: crate::check_crate_docs
+/// [`check_html_root_url`]: crate::check_html_root_url
+/// [`check_deprecated_since`]: crate::check_deprecated_since
+/// [`check_changelog`]: crate::check_changelog
+/// [`check_html_root_url_with_options`]: crate::check_html_root_url_with_options
+/// [`check_contains_substring`]: crate::check_contains_substring
+/// [`check_contains_regex`]: crate::check_contains_regex
+/// [`check_only_contains_regex`]: crate::check_only_contains_regex
+/// [`assert_all_from_config`]: crate::assert_all_from_config
+///
+/// # Errors
+///
+/// An [`Error::Config`] is returned if the configuration is invalid.
+/// If any check fails, an [`Error::Failed`] is returned with the
+/// error from each failed check. Status information has then already
+/// been printed on `stdout`.
+pub fn check_config(dir: &str) -> Result<()> {
+    check_config_with_reporter(dir, &mut StdoutReporter)
+}
+
+/// Run all configured checks and send status information to
+/// `reporter`.
+///
+/// This works like [`check_config`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If any check fails, an [`Error::Failed`] is returned with the error
+/// from each failed check.
+pub fn check_config_with_reporter(dir: &str, reporter: &mut dyn Reporter) -> Result<()> {
+    run_checks(dir, false, reporter)
+}
+
+/// Run all configured checks, fixing outdated version numbers where
+/// possible.
+///
+/// This works like [`check_config`], but `markdown-deps` and
+/// `html-root-url` checks are done with [`fix_markdown_deps`] and
+/// [`fix_html_root_url`].
+///
+/// [`fix_markdown_deps`]: crate::fix_markdown_deps
+/// [`fix_html_root_url`]: crate::fix_html_root_url
+///
+/// # Errors
+///
+/// If any check fails and cannot be fixed, an [`Error::Failed`] is
+/// returned with the error from each failed check.
+pub fn fix_config(dir: &str) -> Result<()> {
+    fix_config_with_reporter(dir, &mut StdoutReporter)
+}
+
+/// Run all configured checks, fixing outdated version numbers where
+/// possible, and send status information to `reporter`.
+///
+/// This works like [`fix_config`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If any check fails and cannot be fixed, an [`Error::Failed`] is
+/// returned with the error from each failed check.
+pub fn fix_config_with_reporter(dir: &str, reporter: &mut dyn Reporter) -> Result<()> {
+    run_checks(dir, true, reporter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reporter::Silent;
+    use std::fs;
+
+    fn package(manifest_extra: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest =
+            format!("[package]\nname = \"foobar\"\nversion = \"1.2.3\"\n{manifest_extra}");
+        fs::write(dir.path().join("Cargo.toml"), manifest).unwrap();
+        fs::write(
+            dir.path().join("README.md"),
+            "Version 1.2.3\n\n```toml\n[dependencies]\nfoobar = \"1.1\"\n```\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn checks_from_manifest() {
+        let dir = package(
+            "[[package.metadata.version-sync.checks]]\n\
+             kind = \"contains\"\n\
+             files = [\"README.md\"]\n\
+             template = \"Version {version}\"\n",
+        );
+        let dir = dir.path().to_str().unwrap();
+        assert!(check_config_with_reporter(dir, &mut Silent).is_ok());
+    }
+
+    #[test]
+    fn checks_from_config_file() {
+        let dir = package("");
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            "checks = [\n  { kind = \"markdown-deps\", files = [\"README.md\"] },\n  \
+             { kind = \"contains\", files = [\"README.md\"], template = \"Version {version}\" },\n  \
+             { kind = \"contains\", files = [\"README.md\"], template = \"Version 0.0\" },\n]\n",
+        )
+        .unwrap();
+        let dir = dir.path().to_str().unwrap();
+        match check_config_with_reporter(dir, &mut Silent) {
+            Err(Error::Failed { path, errors }) => {
+                assert_eq!(path, resolve(dir, CONFIG_FILE));
+                assert_eq!(errors.len(), 2);
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+
+        assert!(fix_config_with_reporter(dir, &mut Silent).is_err());
+        let readme = fs::read_to_string(resolve(dir, "README.md")).unwrap();
+        assert!(readme.contains("foobar = \"1.2\""));
+    }
+
+    #[test]
+    fn manifest_placeholders() {
+        let dir = package(
+            "rust-version = \"1.70\"\n\n\
+             [package.metadata]\n\
+             channel = \"stable\"\n\n\
+             [[package.metadata.version-sync.checks]]\n\
+             kind = \"contains-regex\"\n\
+             files = [\"rust.md\"]\n\
+             template = \"^Rust {msrv} \\\\({metadata.channel}\\\\)$\"\n",
+        );
+        fs::write(dir.path().join("rust.md"), "Rust 1.70 (stable)\n").unwrap();
+        let dir = dir.path().to_str().unwrap();
+        assert!(check_config_with_reporter(dir, &mut Silent).is_ok());
+    }
+
+    #[test]
+    fn html_root_url_template() {
+        let dir = package(
+            "[[package.metadata.version-sync.checks]]\n\
+             kind = \"html-root-url\"\n\
+             files = [\"lib.rs\"]\n\
+             template = \"https://docs.example.net/{name}/{version}\"\n",
+        );
+        fs::write(
+            dir.path().join("lib.rs"),
+            "#![doc(html_root_url = \"https://docs.example.net/foobar/1.0.0\")]\n",
+        )
+        .unwrap();
+        let dir = dir.path().to_str().unwrap();
+        assert!(check_config_with_reporter(dir, &mut Silent).is_err());
+        assert!(fix_config_with_reporter(dir, &mut Silent).is_ok());
+        assert!(check_config_with_reporter(dir, &mut Silent).is_ok());
+    }
+
+    #[test]
+    fn no_checks() {
+        let dir = package("");
+        let dir = dir.path().to_str().unwrap();
+        assert_eq!(
+            check_config_with_reporter(dir, &mut Silent).map_err(|err| err.to_string()),
+            Err(format!(
+                "invalid configuration in {}: no checks configured in \
+                 package.metadata.version-sync.checks",
+                resolve(dir, "Cargo.toml")
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_checks() {
+        let parse = |text: &str| {
+            let document: toml_edit::ImDocument<_> = text.parse().unwrap();
+            parse_checks("config.toml", document.get("checks"), "checks")
+                .map_err(|err| err.to_string())
+        };
+        assert_eq!(
+            parse("checks = 10"),
+            Err(String::from(
+                "invalid configuration in config.toml: checks must be an array of tables"
+            ))
+        );
+        assert_eq!(
+            parse("[[checks]]\nfiles = ['README.md']"),
+            Err(String::from(
+                "invalid configuration in config.toml: checks[0] has no kind"
+            ))
+        );
+        assert_eq!(
+            parse("[[checks]]\nkind = 'foo'\nfiles = ['README.md']"),
+            Err(String::from(
+                "invalid configuration in config.toml: checks[0] has unknown kind \"foo\""
+            ))
+        );
+        assert_eq!(
+            parse("[[checks]]\nkind = 'contains'\nfiles = ['README.md']"),
+            Err(String::from(
+                "invalid configuration in config.toml: checks[0] has no template"
+            ))
+        );
+        assert_eq!(
+            parse("[[checks]]\nkind = 'markdown-deps'\nfiles = 'README.md'"),
+            Err(String::from(
+                "invalid configuration in config.toml: checks[0].files must be an array of file names"
+            ))
+        );
+        assert_eq!(
+            parse("[[checks]]\nkind = 'markdown-deps'\nfiles = ['README.md']"),
+            Ok(vec![Check {
+                kind: String::from("markdown-deps"),
+                files: vec![String::from("README.md")],
+                template: None,
+            }])
+        );
+    }
+}
@@ -1,21 +1,35 @@
 #![cfg(feature = "contains_regex")]
 use regex::{escape, Regex, RegexBuilder};
-use semver::{Version, VersionReq};
+use semver::VersionReq;
 
-use crate::helpers::{read_file, version_matches_request, Result};
+use crate::error::{Error, Result};
+use crate::helpers::{
+    line_column, parse_version, read_path, source_lines, version_matches_request,
+};
+use crate::reporter::{CheckKind, Reporter, StdoutReporter};
+use crate::template::{expand, Placeholders, TemplateOptions};
 
 /// Matches a full or partial SemVer version number.
 const SEMVER_RE: &str = concat!(
-    r"(?P<major>0|[1-9]\d*)",
-    r"(?:\.(?P<minor>0|[1-9]\d*)",
-    r"(?:\.(?P<patch>0|[1-9]\d*)",
-    r"(?:-(?P<prerelease>(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)",
+    r"(?:0|[1-9]\d*)",
+    r"(?:\.(?:0|[1-9]\d*)",
+    r"(?:\.(?:0|[1-9]\d*)",
+    r"(?:-(?:(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)",
     r"(?:\.(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?",
-    r"(?:\+(?P<buildmetadata>[0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?",
+    r"(?:\+(?:[0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?",
     r")?", // Close patch plus prerelease and buildmetadata.
     r")?", // Close minor.
 );
 
+/// Matches a major version number.
+const MAJOR_RE: &str = r"(?:0|[1-9]\d*)";
+
+/// Matches a major and minor version number.
+const MAJOR_MINOR_RE: &str = r"(?:0|[1-9]\d*)\.(?:0|[1-9]\d*)";
+
+/// Prefix for the names of the capture groups with version numbers.
+const VERSION_GROUP: &str = "version";
+
 /// Check that `path` contain the regular expression given by
 /// `template`.
 ///
@@ -25,7 +39,10 @@ const SEMVER_RE: &str = concat!(
 ///
 /// The placeholders `{name}` and `{version}` will be replaced with
 /// `pkg_name` and `pkg_version`, if they are present in `template`.
-/// It is okay if `template` do not contain these placeholders.
+/// It is okay if `template` do not contain these placeholders. The
+/// other placeholders, such as `{major}` and `{date}`, are listed in
+/// the [crate documentation](crate#templates). The values are escaped
+/// so that they only match themselves.
 ///
 /// The matching is done in multi-line mode, which means that `^` in
 /// the regular expression will match the beginning of any line in the
@@ -33,34 +50,96 @@ const SEMVER_RE: &str = concat!(
 ///
 /// # Errors
 ///
-/// If the regular expression cannot be found, an `Err` is returned
-/// with a succinct error message. Status information has then already
-/// been printed on `stdout`.
+/// If the regular expression cannot be found, an [`Error::NoMatch`]
+/// is returned. Status information has then already been printed on
+/// `stdout`.
 pub fn check_contains_regex(
     path: &str,
     template: &str,
     pkg_name: &str,
     pkg_version: &str,
 ) -> Result<()> {
-    // Expand the placeholders in the template.
-    let pattern = template
-        .replace("{name}", &escape(pkg_name))
-        .replace("{version}", &escape(pkg_version));
+    check_contains_regex_with_reporter(path, template, pkg_name, pkg_version, &mut StdoutReporter)
+}
+
+/// Check that `path` contain the regular expression given by
+/// `template` and send status information to `reporter`.
+///
+/// This works like [`check_contains_regex`], but nothing is printed
+/// on `stdout`.
+///
+/// # Errors
+///
+/// If the regular expression cannot be found, an [`Error::NoMatch`]
+/// is returned.
+pub fn check_contains_regex_with_reporter(
+    path: &str,
+    template: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    check_contains_regex_with_options(
+        path,
+        template,
+        pkg_name,
+        pkg_version,
+        TemplateOptions::new().reporter(reporter),
+    )
+}
+
+/// Check that `path` contain the regular expression given by
+/// `template` with the extra placeholders and the reporter in
+/// `options`.
+///
+/// This works like [`check_contains_regex`], but the `template` can
+/// also use user-defined placeholders, such as `{msrv}`, and status
+/// information can be sent to a [`Reporter`]. The values of the
+/// placeholders are escaped as well.
+///
+/// # Errors
+///
+/// If the regular expression cannot be found, an [`Error::NoMatch`]
+/// is returned.
+pub fn check_contains_regex_with_options(
+    path: &str,
+    template: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    options: TemplateOptions,
+) -> Result<()> {
+    options.run(|placeholders, reporter| {
+        // Expand the placeholders in the template.
+        let pattern = expand(template, pkg_name, pkg_version, placeholders, |_, value| {
+            escape(value)
+        });
+
+        reporter.check_started(CheckKind::ContainsRegex, path, Some(&pattern));
+        let result = find_pattern(path, pattern, reporter);
+        reporter.check_finished(CheckKind::ContainsRegex, path, &result);
+        result
+    })
+}
+
+/// Search for the regular expression `pattern` in `path`.
+fn find_pattern(path: &str, pattern: String, reporter: &mut dyn Reporter) -> Result<()> {
     let mut builder = RegexBuilder::new(&pattern);
     builder.multi_line(true);
-    let re = builder
-        .build()
-        .map_err(|err| format!("could not parse template: {err}"))?;
-    let text = read_file(path).map_err(|err| format!("could not read {path}: {err}"))?;
+    let re = builder.build().map_err(|err| Error::Template {
+        message: err.to_string(),
+    })?;
+    let text = read_path(path)?;
 
-    println!("Searching for \"{pattern}\" in {path}...");
     match re.find(&text) {
         Some(m) => {
             let line_no = text[..m.start()].lines().count();
-            println!("{} (line {}) ... ok", path, line_no + 1);
+            reporter.location_ok(path, line_no + 1);
             Ok(())
         }
-        None => Err(format!("could not find \"{pattern}\" in {path}")),
+        None => Err(Error::NoMatch {
+            path: String::from(path),
+            pattern,
+        }),
     }
 }
 
@@ -77,13 +156,17 @@ pub fn check_contains_regex(
 /// 1. Replace `{version}` in `template` by a regular expression which
 ///    will match _any_ SemVer version number. This allows, say,
 ///    `"docs.rs/{name}/{version}/"` to match old and outdated
-///    occurrences of your package.
+///    occurrences of your package. Likewise, `{major}` and
+///    `{major_minor}` match any major version or major and minor
+///    version. The other placeholders are replaced by their values.
 ///
-/// 2. Find all matches in the file and check the version number in
-///    each match for compatibility with `pkg_version`. It is enough
-///    for the version number to be compatible, meaning that
-///    `"foo/{version}/bar" matches `"foo/1.2/bar"` when `pkg_version`
-///    is `"1.2.3"`.
+/// 2. Find all matches in the file and check the version numbers
+///    matched by these placeholders for compatibility with
+///    `pkg_version`. It is enough for the version number to be
+///    compatible, meaning that `"foo/{version}/bar" matches
+///    `"foo/1.2/bar"` when `pkg_version` is `"1.2.3"`. If `template`
+///    has no placeholders other than `{name}` and `{version}`, every
+///    version number in the matches is checked instead.
 ///
 /// It is an error if there are no matches for `template` at all.
 ///
@@ -94,66 +177,195 @@ pub fn check_contains_regex(
 /// # Errors
 ///
 /// If any of the matches are incompatible with `pkg_version`, an
-/// `Err` is returned with a succinct error message. Status
-/// information has then already been printed on `stdout`.
+/// [`Error::Failed`] is returned with an [`Error::Mismatch`] per
+/// incompatible version number. Status information has then already
+/// been printed on `stdout`.
 pub fn check_only_contains_regex(
     path: &str,
     template: &str,
     pkg_name: &str,
     pkg_version: &str,
 ) -> Result<()> {
-    let version = Version::parse(pkg_version)
-        .map_err(|err| format!("bad package version {pkg_version:?}: {err}"))?;
+    check_only_contains_regex_with_reporter(
+        path,
+        template,
+        pkg_name,
+        pkg_version,
+        &mut StdoutReporter,
+    )
+}
+
+/// Check that `path` only contains matches to the regular expression
+/// given by `template` and send status information to `reporter`.
+///
+/// This works like [`check_only_contains_regex`], but nothing is
+/// printed on `stdout`.
+///
+/// # Errors
+///
+/// If any of the matches are incompatible with `pkg_version`, an
+/// [`Error::Failed`] is returned with an [`Error::Mismatch`] per
+/// incompatible version number.
+pub fn check_only_contains_regex_with_reporter(
+    path: &str,
+    template: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    check_only_contains_regex_with_options(
+        path,
+        template,
+        pkg_name,
+        pkg_version,
+        TemplateOptions::new().reporter(reporter),
+    )
+}
 
-    let pattern = template
-        .replace("{name}", &escape(pkg_name))
-        .replace("{version}", SEMVER_RE);
+/// Check that `path` only contains matches to the regular expression
+/// given by `template` with the extra placeholders and the reporter
+/// in `options`.
+///
+/// This works like [`check_only_contains_regex`], but the `template`
+/// can also use user-defined placeholders, such as `{msrv}`, and
+/// status information can be sent to a [`Reporter`].
+///
+/// # Errors
+///
+/// If any of the matches are incompatible with `pkg_version`, an
+/// [`Error::Failed`] is returned with an [`Error::Mismatch`] per
+/// incompatible version number.
+pub fn check_only_contains_regex_with_options(
+    path: &str,
+    template: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    options: TemplateOptions,
+) -> Result<()> {
+    options.run(|placeholders, reporter| {
+        reporter.check_started(CheckKind::OnlyContainsRegex, path, Some(template));
+        let result = check_matches(
+            path,
+            template,
+            pkg_name,
+            pkg_version,
+            placeholders,
+            reporter,
+        );
+        reporter.check_finished(CheckKind::OnlyContainsRegex, path, &result);
+        result
+    })
+}
+
+/// Check the version number in all matches of `template` in `path`.
+fn check_matches(
+    path: &str,
+    template: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    placeholders: &Placeholders,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let version = parse_version(pkg_version)?;
+
+    // Each version number gets its own capture group so that we only
+    // check the version numbers and not other numbers in the match.
+    let mut groups = 0;
+    let mut only_version = true;
+    let pattern = expand(
+        template,
+        pkg_name,
+        pkg_version,
+        placeholders,
+        |name, value| {
+            only_version &= name == "name" || name == "version";
+            let re = match name {
+                "version" => SEMVER_RE,
+                "major" => MAJOR_RE,
+                "major_minor" => MAJOR_MINOR_RE,
+                _ => return escape(value),
+            };
+            groups += 1;
+            format!("(?P<{VERSION_GROUP}{groups}>{re})")
+        },
+    );
     let re = RegexBuilder::new(&pattern)
         .multi_line(true)
         .build()
-        .map_err(|err| format!("could not parse template: {err}"))?;
-
+        .map_err(|err| Error::Template {
+            message: err.to_string(),
+        })?;
+    let group_names = re
+        .capture_names()
+        .flatten()
+        .filter(|name| name.starts_with(VERSION_GROUP))
+        .collect::<Vec<_>>();
     let semver_re = Regex::new(SEMVER_RE).unwrap();
 
-    let text = read_file(path).map_err(|err| format!("could not read {path}: {err}"))?;
+    let text = read_path(path)?;
 
-    println!("Searching for \"{template}\" in {path}...");
-    let mut errors = 0;
+    let mut errors = Vec::new();
     let mut has_match = false;
 
-    for m in re.find_iter(&text) {
+    for captures in re.captures_iter(&text) {
         has_match = true;
-        let line_no = text[..m.start()].lines().count() + 1;
+        let m = captures.get(0).unwrap();
+        let (line_no, _) = line_column(&text, m.start());
+
+        // Templates with only {name} and {version} have no other
+        // numbers in the match, so all version numbers are checked.
+        let semvers = if only_version {
+            semver_re
+                .find_iter(m.as_str())
+                .map(|semver| (m.start() + semver.start(), semver.as_str()))
+                .collect::<Vec<_>>()
+        } else {
+            group_names
+                .iter()
+                .filter_map(|name| captures.name(name))
+                .map(|semver| (semver.start(), semver.as_str()))
+                .collect()
+        };
 
-        for semver in semver_re.find_iter(m.as_str()) {
-            let semver_request = VersionReq::parse(semver.as_str())
-                .map_err(|err| format!("could not parse version: {err}"))?;
-            let result = version_matches_request(&version, &semver_request);
+        for (start, semver) in semvers {
+            let result = VersionReq::parse(semver)
+                .map_err(|err| format!("could not parse version: {err}"))
+                .and_then(|request| version_matches_request(&version, &request));
             match result {
                 Err(err) => {
-                    errors += 1;
-                    println!(
-                        "{} (line {}) ... found \"{}\", which does not match version \"{}\": {}",
-                        path,
-                        line_no,
-                        semver.as_str(),
-                        pkg_version,
-                        err
-                    );
-                }
-                Ok(()) => {
-                    println!("{path} (line {line_no}) ... ok");
+                    let (line, column) = line_column(&text, start);
+                    let err = Error::Mismatch {
+                        path: String::from(path),
+                        line,
+                        column,
+                        found: String::from(semver),
+                        expected: String::from(pkg_version),
+                        message: format!(
+                            "found \"{}\", which does not match version \"{}\": {}",
+                            semver, pkg_version, err
+                        ),
+                    };
+                    let source = source_lines(&text, line_no, line);
+                    reporter.location_mismatch(path, line_no, &err, source);
+                    errors.push(err);
                 }
+                Ok(()) => reporter.location_ok(path, line_no),
             }
         }
     }
 
     if !has_match {
-        return Err(format!("{path} ... found no matches for \"{template}\""));
+        return Err(Error::NoMatch {
+            path: String::from(path),
+            pattern: String::from(template),
+        });
     }
 
-    if errors > 0 {
-        return Err(format!("{path} ... found {errors} errors"));
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
     }
 
     Ok(())
@@ -169,7 +381,8 @@ mod tests {
         // Check that the error from a bad pattern doesn't contain
         // the (?m) prefix.
         assert_eq!(
-            check_contains_regex("README.md", "Version {version} [ups", "foobar", "1.2.3"),
+            check_contains_regex("README.md", "Version {version} [ups", "foobar", "1.2.3")
+                .map_err(|err| err.to_string()),
             Err([
                 r"could not parse template: regex parse error:",
                 r"    Version 1\.2\.3 [ups",
@@ -183,7 +396,8 @@ mod tests {
     #[test]
     fn not_found() {
         assert_eq!(
-            check_contains_regex("README.md", "should not be found", "foobar", "1.2.3"),
+            check_contains_regex("README.md", "should not be found", "foobar", "1.2.3")
+                .map_err(|err| err.to_string()),
             Err(String::from(
                 "could not find \"should not be found\" in README.md"
             ))
@@ -198,7 +412,8 @@ mod tests {
                 "escaped: {name}-{version}, not escaped: foo*bar-1.2.3",
                 "foo*bar",
                 "1.2.3"
-            ),
+            )
+            .map_err(|err| err.to_string()),
             Err([
                 r#"could not find "escaped: foo\*bar-1\.2\.3,"#,
                 r#"not escaped: foo*bar-1.2.3" in README.md"#
@@ -210,7 +425,8 @@ mod tests {
     #[test]
     fn good_pattern() {
         assert_eq!(
-            check_contains_regex("README.md", "{name}", "version-sync", "1.2.3"),
+            check_contains_regex("README.md", "{name}", "version-sync", "1.2.3")
+                .map_err(|err| err.to_string()),
             Ok(())
         )
     }
@@ -226,7 +442,8 @@ mod tests {
         file.write_all(b"first line\r\nsecond line\r\nthird line\r\n")
             .unwrap();
         assert_eq!(
-            check_contains_regex(file.path().to_str().unwrap(), "^second line$", "", ""),
+            check_contains_regex(file.path().to_str().unwrap(), "^second line$", "", "")
+                .map_err(|err| err.to_string()),
             Ok(())
         )
     }
@@ -261,7 +478,8 @@ mod tests {
                 "docs.rs/{name}/{version}/{name}/",
                 "foo",
                 "1.2.3"
-            ),
+            )
+            .map_err(|err| err.to_string()),
             Ok(())
         )
     }
@@ -281,7 +499,8 @@ mod tests {
                 "docs.rs/{name}/{version}/{name}/",
                 "foo",
                 "1.2.3"
-            ),
+            )
+            .map_err(|err| err.to_string()),
             Ok(())
         )
     }
@@ -302,11 +521,55 @@ mod tests {
                 "docs.rs/{name}/{version}/{name}/",
                 "foo",
                 "2.0.0"
-            ),
+            )
+            .map_err(|err| err.to_string()),
             Err(format!("{} ... found 2 errors", file.path().display()))
         )
     }
 
+    #[test]
+    fn only_contains_without_placeholders() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"foo = \"1.2\"\nfoo = \"0.9\"\n").unwrap();
+
+        let path = file.path().to_str().unwrap();
+        let result = check_only_contains_regex(path, r#"foo = "[\d.]+""#, "foo", "1.2.3");
+        match result {
+            Err(Error::Failed { errors, .. }) => match &errors[..] {
+                [Error::Mismatch {
+                    line,
+                    column,
+                    found,
+                    ..
+                }] => assert_eq!((*line, *column, found.as_str()), (2, 8, "0.9")),
+                errors => panic!("unexpected errors: {errors:?}"),
+            },
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn only_contains_all_versions_in_match() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"foo 2.0.0 replaces foo 1.0.0\n").unwrap();
+
+        let path = file.path().to_str().unwrap();
+        let template = r"{name} {version} replaces {name} [\d.]+";
+        let result = check_only_contains_regex(path, template, "foo", "2.0.0");
+        match result {
+            Err(Error::Failed { errors, .. }) => match &errors[..] {
+                [Error::Mismatch {
+                    line,
+                    column,
+                    found,
+                    ..
+                }] => assert_eq!((*line, *column, found.as_str()), (1, 24, "1.0.0")),
+                errors => panic!("unexpected errors: {errors:?}"),
+            },
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+
     #[test]
     fn only_contains_fails_if_no_match() {
         let mut file = tempfile::NamedTempFile::new().unwrap();
@@ -318,11 +581,46 @@ mod tests {
                 "docs.rs/{name}/{version}/{name}/",
                 "foo",
                 "1.2.3"
-            ),
+            )
+            .map_err(|err| err.to_string()),
             Err(format!(
-                r#"{} ... found no matches for "docs.rs/{{name}}/{{version}}/{{name}}/""#,
+                r#"could not find "docs.rs/{{name}}/{{version}}/{{name}}/" in {}"#,
                 file.path().display()
             ))
         );
     }
+
+    #[test]
+    fn escaped_placeholders() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"foo 1.2.3 needs Rust 1x70").unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let mut placeholders = Placeholders::new();
+        placeholders.insert("msrv", "1.70");
+        assert_eq!(
+            check_contains_regex_with_options(
+                path,
+                "Rust {msrv}",
+                "foo",
+                "1.2.3",
+                TemplateOptions::new().placeholders(&placeholders)
+            )
+            .map_err(|err| err.to_string()),
+            Err(format!(r#"could not find "Rust 1\.70" in {path}"#))
+        );
+
+        placeholders.insert("msrv", "1x70");
+        assert_eq!(
+            check_only_contains_regex_with_options(
+                path,
+                "{name} {version} needs Rust {msrv}",
+                "foo",
+                "1.2.3",
+                TemplateOptions::new().placeholders(&placeholders)
+            )
+            .map_err(|err| err.to_string()),
+            Ok(())
+        );
+    }
 }
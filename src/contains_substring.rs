@@ -1,56 +1,129 @@
-use crate::helpers::{read_file, Result};
+use crate::error::{Error, Result};
+use crate::helpers::read_path;
+use crate::reporter::{CheckKind, Reporter, StdoutReporter};
+use crate::template::{expand, TemplateOptions};
 
 /// Check that `path` contain the substring given by `template`.
 ///
 /// The placeholders `{name}` and `{version}` will be replaced with
 /// `pkg_name` and `pkg_version`, if they are present in `template`.
-/// It is okay if `template` do not contain these placeholders.
+/// It is okay if `template` do not contain these placeholders. The
+/// parts of the version can be used as well, e.g., `{major}` and
+/// `{major_minor}`, and so can today's date with `{date}`. See the
+/// [crate documentation](crate#templates) for the full list.
 ///
 /// See [`check_contains_regex`](crate::check_contains_regex) if you
 /// want to match with a regular expression instead.
 ///
 /// # Errors
 ///
-/// If the template cannot be found, an `Err` is returned with a
-/// succinct error message. Status information has then already been
-/// printed on `stdout`.
+/// If the template cannot be found, an [`Error::NoMatch`] is
+/// returned. Status information has then already been printed on
+/// `stdout`.
 pub fn check_contains_substring(
     path: &str,
     template: &str,
     pkg_name: &str,
     pkg_version: &str,
 ) -> Result<()> {
-    // Expand the optional {name} and {version} placeholders in the
-    // template. This is almost like
-    //
-    //   format!(template, name = pkg_name, version = pkg_version)
-    //
-    // but allows the user to leave out unnecessary placeholders.
-    let pattern = template
-        .replace("{name}", pkg_name)
-        .replace("{version}", pkg_version);
+    check_contains_substring_with_reporter(
+        path,
+        template,
+        pkg_name,
+        pkg_version,
+        &mut StdoutReporter,
+    )
+}
+
+/// Check that `path` contain the substring given by `template` and
+/// send status information to `reporter`.
+///
+/// This works like [`check_contains_substring`], but nothing is
+/// printed on `stdout`.
+///
+/// # Errors
+///
+/// If the template cannot be found, an [`Error::NoMatch`] is
+/// returned.
+pub fn check_contains_substring_with_reporter(
+    path: &str,
+    template: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    check_contains_substring_with_options(
+        path,
+        template,
+        pkg_name,
+        pkg_version,
+        TemplateOptions::new().reporter(reporter),
+    )
+}
+
+/// Check that `path` contain the substring given by `template` with
+/// the extra placeholders and the reporter in `options`.
+///
+/// This works like [`check_contains_substring`], but the `template`
+/// can also use user-defined placeholders, such as `{msrv}`, and
+/// status information can be sent to a [`Reporter`].
+///
+/// # Errors
+///
+/// If the template cannot be found, an [`Error::NoMatch`] is
+/// returned.
+pub fn check_contains_substring_with_options(
+    path: &str,
+    template: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    options: TemplateOptions,
+) -> Result<()> {
+    options.run(|placeholders, reporter| {
+        // Expand the optional placeholders in the template. This is
+        // almost like
+        //
+        //   format!(template, name = pkg_name, version = pkg_version)
+        //
+        // but allows the user to leave out unnecessary placeholders.
+        let pattern = expand(template, pkg_name, pkg_version, placeholders, |_, value| {
+            String::from(value)
+        });
 
-    let text = read_file(path).map_err(|err| format!("could not read {path}: {err}"))?;
+        reporter.check_started(CheckKind::ContainsSubstring, path, Some(&pattern));
+        let result = find_substring(path, pattern, reporter);
+        reporter.check_finished(CheckKind::ContainsSubstring, path, &result);
+        result
+    })
+}
+
+/// Search for `pattern` in `path`.
+fn find_substring(path: &str, pattern: String, reporter: &mut dyn Reporter) -> Result<()> {
+    let text = read_path(path)?;
 
-    println!("Searching for \"{pattern}\" in {path}...");
     match text.find(&pattern) {
         Some(idx) => {
             let line_no = text[..idx].lines().count();
-            println!("{} (line {}) ... ok", path, line_no + 1);
+            reporter.location_ok(path, line_no + 1);
             Ok(())
         }
-        None => Err(format!("could not find \"{pattern}\" in {path}")),
+        None => Err(Error::NoMatch {
+            path: String::from(path),
+            pattern,
+        }),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::template::Placeholders;
 
     #[test]
     fn pattern_not_found() {
         assert_eq!(
-            check_contains_substring("README.md", "should not be found", "foobar", "1.2.3"),
+            check_contains_substring("README.md", "should not be found", "foobar", "1.2.3")
+                .map_err(|err| err.to_string()),
             Err(String::from(
                 "could not find \"should not be found\" in README.md"
             ))
@@ -60,7 +133,25 @@ mod tests {
     #[test]
     fn pattern_found() {
         assert_eq!(
-            check_contains_substring("README.md", "{name}", "version-sync", "1.2.3"),
+            check_contains_substring("README.md", "{name}", "version-sync", "1.2.3")
+                .map_err(|err| err.to_string()),
+            Ok(())
+        )
+    }
+
+    #[test]
+    fn user_defined_placeholder() {
+        let mut placeholders = Placeholders::new();
+        placeholders.insert("license", "MIT");
+        assert_eq!(
+            check_contains_substring_with_options(
+                "Cargo.toml",
+                "license = \"{license}\"",
+                "version-sync",
+                "1.2.3",
+                TemplateOptions::new().placeholders(&placeholders)
+            )
+            .map_err(|err| err.to_string()),
             Ok(())
         )
     }
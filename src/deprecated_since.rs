@@ -0,0 +1,376 @@
+#![cfg(feature = "html_root_url_updated")]
+use std::path::Path;
+
+use semver::Version;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+use crate::error::{Error, Result};
+use crate::helpers::{parse_rust_file, parse_version, rust_files};
+use crate::reporter::{CheckKind, Reporter, StdoutReporter};
+
+/// A `deprecated` attribute found in a Rust file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Deprecated {
+    /// The value of the `since` key and the line and column where it
+    /// starts, if any.
+    since: Option<(String, usize, usize)>,
+    /// The error from parsing the attribute and the line and column
+    /// where it was found, if any.
+    error: Option<(String, usize, usize)>,
+    /// Line number of the start of the attribute, starting with 1.
+    line: usize,
+    /// Column of the start of the attribute, starting with 0.
+    column: usize,
+}
+
+/// Collects all `deprecated` attributes in a file.
+#[derive(Default)]
+struct DeprecatedCollector {
+    attrs: Vec<Deprecated>,
+}
+
+impl<'ast> Visit<'ast> for DeprecatedCollector {
+    fn visit_attribute(&mut self, attr: &'ast syn::Attribute) {
+        if !attr.path().is_ident("deprecated") {
+            return;
+        }
+
+        let mut since = None;
+        let mut error = None;
+        if let syn::Meta::List(ref list) = attr.meta {
+            // Other keys, such as note, can have any expression as
+            // value, so only since must be a string literal.
+            let result = list.parse_nested_meta(|meta| {
+                if meta.path.is_ident("since") {
+                    let lit = meta.value()?.parse::<syn::LitStr>()?;
+                    let start = lit.span().start();
+                    since = Some((lit.value(), start.line, start.column));
+                } else if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<syn::Expr>()?;
+                }
+                Ok(())
+            });
+            if let Err(err) = result {
+                let start = err.span().start();
+                error = Some((err.to_string(), start.line, start.column));
+            }
+        }
+
+        let start = attr.span().start();
+        self.attrs.push(Deprecated {
+            since,
+            error,
+            line: start.line,
+            column: start.column,
+        });
+    }
+}
+
+/// Find all `deprecated` attributes in `krate`.
+fn find_deprecated(krate: &syn::File) -> Vec<Deprecated> {
+    let mut collector = DeprecatedCollector::default();
+    collector.visit_file(krate);
+    collector.attrs
+}
+
+/// Check a single `deprecated` attribute.
+fn check_attribute(path: &str, attr: &Deprecated, version: &Version, required: bool) -> Result<()> {
+    if let Some((err, line, column)) = &attr.error {
+        return Err(Error::Mismatch {
+            path: String::from(path),
+            line: *line,
+            column: column + 1,
+            found: String::new(),
+            expected: version.to_string(),
+            message: format!("could not parse deprecated attribute: {err}"),
+        });
+    }
+    let (since, line, column) = match &attr.since {
+        Some(since) => since.clone(),
+        None if required => {
+            return Err(Error::Mismatch {
+                path: String::from(path),
+                line: attr.line,
+                column: attr.column + 1,
+                found: String::new(),
+                expected: version.to_string(),
+                message: String::from("deprecated attribute without since"),
+            })
+        }
+        None => return Ok(()),
+    };
+
+    let message = match Version::parse(&since) {
+        Ok(since) if since <= *version => return Ok(()),
+        Ok(_) => format!("deprecated since {since}, which is after version {version}"),
+        Err(err) => format!("could not parse version in since: {err}"),
+    };
+    Err(Error::Mismatch {
+        path: String::from(path),
+        line,
+        column: column + 1,
+        found: since,
+        expected: version.to_string(),
+        message,
+    })
+}
+
+/// Check the `deprecated` attributes in the Rust file in `path`.
+fn check_file(
+    path: &str,
+    pkg_version: &str,
+    required: bool,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let version = parse_version(pkg_version)?;
+    let (code, krate) = parse_rust_file(path)?;
+
+    let mut errors = Vec::new();
+    for attr in find_deprecated(&krate) {
+        if attr.since.is_none() && attr.error.is_none() && !required {
+            continue;
+        }
+        match check_attribute(path, &attr, &version, required) {
+            Ok(()) => reporter.location_ok(path, attr.line),
+            Err(err) => {
+                let source = code.lines().nth(attr.line - 1).unwrap_or_default();
+                reporter.location_mismatch(path, attr.line, &err, source);
+                errors.push(err);
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
+    }
+    Ok(())
+}
+
+/// Check a single Rust file and send status information to
+/// `reporter`.
+fn check_file_with_reporter(
+    path: &str,
+    pkg_version: &str,
+    required: bool,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    reporter.check_started(CheckKind::DeprecatedSince, path, None);
+    let result = check_file(path, pkg_version, required, reporter);
+    reporter.check_finished(CheckKind::DeprecatedSince, path, &result);
+    result
+}
+
+/// Check the Rust file or all Rust files in the directory in `path`.
+fn check_path(
+    path: &str,
+    pkg_version: &str,
+    required: bool,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let files = if Path::new(path).is_dir() {
+        rust_files(Path::new(path))?
+    } else {
+        return check_file_with_reporter(path, pkg_version, required, reporter);
+    };
+
+    let mut errors = Vec::new();
+    for file in files {
+        let file = file.to_string_lossy();
+        errors.extend(check_file_with_reporter(&file, pkg_version, required, reporter).err());
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
+    }
+    Ok(())
+}
+
+/// Check the `since` version in `deprecated` attributes.
+///
+/// The `path` is either a Rust source file or a directory, such as
+/// `src`, in which case all `.rs` files in the directory and its
+/// subdirectories are checked. Every attribute such as
+///
+/// ```rust
+/// #[deprecated(since = "0.9.0", note = "use `bar` instead")]
+/// pub fn foo() {}
+/// ```
+///
+/// must have a `since` value which is a valid SemVer version number
+/// no greater than `pkg_version`. A version in the future is usually
+/// a typo or an item deprecated ahead of the release which will
+/// deprecate it. Attributes without `since` are accepted, use
+/// [`check_deprecated_since_required`] to reject them.
+///
+/// # Errors
+///
+/// If any attribute fails the check, an [`Error::Failed`] is returned
+/// with an [`Error::Mismatch`] per attribute. When `path` is a
+/// directory, the error has an [`Error::Failed`] per file. Status
+/// information has then already been printed on `stdout`.
+pub fn check_deprecated_since(path: &str, pkg_version: &str) -> Result<()> {
+    check_deprecated_since_with_reporter(path, pkg_version, &mut StdoutReporter)
+}
+
+/// Check the `since` version in `deprecated` attributes and send
+/// status information to `reporter`.
+///
+/// This works like [`check_deprecated_since`], but nothing is printed
+/// on `stdout`.
+///
+/// # Errors
+///
+/// If any attribute fails the check, an [`Error::Failed`] is
+/// returned.
+pub fn check_deprecated_since_with_reporter(
+    path: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    check_path(path, pkg_version, false, reporter)
+}
+
+/// Check the `since` version in `deprecated` attributes and require
+/// it on every attribute.
+///
+/// This works like [`check_deprecated_since`], except that a
+/// `deprecated` attribute without a `since` key fails the check.
+///
+/// # Errors
+///
+/// If any attribute fails the check, an [`Error::Failed`] is returned
+/// with an [`Error::Mismatch`] per attribute. Status information has
+/// then already been printed on `stdout`.
+pub fn check_deprecated_since_required(path: &str, pkg_version: &str) -> Result<()> {
+    check_deprecated_since_required_with_reporter(path, pkg_version, &mut StdoutReporter)
+}
+
+/// Check the `since` version in `deprecated` attributes, require it
+/// on every attribute, and send status information to `reporter`.
+///
+/// This works like [`check_deprecated_since_required`], but nothing
+/// is printed on `stdout`.
+///
+/// # Errors
+///
+/// If any attribute fails the check, an [`Error::Failed`] is
+/// returned.
+pub fn check_deprecated_since_required_with_reporter(
+    path: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    check_path(path, pkg_version, true, reporter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reporter::Silent;
+    use std::io::Write;
+
+    fn errors(code: &str, pkg_version: &str, required: bool) -> Vec<(usize, usize, String)> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(code.as_bytes()).unwrap();
+        let path = file.path().to_str().unwrap();
+        match check_path(path, pkg_version, required, &mut Silent) {
+            Ok(()) => Vec::new(),
+            Err(Error::Failed { errors, .. }) => errors
+                .iter()
+                .map(|err| match err {
+                    Error::Mismatch {
+                        line,
+                        column,
+                        message,
+                        ..
+                    } => (*line, *column, message.clone()),
+                    err => panic!("unexpected error: {err:?}"),
+                })
+                .collect(),
+            Err(err) => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    const CODE: &str = "#[deprecated(since = \"0.8.0\", note = \"old\")]\n\
+                        pub fn foo() {}\n\
+                        pub struct Bar {\n\
+                        \x20   #[deprecated(note = \"old\", since = \"0.9.0\")]\n\
+                        \x20   pub baz: u32,\n\
+                        }\n\
+                        #[deprecated]\n\
+                        pub fn qux() {}\n\
+                        #[deprecated(since = \"0.9\")]\n\
+                        pub fn quux() {}\n";
+
+    #[test]
+    fn future_and_invalid_versions() {
+        assert_eq!(
+            errors(CODE, "0.8.1", false),
+            vec![
+                (
+                    4,
+                    40,
+                    String::from("deprecated since 0.9.0, which is after version 0.8.1")
+                ),
+                (
+                    9,
+                    22,
+                    String::from(
+                        "could not parse version in since: \
+                         unexpected end of input while parsing minor version number"
+                    )
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn current_version() {
+        assert_eq!(errors(CODE, "0.9.0", false).len(), 1);
+    }
+
+    #[test]
+    fn expression_values() {
+        let code = "#[deprecated(note = concat!(\"a\", \"b\"), since = \"9.9.9\")]\n\
+                    pub fn foo() {}\n";
+        assert_eq!(
+            errors(code, "1.0.0", false),
+            vec![(
+                1,
+                48,
+                String::from("deprecated since 9.9.9, which is after version 1.0.0")
+            )]
+        );
+    }
+
+    #[test]
+    fn unparsable_since() {
+        let code = "#[deprecated(since = concat!(\"1.\", \"0.0\"))]\n\
+                    pub fn foo() {}\n";
+        assert_eq!(
+            errors(code, "1.0.0", false),
+            vec![(
+                1,
+                22,
+                String::from("could not parse deprecated attribute: expected string literal")
+            )]
+        );
+    }
+
+    #[test]
+    fn required_since() {
+        let errors = errors(CODE, "0.9.0", true);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0],
+            (7, 1, String::from("deprecated attribute without since"))
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use crate::error::Error;
+
+/// ANSI escape codes for the parts of a diagnostic.
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Check if diagnostics on `stdout` should use colors.
+///
+/// This is the case when `stdout` is a terminal and the `NO_COLOR`
+/// environment variable is unset or empty.
+pub fn use_color() -> bool {
+    let no_color = env::var_os("NO_COLOR").filter(|value| !value.is_empty());
+    io::stdout().is_terminal() && no_color.is_none()
+}
+
+/// Wrap `text` in the ANSI escape code `style` if `color` is set.
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("{style}{text}{RESET}")
+    } else {
+        String::from(text)
+    }
+}
+
+/// Render a rustc-style diagnostic for a mismatch in `source_line`.
+///
+/// The `found` text is underlined, starting at the 1-based `column`.
+/// If `found` is not at `column`, such as when `column` points at the
+/// quote before a string, the first occurrence after `column` is
+/// underlined instead.
+#[allow(clippy::too_many_arguments)]
+fn render(
+    path: &str,
+    line: usize,
+    column: usize,
+    found: &str,
+    expected: &str,
+    message: &str,
+    source_line: &str,
+    color: bool,
+) -> String {
+    let chars = source_line.chars().collect::<Vec<_>>();
+    let start = column.saturating_sub(1).min(chars.len());
+    let found_chars = found.chars().collect::<Vec<_>>();
+    let (start, width) = match (start..=chars.len())
+        .find(|&idx| !found_chars.is_empty() && chars[idx..].starts_with(&found_chars))
+    {
+        Some(idx) => (idx, found_chars.len()),
+        None => (start, 1),
+    };
+
+    // Keep tabs in the padding so the carets line up with the source.
+    let padding = chars[..start]
+        .iter()
+        .map(|&c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    let number = line.to_string();
+    let gutter = " ".repeat(number.len());
+    let bar = paint("|", BLUE, color);
+
+    let mut lines = vec![
+        format!(
+            "{}{}",
+            paint("error", RED, color),
+            paint(&format!(": {message}"), BOLD, color)
+        ),
+        format!(
+            "{gutter}{} {path}:{line}:{}",
+            paint("-->", BLUE, color),
+            start + 1
+        ),
+        format!("{gutter} {bar}"),
+        format!(
+            "{} {bar} {}",
+            paint(&number, BLUE, color),
+            source_line.trim_end()
+        ),
+        format!(
+            "{gutter} {bar} {padding}{}",
+            paint(&"^".repeat(width), RED, color)
+        ),
+    ];
+    if !expected.is_empty() {
+        lines.push(format!("{gutter} {bar}"));
+        lines.push(format!(
+            "{}{}",
+            paint("help", CYAN, color),
+            paint(&format!(": replace with `{expected}`"), BOLD, color)
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Render a rustc-style diagnostic for `error`.
+///
+/// The `source` is the source code of the location starting at
+/// `first_line`, as given to [`Reporter::location_mismatch`]. Returns
+/// `None` unless `error` is an [`Error::Mismatch`] whose line is part
+/// of `source`.
+///
+/// [`Reporter::location_mismatch`]: crate::Reporter::location_mismatch
+pub fn mismatch_diagnostic(
+    error: &Error,
+    first_line: usize,
+    source: &str,
+    color: bool,
+) -> Option<String> {
+    match error {
+        Error::Mismatch {
+            path,
+            line,
+            column,
+            found,
+            expected,
+            message,
+        } => {
+            let source_line = source.lines().nth(line.checked_sub(first_line)?)?;
+            Some(render(
+                path,
+                *line,
+                *column,
+                found,
+                expected,
+                message,
+                source_line,
+                color,
+            ))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn underline_found() {
+        assert_eq!(
+            render(
+                "README.md",
+                12,
+                10,
+                "0.1",
+                "0.2.0",
+                "expected minor version 2, found 1",
+                "foobar = \"0.1\"",
+                false
+            ),
+            "error: expected minor version 2, found 1\n  \
+             --> README.md:12:11\n   \
+             |\n\
+             12 | foobar = \"0.1\"\n   \
+             |           ^^^\n   \
+             |\n\
+             help: replace with `0.2.0`"
+        );
+    }
+
+    #[test]
+    fn tabs_and_missing_text() {
+        let rendered = render("a.rs", 3, 2, "1.0", "", "bad", "\tx = 1", false);
+        assert_eq!(
+            rendered,
+            "error: bad\n --> a.rs:3:2\n  |\n3 | \tx = 1\n  | \t^"
+        );
+    }
+
+    #[test]
+    fn colors() {
+        let rendered = render("a.md", 1, 1, "1.0", "2.0.0", "bad", "1.0", true);
+        assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m\x1b[1m: bad\x1b[0m\n"));
+        assert!(rendered.contains("\x1b[1;31m^^^\x1b[0m"));
+    }
+
+    #[test]
+    fn other_errors() {
+        let err = Error::NoMatch {
+            path: String::from("README.md"),
+            pattern: String::from("foo"),
+        };
+        assert_eq!(mismatch_diagnostic(&err, 1, "foo", false), None);
+    }
+
+    #[test]
+    fn line_from_source() {
+        let err = Error::Mismatch {
+            path: String::from("README.md"),
+            line: 11,
+            column: 10,
+            found: String::from("0.1"),
+            expected: String::from("0.2.0"),
+            message: String::from("bad"),
+        };
+        let source = "[dependencies]\n  foobar = \"0.1\"\n";
+        assert_eq!(
+            mismatch_diagnostic(&err, 10, source, false).unwrap(),
+            "error: bad\n  \
+             --> README.md:11:13\n   \
+             |\n\
+             11 |   foobar = \"0.1\"\n   \
+             |             ^^^\n   \
+             |\n\
+             help: replace with `0.2.0`"
+        );
+        assert_eq!(mismatch_diagnostic(&err, 12, source, false), None);
+        assert_eq!(mismatch_diagnostic(&err, 1, source, false), None);
+    }
+}
@@ -0,0 +1,201 @@
+use std::{error, fmt, io};
+
+/// The result type used by the check functions.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors returned by the check functions.
+///
+/// The `Display` implementation gives a succinct error message. For
+/// errors tied to a location in a file, such as [`Error::Mismatch`],
+/// the message does not repeat the location: use the fields to find
+/// the file, line, and column.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A file could not be read.
+    Io {
+        /// The file we tried to read.
+        path: String,
+        /// The underlying I/O error.
+        source: io::Error,
+    },
+    /// A file could not be written while fixing version numbers.
+    Write {
+        /// The file we tried to write.
+        path: String,
+        /// The underlying I/O error.
+        source: io::Error,
+    },
+    /// The package version is not a valid SemVer version number.
+    PackageVersion {
+        /// The package version given.
+        version: String,
+        /// Description of the parse error.
+        message: String,
+    },
+    /// A template could not be turned into a regular expression.
+    Template {
+        /// Description of the parse error.
+        message: String,
+    },
+    /// A TOML code block could not be parsed.
+    Toml {
+        /// The file with the code block.
+        path: String,
+        /// Line number of the error, starting with 1.
+        line: usize,
+        /// Description of the parse error.
+        message: String,
+    },
+    /// A `Cargo.toml` file lacks required information.
+    Manifest {
+        /// The manifest file.
+        path: String,
+        /// Description of the problem.
+        message: String,
+    },
+    /// The version-sync configuration is invalid.
+    Config {
+        /// The file with the configuration.
+        path: String,
+        /// Description of the problem.
+        message: String,
+    },
+    /// A Rust source file could not be parsed.
+    Rust {
+        /// The file we tried to parse.
+        path: String,
+        /// Description of the parse error.
+        message: String,
+    },
+    /// A file contains no match for a template.
+    NoMatch {
+        /// The file searched.
+        path: String,
+        /// The expanded template we searched for.
+        pattern: String,
+    },
+    /// A Rust source file lacks a required attribute.
+    MissingAttribute {
+        /// The file searched.
+        path: String,
+        /// The name of the attribute, such as `html_root_url`.
+        name: String,
+    },
+    /// A TOML code block has no dependency on the package.
+    MissingDependency {
+        /// The file with the code block.
+        path: String,
+        /// Line number of the code block, starting with 1.
+        line: usize,
+        /// The name of the package.
+        pkg_name: String,
+    },
+    /// A version number does not match the package version.
+    Mismatch {
+        /// The file with the version number.
+        path: String,
+        /// Line number of the version number, starting with 1.
+        line: usize,
+        /// Column of the version number, starting with 1.
+        column: usize,
+        /// The version number or URL found in the file.
+        found: String,
+        /// The version number or URL we expected to find.
+        expected: String,
+        /// Description of the mismatch.
+        message: String,
+    },
+    /// The output format is unknown.
+    Format {
+        /// The format given, such as the value of the
+        /// `VERSION_SYNC_FORMAT` environment variable.
+        format: String,
+    },
+    /// One or more locations in a file failed the check.
+    Failed {
+        /// The file checked.
+        path: String,
+        /// The errors found, in the order they appear in the file.
+        errors: Vec<Error>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "could not read {path}: {source}"),
+            Error::Write { path, source } => write!(f, "could not write {path}: {source}"),
+            Error::PackageVersion { version, message } => {
+                write!(f, "bad package version {version:?}: {message}")
+            }
+            Error::Template { message } => write!(f, "could not parse template: {message}"),
+            Error::Toml { message, .. } => f.write_str(message),
+            Error::Manifest { path, message } => write!(f, "invalid manifest {path}: {message}"),
+            Error::Config { path, message } => {
+                write!(f, "invalid configuration in {path}: {message}")
+            }
+            Error::Rust { path, message } => write!(f, "could not parse {path}: {message}"),
+            Error::NoMatch { path, pattern } => write!(f, "could not find \"{pattern}\" in {path}"),
+            Error::MissingAttribute { path, name } => {
+                write!(f, "could not find {name} attribute in {path}")
+            }
+            Error::MissingDependency { pkg_name, .. } => write!(f, "no dependency on {pkg_name}"),
+            Error::Mismatch { message, .. } => f.write_str(message),
+            Error::Format { format } => write!(
+                f,
+                "unknown output format \"{format}\", expected text, json, sarif, github, or gitlab"
+            ),
+            Error::Failed { path, errors } => match errors.len() {
+                1 => write!(f, "{path} ... found 1 error"),
+                n => write!(f, "{path} ... found {n} errors"),
+            },
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } | Error::Write { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_source() {
+        let err = Error::Io {
+            path: String::from("README.md"),
+            source: io::Error::new(io::ErrorKind::NotFound, "not found"),
+        };
+        assert_eq!(err.to_string(), "could not read README.md: not found");
+        assert!(error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn failed_count() {
+        let mismatch = || Error::Mismatch {
+            path: String::from("README.md"),
+            line: 10,
+            column: 5,
+            found: String::from("1.0"),
+            expected: String::from("2.0.0"),
+            message: String::from("expected major version 2, found 1"),
+        };
+        let err = Error::Failed {
+            path: String::from("README.md"),
+            errors: vec![mismatch()],
+        };
+        assert_eq!(err.to_string(), "README.md ... found 1 error");
+        let err = Error::Failed {
+            path: String::from("README.md"),
+            errors: vec![mismatch(), mismatch()],
+        };
+        assert_eq!(err.to_string(), "README.md ... found 2 errors");
+    }
+}
@@ -1,10 +1,8 @@
 use std::fs::File;
 use std::io::{self, Read};
 
-/// The common result type, our errors will be simple strings.
-pub type Result<T> = std::result::Result<T, String>;
+use crate::error::{Error, Result};
 
-#[cfg(any(feature = "html_root_url_updated", feature = "markdown_deps_updated"))]
 fn join<T>(iter: T, sep: &str) -> String
 where
     T: IntoIterator,
@@ -36,12 +34,247 @@ pub fn read_file(path: &str) -> io::Result<String> {
     Ok(buf.replace("\r\n", "\n"))
 }
 
+/// Read `path` like [`read_file`], but return an [`Error`] which
+/// mentions the path on failure.
+pub fn read_path(path: &str) -> Result<String> {
+    read_file(path).map_err(|source| Error::Io {
+        path: String::from(path),
+        source,
+    })
+}
+
+/// Read and parse the TOML file at `path`.
+#[cfg(any(
+    feature = "cli",
+    feature = "config",
+    feature = "workspace",
+    feature = "html_root_url_updated"
+))]
+pub fn read_toml(path: &str) -> Result<toml_edit::ImDocument<String>> {
+    let text = read_path(path)?;
+    toml_edit::ImDocument::parse(text.clone()).map_err(|err| Error::Toml {
+        path: String::from(path),
+        line: line_column(&text, err.span().map_or(0, |span| span.start)).0,
+        message: String::from(err.message().trim_end()),
+    })
+}
+
+/// Find `file` relative to `dir`.
+#[cfg(any(
+    feature = "config",
+    feature = "workspace",
+    feature = "html_root_url_updated"
+))]
+pub fn resolve(dir: &str, file: &str) -> String {
+    if dir.is_empty() || dir == "." {
+        String::from(file)
+    } else {
+        std::path::Path::new(dir)
+            .join(file)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Find all Rust files in `dir` and its subdirectories, sorted by
+/// path.
+#[cfg(any(feature = "rustdoc_deps_updated", feature = "html_root_url_updated"))]
+pub fn rust_files(dir: &std::path::Path) -> Result<Vec<std::path::PathBuf>> {
+    let io_error = |source| Error::Io {
+        path: dir.to_string_lossy().into_owned(),
+        source,
+    };
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        if path.is_dir() {
+            files.extend(rust_files(&path)?);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Read and parse the Rust file in `path`.
+#[cfg(any(feature = "rustdoc_deps_updated", feature = "html_root_url_updated"))]
+pub fn parse_rust_file(path: &str) -> Result<(String, syn::File)> {
+    let code = read_path(path)?;
+    let krate = syn::parse_file(&code).map_err(|err| Error::Rust {
+        path: String::from(path),
+        message: err.to_string(),
+    })?;
+    Ok((code, krate))
+}
+
+/// Parse the package version.
+#[cfg(any(
+    feature = "html_root_url_updated",
+    feature = "markdown_deps_updated",
+    feature = "contains_regex",
+    feature = "changelog"
+))]
+pub fn parse_version(pkg_version: &str) -> Result<semver::Version> {
+    semver::Version::parse(pkg_version).map_err(|err| Error::PackageVersion {
+        version: String::from(pkg_version),
+        message: err.to_string(),
+    })
+}
+
+/// Find the line and column of the byte `offset` in `text`. Both are
+/// counted from 1 and the column is counted in characters.
+#[cfg(any(
+    feature = "markdown_deps_updated",
+    feature = "html_root_url_updated",
+    feature = "contains_regex",
+    feature = "changelog"
+))]
+pub fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    let line = before.matches('\n').count() + 1;
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// Find the lines from `first_line` to `last_line` in `text`. Both
+/// are counted from 1. The lines are not trimmed, but the line ending
+/// of the last line is left out.
+#[cfg(any(
+    feature = "markdown_deps_updated",
+    feature = "html_root_url_updated",
+    feature = "contains_regex",
+    feature = "changelog"
+))]
+pub fn source_lines(text: &str, first_line: usize, last_line: usize) -> &str {
+    let start = text
+        .split_inclusive('\n')
+        .take(first_line.saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>();
+    let len = text[start..]
+        .split_inclusive('\n')
+        .take((last_line + 1).saturating_sub(first_line))
+        .map(str::len)
+        .sum::<usize>();
+    let lines = &text[start..start + len];
+    let lines = lines.strip_suffix('\n').unwrap_or(lines);
+    lines.strip_suffix('\r').unwrap_or(lines)
+}
+
+/// Find the byte range of the contents of a TOML string literal
+/// which occupies `span` in `text`.
+#[cfg(feature = "markdown_deps_updated")]
+pub fn string_contents(text: &str, span: std::ops::Range<usize>) -> std::ops::Range<usize> {
+    let literal = &text[span.clone()];
+    let quotes = if literal.starts_with("\"\"\"") || literal.starts_with("'''") {
+        3
+    } else {
+        1
+    };
+    span.start + quotes..span.end - quotes
+}
+
+/// Dependency tables recognized by Cargo.
+#[cfg(feature = "markdown_deps_updated")]
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Find all dependency tables in a TOML document, including the
+/// target-specific tables and `[workspace.dependencies]`.
+#[cfg(feature = "markdown_deps_updated")]
+pub fn dependency_tables(document: &toml_edit::Table) -> Vec<&toml_edit::Item> {
+    let mut tables: Vec<&toml_edit::Item> = DEPENDENCY_TABLES
+        .iter()
+        .filter_map(|name| document.get(name))
+        .collect();
+    if let Some(targets) = document.get("target").and_then(|item| item.as_table_like()) {
+        for (_, target) in targets.iter() {
+            tables.extend(DEPENDENCY_TABLES.iter().filter_map(|name| target.get(name)));
+        }
+    }
+    if let Some(dependencies) = document
+        .get("workspace")
+        .and_then(|workspace| workspace.get("dependencies"))
+    {
+        tables.push(dependencies);
+    }
+    tables
+}
+
 /// Indent every line in text by four spaces.
-#[cfg(any(feature = "html_root_url_updated", feature = "markdown_deps_updated"))]
 pub fn indent(text: &str) -> String {
     join(text.lines().map(|line| String::from("    ") + line), "\n")
 }
 
+/// Check if the user asked us to fix outdated version numbers.
+///
+/// This is the case when the `VERSION_SYNC_FIX` environment variable
+/// is set to something other than an empty string or `0`.
+#[cfg(any(feature = "html_root_url_updated", feature = "markdown_deps_updated"))]
+pub fn fix_requested() -> bool {
+    std::env::var_os("VERSION_SYNC_FIX").is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Update a version requirement so that it matches `version`.
+///
+/// Each comparator in `request` which is checked by
+/// [`version_matches_request`] is rewritten to use `version`. The
+/// operator and the number of version components are preserved, so
+/// `"^0.1"` becomes `"^0.2"` and `"0.1.2"` becomes `"0.2.0"` when
+/// `version` is 0.2.0. All components are included if `version` is a
+/// pre-release.
+///
+/// Returns `None` if the updated requirement does not match
+/// `version`, such as when `">= 1.2.0, < 2.0"` would become
+/// `">= 2.1.0, < 2.0"`. Such requirements must be updated by hand.
+#[cfg(feature = "markdown_deps_updated")]
+pub fn update_request(request: &str, version: &semver::Version) -> Option<String> {
+    use semver::{Comparator, Op};
+
+    let comparators = request.split(',').map(|text| {
+        let comparator = match Comparator::parse(text.trim()) {
+            Ok(comparator) => comparator,
+            Err(_) => return String::from(text),
+        };
+        match comparator.op {
+            Op::Tilde | Op::Caret | Op::Exact | Op::Greater | Op::GreaterEq | Op::Wildcard => {}
+            _ => return String::from(text), // We do not check other operators.
+        }
+
+        let start = text
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(text.len());
+        let trimmed = text.trim_end();
+        let mut updated = String::from(&text[..start]);
+        if !version.pre.is_empty() {
+            updated.push_str(&version.to_string());
+        } else {
+            updated.push_str(&version.major.to_string());
+            if comparator.minor.is_some() {
+                updated.push_str(&format!(".{}", version.minor));
+            }
+            if comparator.patch.is_some() {
+                updated.push_str(&format!(".{}", version.patch));
+            }
+            if comparator.op == Op::Wildcard {
+                // Keep the wildcard as written, e.g., ".*" or ".x".
+                updated.push_str(&trimmed[trimmed.rfind('.').unwrap_or(trimmed.len())..]);
+            }
+        }
+        updated.push_str(&text[trimmed.len()..]);
+        updated
+    });
+    let updated = join(comparators, ",");
+
+    let request = semver::VersionReq::parse(&updated).ok()?;
+    if request.matches(version) && version_matches_request(version, &request).is_ok() {
+        Some(updated)
+    } else {
+        None
+    }
+}
+
 /// Verify that the version range request matches the given version.
 #[cfg(any(
     feature = "html_root_url_updated",
@@ -51,7 +284,7 @@ pub fn indent(text: &str) -> String {
 pub fn version_matches_request(
     version: &semver::Version,
     request: &semver::VersionReq,
-) -> Result<()> {
+) -> Result<(), String> {
     use semver::Op;
 
     for comparator in &request.comparators {
@@ -95,6 +328,19 @@ pub fn version_matches_request(
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    #[cfg(any(
+        feature = "config",
+        feature = "workspace",
+        feature = "html_root_url_updated"
+    ))]
+    fn resolve_dot() {
+        use super::resolve;
+        assert_eq!(resolve(".", "README.md"), "README.md");
+        assert_eq!(resolve("", "README.md"), "README.md");
+        assert_eq!(resolve("foo", "README.md"), "foo/README.md");
+    }
+
     #[cfg(any(feature = "html_root_url_updated", feature = "markdown_deps_updated"))]
     use semver::{Version, VersionReq};
 
@@ -246,4 +492,57 @@ mod tests {
             );
         }
     }
+
+    #[cfg(feature = "markdown_deps_updated")]
+    #[test]
+    fn line_column_counts_characters() {
+        let text = "first\nsæcond line";
+        assert_eq!(line_column(text, 0), (1, 1));
+        assert_eq!(line_column(text, 6), (2, 1));
+        assert_eq!(line_column(text, text.find("line").unwrap()), (2, 8));
+    }
+
+    #[cfg(feature = "markdown_deps_updated")]
+    mod test_update_request {
+        use super::*;
+
+        #[test]
+        fn keeps_precision() {
+            let version = Version::parse("0.2.0").unwrap();
+            assert_eq!(update_request("0.1", &version).unwrap(), "0.2");
+            assert_eq!(update_request("0.1.2", &version).unwrap(), "0.2.0");
+            assert_eq!(update_request("0", &version).unwrap(), "0");
+        }
+
+        #[test]
+        fn keeps_operators() {
+            let version = Version::parse("1.3.0").unwrap();
+            assert_eq!(update_request("^1.2", &version).unwrap(), "^1.3");
+            assert_eq!(update_request("~ 1.2.3", &version).unwrap(), "~ 1.3.0");
+            assert_eq!(update_request("=1.2.3", &version).unwrap(), "=1.3.0");
+            assert_eq!(update_request("1.2.*", &version).unwrap(), "1.3.*");
+        }
+
+        #[test]
+        fn multiple_comparators() {
+            let version = Version::parse("1.3.0").unwrap();
+            assert_eq!(
+                update_request(">= 1.2.0, < 2.0", &version).unwrap(),
+                ">= 1.3.0, < 2.0"
+            );
+        }
+
+        #[test]
+        fn unsatisfiable_update() {
+            let version = Version::parse("2.1.0").unwrap();
+            assert_eq!(update_request(">= 1.2.0, < 2.0", &version), None);
+        }
+
+        #[test]
+        fn pre_release() {
+            let version = Version::parse("1.0.0-rc2").unwrap();
+            assert_eq!(update_request("1.0.0-rc1", &version).unwrap(), "1.0.0-rc2");
+            assert_eq!(update_request("^0.9", &version).unwrap(), "^1.0.0-rc2");
+        }
+    }
 }
@@ -1,79 +1,357 @@
 #![cfg(feature = "html_root_url_updated")]
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
 use semver::{Version, VersionReq};
 use syn::spanned::Spanned;
 use syn::token;
 use url::Url;
 
-use crate::helpers::{indent, read_file, version_matches_request, Result};
+use crate::error::{Error, Result};
+use crate::helpers::{
+    parse_version, read_path, read_toml, resolve, source_lines, version_matches_request,
+};
+use crate::reporter::{CheckKind, Reporter, StdoutReporter};
+use crate::template::{expand, Placeholders};
+
+/// The default URL template for `html_root_url` attributes.
+pub(crate) const DEFAULT_URL_TEMPLATE: &str = "https://docs.rs/{name}/{version}";
+
+/// What to do with `html_root_url` attributes which point to a
+/// different host than the URL template.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OtherHosts {
+    /// Accept URLs on other hosts without checking them.
+    #[default]
+    Allow,
+    /// Reject URLs on other hosts.
+    Deny,
+}
 
-fn url_matches(value: &str, pkg_name: &str, version: &Version) -> Result<()> {
-    let url = Url::parse(value).map_err(|err| format!("parse error: {err}"))?;
+/// Options for the `html_root_url` checks, such as
+/// [`check_html_root_url_with_options`].
+///
+/// By default, URLs are checked against the template
+/// `https://docs.rs/{name}/{version}`, URLs on other hosts are
+/// accepted, files without an `html_root_url` attribute pass, and
+/// status information is printed on `stdout`:
+///
+/// ```rust
+/// use version_sync::{OtherHosts, UrlOptions};
+///
+/// let options = UrlOptions::new()
+///     .template("https://docs.internal.example/{name}/{version}/")
+///     .other_hosts(OtherHosts::Deny);
+/// ```
+pub struct UrlOptions<'a> {
+    template: &'a str,
+    other_hosts: OtherHosts,
+    required: bool,
+    reporter: Option<&'a mut dyn Reporter>,
+}
 
-    // We can only reason about docs.rs.
-    if url.domain().is_some() && url.domain() != Some("docs.rs") {
-        return Ok(());
+impl<'a> UrlOptions<'a> {
+    /// Create the default options.
+    pub fn new() -> UrlOptions<'a> {
+        UrlOptions::default()
     }
 
-    // Since docs.rs redirects HTTP traffic to HTTPS, we will ensure
-    // that the scheme is "https" here.
-    if url.scheme() != "https" {
-        return Err(format!("expected \"https\", found {:?}", url.scheme()));
+    /// Check the URLs against `template`.
+    ///
+    /// The template is a URL where `{name}` is replaced by the package
+    /// name and where the path has a `{version}` placeholder, such as
+    /// `https://docs.example.net/{name}/{version}/`. Templates for
+    /// other doc keys than `html_root_url` can leave out `{version}`,
+    /// see [`check_doc_url_with_options`].
+    pub fn template(mut self, template: &'a str) -> UrlOptions<'a> {
+        self.template = template;
+        self
     }
 
-    let mut path_segments = url
-        .path_segments()
-        .ok_or_else(|| String::from("no path in URL"))?;
+    /// Accept or reject URLs with another host than the template.
+    pub fn other_hosts(mut self, other_hosts: OtherHosts) -> UrlOptions<'a> {
+        self.other_hosts = other_hosts;
+        self
+    }
 
-    // The package name should not be empty.
-    let name = path_segments
-        .next()
-        .and_then(|path| if path.is_empty() { None } else { Some(path) })
-        .ok_or_else(|| String::from("missing package name"))?;
+    /// Require that there is an `html_root_url` attribute.
+    ///
+    /// When checking all crate roots of a package, only the library
+    /// needs the attribute.
+    pub fn required(mut self, required: bool) -> UrlOptions<'a> {
+        self.required = required;
+        self
+    }
 
-    // The version number should not be empty.
-    let request = path_segments
-        .next()
-        .and_then(|path| if path.is_empty() { None } else { Some(path) })
-        .ok_or_else(|| String::from("missing version number"))?;
+    /// Send status information to `reporter` instead of `stdout`.
+    pub fn reporter(mut self, reporter: &'a mut dyn Reporter) -> UrlOptions<'a> {
+        self.reporter = Some(reporter);
+        self
+    }
 
-    // Finally, we check that the package name and version matches.
-    if name != pkg_name {
-        Err(format!("expected package \"{pkg_name}\", found \"{name}\""))
-    } else {
-        // The Rust API Guidelines[1] suggest using an exact version
-        // number, but we have relaxed this a little and allow the
-        // user to specify the version as just "1" or "1.2". We might
-        // make this more strict in the future.
-        //
-        // [1]: https://rust-lang-nursery.github.io/api-guidelines/documentation.html
-        // #crate-sets-html_root_url-attribute-c-html-root
-        VersionReq::parse(request)
-            .map_err(|err| format!("could not parse version in URL: {err}"))
-            .and_then(|request| version_matches_request(version, &request))
+    /// Call `check` with the options and the reporter.
+    fn run<T>(mut self, check: impl FnOnce(&UrlOptions, &mut dyn Reporter) -> T) -> T {
+        match self.reporter.take() {
+            Some(reporter) => check(&self, reporter),
+            None => check(&self, &mut StdoutReporter),
+        }
     }
 }
 
-/// Check version numbers in `html_root_url` attributes.
+impl Default for UrlOptions<'_> {
+    fn default() -> Self {
+        UrlOptions {
+            template: DEFAULT_URL_TEMPLATE,
+            other_hosts: OtherHosts::Allow,
+            required: false,
+            reporter: None,
+        }
+    }
+}
+
+impl fmt::Debug for UrlOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UrlOptions")
+            .field("template", &self.template)
+            .field("other_hosts", &self.other_hosts)
+            .field("required", &self.required)
+            .field("reporter", &self.reporter.as_ref().map(|_| "..."))
+            .finish()
+    }
+}
+
+/// A URL template with `{name}` and `{version}` placeholders.
 ///
-/// This function parses the Rust source file in `path` and looks for
-/// `html_root_url` attributes. Such an attribute must specify a valid
-/// URL and if the URL points to docs.rs, it must be point to the
-/// documentation for `pkg_name` and `pkg_version`.
+/// Without `{version}`, only the host and the start of the path are
+/// checked.
+struct UrlTemplate<'a> {
+    /// The full template.
+    template: &'a str,
+    /// The scheme, such as `https`.
+    scheme: &'a str,
+    /// The host, including the port if any.
+    host: &'a str,
+    /// The non-empty segments of the path.
+    segments: Vec<&'a str>,
+    /// What to do with URLs on other hosts.
+    other_hosts: OtherHosts,
+}
+
+impl<'a> UrlTemplate<'a> {
+    /// Split `template` into its parts.
+    fn parse(template: &'a str, other_hosts: OtherHosts) -> Result<UrlTemplate<'a>> {
+        let template_error = |message: &str| Error::Template {
+            message: format!("{message} in URL template \"{template}\""),
+        };
+        let (scheme, rest) = template
+            .split_once("://")
+            .ok_or_else(|| template_error("no scheme"))?;
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        if host.is_empty() {
+            return Err(template_error("no host"));
+        }
+        if host.contains("{version}") {
+            return Err(template_error("{version} placeholder in the host"));
+        }
+
+        Ok(UrlTemplate {
+            template,
+            scheme,
+            host,
+            segments: path.split('/').filter(|s| !s.is_empty()).collect(),
+            other_hosts,
+        })
+    }
+
+    /// Check that `value` is a URL matching the template.
+    fn matches(&self, value: &str, pkg_name: &str, version: &Version) -> Result<(), UrlMismatch> {
+        let url = Url::parse(value).map_err(|err| format!("parse error: {err}"))?;
+
+        // We can only reason about the host given in the template.
+        let host = fill(self.host, pkg_name, version).to_lowercase();
+        if let Some(found) = url_host(&url) {
+            if found != host {
+                return match self.other_hosts {
+                    OtherHosts::Allow => Ok(()),
+                    OtherHosts::Deny => {
+                        Err(format!("expected host \"{host}\", found \"{found}\"").into())
+                    }
+                };
+            }
+        }
+
+        // Since docs.rs redirects HTTP traffic to HTTPS, the default
+        // template ensures that the scheme is "https".
+        if url.scheme() != self.scheme {
+            return Err(format!("expected \"{}\", found {:?}", self.scheme, url.scheme()).into());
+        }
+
+        let mut path_segments = url
+            .path_segments()
+            .ok_or_else(|| String::from("no path in URL"))?;
+
+        for (index, segment) in self.segments.iter().enumerate() {
+            let found = path_segments.next().unwrap_or_default();
+            let (prefix, suffix) = match segment.split_once("{version}") {
+                Some(parts) => parts,
+                None => {
+                    let expected = fill(segment, pkg_name, version);
+                    match (*segment, found) {
+                        _ if found == expected => continue,
+                        ("{name}", "") => return Err(String::from("missing package name").into()),
+                        ("{name}", name) => {
+                            return Err(format!(
+                                "expected package \"{pkg_name}\", found \"{name}\""
+                            )
+                            .into())
+                        }
+                        _ => {
+                            return Err(format!(
+                                "expected \"{expected}\" in path, found \"{found}\""
+                            )
+                            .into())
+                        }
+                    }
+                }
+            };
+
+            // The version number should not be empty.
+            if found.is_empty() {
+                return Err(String::from("missing version number").into());
+            }
+            let prefix = fill(prefix, pkg_name, version);
+            let suffix = fill(suffix, pkg_name, version);
+            let request = found
+                .strip_prefix(prefix.as_str())
+                .and_then(|rest| rest.strip_suffix(suffix.as_str()))
+                .filter(|request| !request.is_empty())
+                .ok_or_else(|| format!("expected \"{segment}\" in path, found \"{found}\""))?;
+
+            // The Rust API Guidelines[1] suggest using an exact version
+            // number, but we have relaxed this a little and allow the
+            // user to specify the version as just "1" or "1.2". We might
+            // make this more strict in the future.
+            //
+            // [1]: https://rust-lang-nursery.github.io/api-guidelines/documentation.html
+            // #crate-sets-html_root_url-attribute-c-html-root
+            VersionReq::parse(request)
+                .map_err(|err| format!("could not parse version in URL: {err}"))
+                .and_then(|request| version_matches_request(version, &request))
+                .map_err(|message| UrlMismatch {
+                    message,
+                    version: segment_range(value, index)
+                        .map(|range| range.start + prefix.len()..range.end - suffix.len())
+                        .filter(|range| value.get(range.clone()) == Some(request)),
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Check if the path has a `{version}` placeholder.
+    fn has_version(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| segment.contains("{version}"))
+    }
+
+    /// Construct an updated URL for `pkg_name` and `version`.
+    ///
+    /// A trailing slash in `value` is kept.
+    fn updated(&self, value: &str, pkg_name: &str, version: &Version) -> String {
+        let mut url = fill(self.template, pkg_name, version);
+        if value.ends_with('/') && !url.ends_with('/') {
+            url.push('/');
+        }
+        url
+    }
+}
+
+/// Why a URL does not match a template.
+struct UrlMismatch {
+    /// Description of the problem.
+    message: String,
+    /// The byte range of the outdated version number in the URL, if
+    /// the version number is the problem.
+    version: Option<Range<usize>>,
+}
+
+impl From<String> for UrlMismatch {
+    fn from(message: String) -> Self {
+        UrlMismatch {
+            message,
+            version: None,
+        }
+    }
+}
+
+/// Find the byte range of the path segment at `index` in the URL in
+/// `value`.
+fn segment_range(value: &str, index: usize) -> Option<Range<usize>> {
+    let authority = value.find("://")? + 3;
+    let start = authority + value[authority..].find('/')? + 1;
+    let end = value[start..]
+        .find(['?', '#'])
+        .map_or(value.len(), |end| start + end);
+    let mut offset = start;
+    for (idx, segment) in value[start..end].split('/').enumerate() {
+        if idx == index {
+            return Some(offset..offset + segment.len());
+        }
+        offset += segment.len() + 1;
+    }
+    None
+}
+
+/// Expand the placeholders in `text` for `pkg_name` and `version`.
+fn fill(text: &str, pkg_name: &str, version: &Version) -> String {
+    let placeholders = Placeholders::new();
+    expand(
+        text,
+        pkg_name,
+        &version.to_string(),
+        &placeholders,
+        |_, value| String::from(value),
+    )
+}
+
+/// The host of `url`, including the port if any.
 ///
-/// # Errors
+/// The host is a domain or an IP address. URLs without a host give
+/// `None` and are always checked against the template.
+fn url_host(url: &Url) -> Option<String> {
+    let host = url.host_str()?;
+    Some(match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => String::from(host),
+    })
+}
+
+/// An `html_root_url` or similar attribute found in a crate root.
+struct DocUrl {
+    /// Index of the doc key, such as `html_root_url`, in the keys we
+    /// searched for.
+    key: usize,
+    /// The string literal with the URL, if any.
+    url: Option<syn::LitStr>,
+    /// Line number of the start of the attribute, starting with 1.
+    first_line: usize,
+    /// Column of the start of the attribute, starting with 0.
+    column: usize,
+    /// Line number of the end of the attribute.
+    last_line: usize,
+}
+
+/// Find all attributes for the doc `keys` in `krate`, such as
+/// `#![doc(html_root_url = "...")]`.
 ///
-/// If any attribute fails the check, an `Err` is returned with a
-/// succinct error message. Status information has then already been
-/// printed on `stdout`.
-pub fn check_html_root_url(path: &str, pkg_name: &str, pkg_version: &str) -> Result<()> {
-    let code = read_file(path).map_err(|err| format!("could not read {path}: {err}"))?;
-    let version = Version::parse(pkg_version)
-        .map_err(|err| format!("bad package version {pkg_version:?}: {err}"))?;
-    let krate: syn::File = syn::parse_file(&code)
-        .map_err(|_| format!("could not parse {path}: please run \"cargo build\""))?;
-
-    println!("Checking doc attributes in {path}...");
-    for attr in krate.attrs {
+/// Attributes where the URL is not a string literal are skipped.
+fn find_doc_urls(krate: &syn::File, keys: &[&str]) -> syn::Result<Vec<DocUrl>> {
+    let mut doc_urls = Vec::new();
+    for attr in &krate.attrs {
         if let syn::AttrStyle::Outer = attr.style {
             continue;
         }
@@ -84,13 +362,14 @@ pub fn check_html_root_url(path: &str, pkg_name: &str, pkg_version: &str) -> Res
 
         if let syn::Meta::List(ref list) = attr.meta {
             list.parse_nested_meta(|meta| {
-                if meta.path.is_ident("html_root_url") {
-                    let check_result = match meta.value() {
+                let key = keys.iter().position(|key| meta.path.is_ident(key));
+                if let Some(key) = key {
+                    let url = match meta.value() {
                         Ok(value) => match value.parse()? {
-                            syn::Lit::Str(ref s) => url_matches(&s.value(), pkg_name, &version),
+                            syn::Lit::Str(s) => Some(s),
                             _ => return Ok(()),
                         },
-                        Err(_err) => Err(String::from("html_root_url attribute without URL")),
+                        Err(_err) => None,
                     };
 
                     // FIXME: the proc-macro2-0.4.27 crate hides accurate span
@@ -98,24 +377,13 @@ pub fn check_html_root_url(path: &str, pkg_name: &str, pkg_version: &str) -> Res
                     // start line is correct, but the end line is always equal
                     // to the start. Luckily, most html_root_url attributes
                     // are on a single line, so the code below works okay.
-                    let first_line = attr.span().start().line;
-                    let last_line = attr.span().end().line;
-                    // Getting the source code for a span is tracked upstream:
-                    // https://github.com/alexcrichton/proc-macro2/issues/110.
-                    let source_lines = code.lines().take(last_line).skip(first_line - 1);
-                    match check_result {
-                        Ok(()) => {
-                            println!("{path} (line {first_line}) ... ok");
-                            return Ok(());
-                        }
-                        Err(err) => {
-                            println!("{path} (line {first_line}) ... {err} in");
-                            for line in source_lines {
-                                println!("{}", indent(line));
-                            }
-                            return Err(meta.error(format!("html_root_url errors in {path}")));
-                        }
-                    }
+                    doc_urls.push(DocUrl {
+                        key,
+                        url,
+                        first_line: attr.span().start().line,
+                        column: attr.span().start().column,
+                        last_line: attr.span().end().line,
+                    });
                 }
                 // Need to advance the input stream by parsing it.
                 // Otherwise syn gets stuck parsing the wrong tokens.
@@ -134,18 +402,999 @@ pub fn check_html_root_url(path: &str, pkg_name: &str, pkg_version: &str) -> Res
                 }
 
                 Ok(())
-            })
-            .map_err(|err| err.to_string())?;
+            })?;
+        }
+    }
+
+    Ok(doc_urls)
+}
+
+/// Check a single URL attribute against the template for its key.
+fn check_attribute(
+    path: &str,
+    attr: &DocUrl,
+    templates: &[(&str, UrlTemplate)],
+    pkg_name: &str,
+    version: &Version,
+) -> Result<()> {
+    let (key, template) = &templates[attr.key];
+    let (value, line, column, result) = match attr.url {
+        Some(ref url) => {
+            let value = url.value();
+            let start = url.span().start();
+            let result = template.matches(&value, pkg_name, version);
+            (value, start.line, start.column, result)
+        }
+        None => (
+            String::new(),
+            attr.first_line,
+            attr.column,
+            Err(UrlMismatch::from(format!("{key} attribute without URL"))),
+        ),
+    };
+    result.map_err(|mismatch| match mismatch.version {
+        // Point at the outdated version number, after the quote which
+        // starts the literal, rather than at the whole URL.
+        Some(range) => Error::Mismatch {
+            path: String::from(path),
+            line,
+            column: column + 2 + value[..range.start].chars().count(),
+            found: String::from(&value[range]),
+            expected: version.to_string(),
+            message: mismatch.message,
+        },
+        None => Error::Mismatch {
+            path: String::from(path),
+            line,
+            column: column + 1,
+            expected: template.updated(&value, pkg_name, version),
+            found: value,
+            message: mismatch.message,
+        },
+    })
+}
+
+/// Parse `code` and find all attributes for the doc `keys` in it.
+fn parse_doc_urls(path: &str, code: &str, keys: &[&str]) -> Result<Vec<DocUrl>> {
+    let rust_error = |err: syn::Error| Error::Rust {
+        path: String::from(path),
+        message: err.to_string(),
+    };
+    let krate: syn::File = syn::parse_file(code).map_err(rust_error)?;
+    find_doc_urls(&krate, keys).map_err(rust_error)
+}
+
+/// Convert a line and column from a span to a byte offset in `code`.
+fn byte_offset(code: &str, position: proc_macro2::LineColumn) -> Option<usize> {
+    let line_start = match position.line {
+        1 => 0,
+        line => code.match_indices('\n').nth(line - 2)?.0 + 1,
+    };
+    // The column is counted in characters, not bytes.
+    let line = &code[line_start..];
+    line.char_indices()
+        .map(|(idx, _)| idx)
+        .chain(Some(line.len()))
+        .nth(position.column)
+        .map(|idx| line_start + idx)
+}
+
+/// Check version numbers in `html_root_url` attributes.
+///
+/// This function parses the Rust source file in `path` and looks for
+/// `html_root_url` attributes. Such an attribute must specify a valid
+/// URL and if the URL points to docs.rs, it must be point to the
+/// documentation for `pkg_name` and `pkg_version`.
+///
+/// # Errors
+///
+/// If any attribute fails the check, an [`Error::Failed`] is returned
+/// with an [`Error::Mismatch`] per attribute. Status information has
+/// then already been printed on `stdout`.
+pub fn check_html_root_url(path: &str, pkg_name: &str, pkg_version: &str) -> Result<()> {
+    check_html_root_url_with_reporter(path, pkg_name, pkg_version, &mut StdoutReporter)
+}
+
+/// Check version numbers in `html_root_url` attributes and send
+/// status information to `reporter`.
+///
+/// This works like [`check_html_root_url`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If any attribute fails the check, an [`Error::Failed`] is returned
+/// with an [`Error::Mismatch`] per attribute.
+pub fn check_html_root_url_with_reporter(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    check_html_root_url_with_options(
+        path,
+        pkg_name,
+        pkg_version,
+        UrlOptions::new().reporter(reporter),
+    )
+}
+
+/// Check version numbers in `html_root_url` attributes and require
+/// that there is at least one such attribute.
+///
+/// This works like [`check_html_root_url`], except that a file without
+/// an `html_root_url` attribute fails the check instead of passing
+/// silently.
+///
+/// # Errors
+///
+/// If the file has no `html_root_url` attribute, an
+/// [`Error::MissingAttribute`] is returned. Otherwise the errors are
+/// the same as for [`check_html_root_url`].
+pub fn check_html_root_url_required(path: &str, pkg_name: &str, pkg_version: &str) -> Result<()> {
+    check_html_root_url_required_with_reporter(path, pkg_name, pkg_version, &mut StdoutReporter)
+}
+
+/// Check version numbers in `html_root_url` attributes, require at
+/// least one such attribute, and send status information to
+/// `reporter`.
+///
+/// This works like [`check_html_root_url_required`], but nothing is
+/// printed on `stdout`.
+///
+/// # Errors
+///
+/// If the file has no `html_root_url` attribute, an
+/// [`Error::MissingAttribute`] is returned. If any attribute fails the
+/// check, an [`Error::Failed`] is returned.
+pub fn check_html_root_url_required_with_reporter(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let options = UrlOptions::new().required(true).reporter(reporter);
+    check_html_root_url_with_options(path, pkg_name, pkg_version, options)
+}
+
+/// Parse the URL template for each doc key.
+fn parse_url_templates<'a>(
+    url_templates: &[(&'a str, &'a str)],
+    other_hosts: OtherHosts,
+) -> Result<Vec<(&'a str, UrlTemplate<'a>)>> {
+    url_templates
+        .iter()
+        .map(|&(key, template)| {
+            let url_template = UrlTemplate::parse(template, other_hosts)?;
+            // URLs such as html_playground_url have no version, but
+            // the html_root_url should always have one.
+            if key == "html_root_url" && !url_template.has_version() {
+                return Err(Error::Template {
+                    message: format!(
+                        "no {{version}} placeholder in the path in URL template \"{template}\""
+                    ),
+                });
+            }
+            Ok((key, url_template))
+        })
+        .collect()
+}
+
+/// Return an [`Error::MissingAttribute`] if there is no attribute in
+/// `doc_urls` for one of the doc `keys`.
+fn find_missing_key(path: &str, keys: &[&str], doc_urls: &[DocUrl]) -> Result<()> {
+    let missing = (0..keys.len()).find(|&key| doc_urls.iter().all(|attr| attr.key != key));
+    match missing {
+        Some(key) => Err(Error::MissingAttribute {
+            path: String::from(path),
+            name: String::from(keys[key]),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check all attributes for the doc keys in `url_templates` in `path`.
+fn check_attributes(
+    path: &str,
+    url_templates: &[(&str, &str)],
+    other_hosts: OtherHosts,
+    pkg_name: &str,
+    pkg_version: &str,
+    required: bool,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let templates = parse_url_templates(url_templates, other_hosts)?;
+    let keys = templates.iter().map(|&(key, _)| key).collect::<Vec<_>>();
+    let code = read_path(path)?;
+    let version = parse_version(pkg_version)?;
+    let doc_urls = parse_doc_urls(path, &code, &keys)?;
+    if required {
+        find_missing_key(path, &keys, &doc_urls)?;
+    }
+
+    let mut errors = Vec::new();
+    for attr in doc_urls {
+        match check_attribute(path, &attr, &templates, pkg_name, &version) {
+            Ok(()) => reporter.location_ok(path, attr.first_line),
+            Err(err) => {
+                reporter.location_mismatch(
+                    path,
+                    attr.first_line,
+                    &err,
+                    source_lines(&code, attr.first_line, attr.last_line),
+                );
+                errors.push(err);
+            }
         }
     }
 
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
+    }
     Ok(())
 }
 
+/// Update version numbers in `html_root_url` attributes.
+///
+/// This function finds the same attributes as
+/// [`check_html_root_url`], but instead of merely reporting outdated
+/// URLs, it replaces each outdated URL literal with the template
+/// `https://docs.rs/{name}/{version}` filled in for `pkg_name` and
+/// `pkg_version`. The whole URL is rewritten from the template, but a
+/// trailing slash is kept. URLs on hosts other than docs.rs are not
+/// checked and are therefore left as they are.
+///
+/// The spans of the URL literals are used to rewrite the file, so the
+/// other attributes and the formatting of the file are left
+/// untouched, also when a `doc(...)` attribute spans multiple lines.
+/// The file is only written if at least one URL was updated.
+///
+/// This is used by [`assert_html_root_url_updated`] when the
+/// `VERSION_SYNC_FIX` environment variable is set.
+///
+/// [`assert_html_root_url_updated`]: crate::assert_html_root_url_updated
+///
+/// # Errors
+///
+/// Attributes which cannot be fixed automatically, such as an
+/// `html_root_url` without a URL, make this function return an
+/// [`Error::Failed`] with an error per attribute. Status information
+/// has then already been printed on `stdout`.
+pub fn fix_html_root_url(path: &str, pkg_name: &str, pkg_version: &str) -> Result<()> {
+    fix_html_root_url_with_reporter(path, pkg_name, pkg_version, &mut StdoutReporter)
+}
+
+/// Update version numbers in `html_root_url` attributes and send
+/// status information to `reporter`.
+///
+/// This works like [`fix_html_root_url`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// Attributes which cannot be fixed automatically make this function
+/// return an [`Error::Failed`] with an error per attribute.
+pub fn fix_html_root_url_with_reporter(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    fix_html_root_url_with_options(
+        path,
+        pkg_name,
+        pkg_version,
+        UrlOptions::new().reporter(reporter),
+    )
+}
+
+/// Fix all attributes for the doc keys in `url_templates` in `path`.
+fn fix_attributes(
+    path: &str,
+    url_templates: &[(&str, &str)],
+    other_hosts: OtherHosts,
+    pkg_name: &str,
+    pkg_version: &str,
+    required: bool,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let templates = parse_url_templates(url_templates, other_hosts)?;
+    let keys = templates.iter().map(|&(key, _)| key).collect::<Vec<_>>();
+    // We read the file directly since read_file would normalize the
+    // line endings, which we want to preserve.
+    let mut code = fs::read_to_string(path).map_err(|source| Error::Io {
+        path: String::from(path),
+        source,
+    })?;
+    let version = parse_version(pkg_version)?;
+    let doc_urls = parse_doc_urls(path, &code, &keys)?;
+    if required {
+        find_missing_key(path, &keys, &doc_urls)?;
+    }
+
+    let mut errors = Vec::new();
+    let mut edits = Vec::new();
+    for attr in doc_urls {
+        let err = match check_attribute(path, &attr, &templates, pkg_name, &version) {
+            Ok(()) => {
+                reporter.location_ok(path, attr.first_line);
+                continue;
+            }
+            Err(err) => err,
+        };
+        let range = attr.url.as_ref().and_then(|url| {
+            let span = url.span();
+            Some(byte_offset(&code, span.start())?..byte_offset(&code, span.end())?)
+        });
+        match (range, &attr.url) {
+            (Some(range), Some(url)) => {
+                let (_, template) = &templates[attr.key];
+                let updated = template.updated(&url.value(), pkg_name, &version);
+                reporter.location_fixed(path, attr.first_line, &err, &updated);
+                edits.push((range, format!("{updated:?}")));
+            }
+            _ => {
+                reporter.location_mismatch(
+                    path,
+                    attr.first_line,
+                    &err,
+                    source_lines(&code, attr.first_line, attr.last_line),
+                );
+                errors.push(err);
+            }
+        }
+    }
+
+    if !edits.is_empty() {
+        // Apply the edits from the back so the earlier ranges stay valid.
+        for (range, updated) in edits.into_iter().rev() {
+            code.replace_range(range, &updated);
+        }
+        fs::write(path, code).map_err(|source| Error::Write {
+            path: String::from(path),
+            source,
+        })?;
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
+    }
+    Ok(())
+}
+
+/// Check or fix the attributes for the doc `key`, such as
+/// `html_root_url`, in `path`.
+#[allow(clippy::too_many_arguments)]
+fn check_root(
+    path: &str,
+    key: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    options: &UrlOptions,
+    required: bool,
+    fix: bool,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let url_templates = [(key, options.template)];
+    reporter.check_started(CheckKind::HtmlRootUrl, path, None);
+    let result = if fix {
+        fix_attributes(
+            path,
+            &url_templates,
+            options.other_hosts,
+            pkg_name,
+            pkg_version,
+            required,
+            reporter,
+        )
+    } else {
+        check_attributes(
+            path,
+            &url_templates,
+            options.other_hosts,
+            pkg_name,
+            pkg_version,
+            required,
+            reporter,
+        )
+    };
+    reporter.check_finished(CheckKind::HtmlRootUrl, path, &result);
+    result
+}
+
+/// Check `html_root_url` attributes with the URL template, the
+/// handling of other hosts, and the reporter in `options`.
+///
+/// This works like [`check_html_root_url`], but the URLs are checked
+/// against the template given by [`UrlOptions::template`] instead of
+/// docs.rs, and status information can be sent to a [`Reporter`].
+/// With [`UrlOptions::required`], a file without an `html_root_url`
+/// attribute fails the check.
+///
+/// # Errors
+///
+/// If the template is invalid, an [`Error::Template`] is returned. If
+/// a required attribute is missing, an [`Error::MissingAttribute`] is
+/// returned. If any attribute fails the check, an [`Error::Failed`]
+/// is returned with an [`Error::Mismatch`] per attribute.
+pub fn check_html_root_url_with_options(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    options: UrlOptions,
+) -> Result<()> {
+    options.run(|options, reporter| {
+        let required = options.required;
+        check_root(
+            path,
+            "html_root_url",
+            pkg_name,
+            pkg_version,
+            options,
+            required,
+            false,
+            reporter,
+        )
+    })
+}
+
+/// Update `html_root_url` attributes with the URL template, the
+/// handling of other hosts, and the reporter in `options`.
+///
+/// This works like [`fix_html_root_url`], but outdated URLs are
+/// checked against and replaced by the template given by
+/// [`UrlOptions::template`], see [`check_html_root_url_with_options`].
+///
+/// # Errors
+///
+/// If the template is invalid, an [`Error::Template`] is returned. If
+/// a required attribute is missing, an [`Error::MissingAttribute`] is
+/// returned. Attributes which cannot be fixed automatically make this
+/// function return an [`Error::Failed`].
+pub fn fix_html_root_url_with_options(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    options: UrlOptions,
+) -> Result<()> {
+    options.run(|options, reporter| {
+        let required = options.required;
+        check_root(
+            path,
+            "html_root_url",
+            pkg_name,
+            pkg_version,
+            options,
+            required,
+            true,
+            reporter,
+        )
+    })
+}
+
+/// Check the URL attribute for the doc `key`, such as
+/// `html_playground_url`, with the URL template, the handling of
+/// other hosts, and the reporter in `options`.
+///
+/// This works like [`check_html_root_url_with_options`] for another
+/// key in `#![doc(...)]` attributes. Unlike for `html_root_url`, the
+/// template need not have a `{version}` placeholder: URLs such as
+/// `html_playground_url` have no version number, so only the host
+/// and the start of the path are compared with the template:
+///
+/// ```rust
+/// use version_sync::{OtherHosts, UrlOptions};
+///
+/// let options = UrlOptions::new()
+///     .template("https://play.rust-lang.org/")
+///     .other_hosts(OtherHosts::Deny);
+/// # let _ = options;
+/// ```
+///
+/// # Errors
+///
+/// If the template is invalid, an [`Error::Template`] is returned. If
+/// a required attribute is missing, an [`Error::MissingAttribute`] is
+/// returned. If any attribute fails the check, an [`Error::Failed`]
+/// is returned with an [`Error::Mismatch`] per attribute.
+pub fn check_doc_url_with_options(
+    path: &str,
+    key: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    options: UrlOptions,
+) -> Result<()> {
+    options.run(|options, reporter| {
+        let required = options.required;
+        check_root(
+            path,
+            key,
+            pkg_name,
+            pkg_version,
+            options,
+            required,
+            false,
+            reporter,
+        )
+    })
+}
+
+/// Update the URL attribute for the doc `key` with the URL template,
+/// the handling of other hosts, and the reporter in `options`.
+///
+/// This works like [`fix_html_root_url_with_options`] for another key
+/// in `#![doc(...)]` attributes, see [`check_doc_url_with_options`].
+///
+/// # Errors
+///
+/// If the template is invalid, an [`Error::Template`] is returned. If
+/// a required attribute is missing, an [`Error::MissingAttribute`] is
+/// returned. Attributes which cannot be fixed automatically make this
+/// function return an [`Error::Failed`].
+pub fn fix_doc_url_with_options(
+    path: &str,
+    key: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    options: UrlOptions,
+) -> Result<()> {
+    options.run(|options, reporter| {
+        let required = options.required;
+        check_root(
+            path,
+            key,
+            pkg_name,
+            pkg_version,
+            options,
+            required,
+            true,
+            reporter,
+        )
+    })
+}
+
+/// Check version numbers in URL attributes such as `html_logo_url`.
+///
+/// This generalizes [`check_html_root_url_with_options`] to other
+/// keys in `#![doc(...)]` attributes. The `url_templates` give the
+/// doc keys to check together with the URL template for each key,
+/// such as:
+///
+/// ```rust
+/// let url_templates = [
+///     ("html_root_url", "https://docs.rs/{name}/{version}"),
+///     (
+///         "html_logo_url",
+///         "https://raw.githubusercontent.com/example/{name}/v{version}/logo.svg",
+///     ),
+/// ];
+/// # let _ = url_templates;
+/// ```
+///
+/// Keys which are not listed, such as `html_playground_url`, are
+/// ignored. Only the template for `html_root_url` must have a
+/// `{version}` placeholder.
+///
+/// # Errors
+///
+/// If a template is invalid, an [`Error::Template`] is returned. If
+/// any attribute fails the check, an [`Error::Failed`] is returned
+/// with an [`Error::Mismatch`] per attribute. Status information has
+/// then already been printed on `stdout`.
+pub fn check_doc_urls(
+    path: &str,
+    url_templates: &[(&str, &str)],
+    other_hosts: OtherHosts,
+    pkg_name: &str,
+    pkg_version: &str,
+) -> Result<()> {
+    check_doc_urls_with_reporter(
+        path,
+        url_templates,
+        other_hosts,
+        pkg_name,
+        pkg_version,
+        &mut StdoutReporter,
+    )
+}
+
+/// Check version numbers in URL attributes such as `html_logo_url`
+/// and send status information to `reporter`.
+///
+/// This works like [`check_doc_urls`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If a template is invalid, an [`Error::Template`] is returned. If
+/// any attribute fails the check, an [`Error::Failed`] is returned.
+pub fn check_doc_urls_with_reporter(
+    path: &str,
+    url_templates: &[(&str, &str)],
+    other_hosts: OtherHosts,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    reporter.check_started(CheckKind::HtmlRootUrl, path, None);
+    let result = check_attributes(
+        path,
+        url_templates,
+        other_hosts,
+        pkg_name,
+        pkg_version,
+        false,
+        reporter,
+    );
+    reporter.check_finished(CheckKind::HtmlRootUrl, path, &result);
+    result
+}
+
+/// Update version numbers in URL attributes such as `html_logo_url`.
+///
+/// This finds the same attributes as [`check_doc_urls`] and replaces
+/// outdated URLs with the expanded template for their key.
+///
+/// # Errors
+///
+/// If a template is invalid, an [`Error::Template`] is returned.
+/// Attributes which cannot be fixed automatically make this function
+/// return an [`Error::Failed`]. Status information has then already
+/// been printed on `stdout`.
+pub fn fix_doc_urls(
+    path: &str,
+    url_templates: &[(&str, &str)],
+    other_hosts: OtherHosts,
+    pkg_name: &str,
+    pkg_version: &str,
+) -> Result<()> {
+    fix_doc_urls_with_reporter(
+        path,
+        url_templates,
+        other_hosts,
+        pkg_name,
+        pkg_version,
+        &mut StdoutReporter,
+    )
+}
+
+/// Update version numbers in URL attributes such as `html_logo_url`
+/// and send status information to `reporter`.
+///
+/// This works like [`fix_doc_urls`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If a template is invalid, an [`Error::Template`] is returned.
+/// Attributes which cannot be fixed automatically make this function
+/// return an [`Error::Failed`].
+pub fn fix_doc_urls_with_reporter(
+    path: &str,
+    url_templates: &[(&str, &str)],
+    other_hosts: OtherHosts,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    reporter.check_started(CheckKind::HtmlRootUrl, path, None);
+    let result = fix_attributes(
+        path,
+        url_templates,
+        other_hosts,
+        pkg_name,
+        pkg_version,
+        false,
+        reporter,
+    );
+    reporter.check_finished(CheckKind::HtmlRootUrl, path, &result);
+    result
+}
+
+/// Find the source files in `dir/auto_dir` which Cargo would discover
+/// as targets: `auto_dir/*.rs` and `auto_dir/*/main.rs`.
+fn discover_targets(dir: &str, auto_dir: &str) -> Vec<String> {
+    let entries = match fs::read_dir(resolve(dir, auto_dir)) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut targets = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            let file_type = entry.file_type().ok()?;
+            if file_type.is_dir() {
+                Some(format!("{auto_dir}/{name}/main.rs"))
+            } else if name.ends_with(".rs") {
+                Some(format!("{auto_dir}/{name}"))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    targets.sort();
+    targets
+}
+
+/// The crate root of the library target in `manifest`.
+fn lib_root(manifest: &toml_edit::Table) -> &str {
+    manifest
+        .get("lib")
+        .and_then(|lib| lib.get("path"))
+        .and_then(|path| path.as_str())
+        .unwrap_or("src/lib.rs")
+}
+
+/// Find the crate roots of the targets of the package in `dir`.
+///
+/// The targets are taken from the `[lib]`, `[[bin]]`, and
+/// `[[example]]` tables of the `manifest`. Like Cargo, we also use
+/// `src/lib.rs`, `src/main.rs`, `src/bin/`, and `examples/` unless
+/// target auto-discovery is turned off with `autobins` or
+/// `autoexamples`. Only the crate roots which exist are returned.
+pub(crate) fn crate_roots(dir: &str, manifest: &toml_edit::Table) -> Vec<String> {
+    let autodiscover = |auto_key| {
+        manifest
+            .get("package")
+            .and_then(|package| package.get(auto_key))
+            .and_then(|auto| auto.as_bool())
+            .unwrap_or(true)
+    };
+
+    let mut roots = vec![String::from(lib_root(manifest))];
+    if autodiscover("autobins") {
+        roots.push(String::from("src/main.rs"));
+    }
+
+    for (kind, auto_key, auto_dir) in [
+        ("bin", "autobins", "src/bin"),
+        ("example", "autoexamples", "examples"),
+    ] {
+        let targets = manifest
+            .get(kind)
+            .and_then(|targets| targets.as_array_of_tables());
+        for target in targets.into_iter().flatten() {
+            let name = target.get("name").and_then(|name| name.as_str());
+            match (target.get("path").and_then(|path| path.as_str()), name) {
+                (Some(path), _) => roots.push(String::from(path)),
+                (None, Some(name)) => {
+                    roots.push(format!("{auto_dir}/{name}.rs"));
+                    roots.push(format!("{auto_dir}/{name}/main.rs"));
+                }
+                (None, None) => {}
+            }
+        }
+
+        if autodiscover(auto_key) {
+            roots.extend(discover_targets(dir, auto_dir));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    roots
+        .into_iter()
+        .map(|root| resolve(dir, &root))
+        .filter(|root| seen.insert(root.clone()) && Path::new(root).is_file())
+        .collect()
+}
+
+/// Check or fix the crate root of every target in the package with
+/// the manifest in `manifest_path`.
+fn check_crate_roots(
+    manifest_path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    options: UrlOptions,
+    fix: bool,
+) -> Result<()> {
+    let manifest = read_toml(manifest_path)?;
+    let dir = Path::new(manifest_path)
+        .parent()
+        .and_then(|dir| dir.to_str())
+        .unwrap_or_default();
+    let lib_root = resolve(dir, lib_root(manifest.as_table()));
+
+    options.run(|options, reporter| {
+        let mut errors = Vec::new();
+        for root in crate_roots(dir, manifest.as_table()) {
+            // Only the library is required to have an html_root_url.
+            let required = options.required && root == lib_root;
+            let result = check_root(
+                &root,
+                "html_root_url",
+                pkg_name,
+                pkg_version,
+                options,
+                required,
+                fix,
+                reporter,
+            );
+            errors.extend(result.err());
+        }
+
+        if !errors.is_empty() {
+            return Err(Error::Failed {
+                path: String::from(manifest_path),
+                errors,
+            });
+        }
+        Ok(())
+    })
+}
+
+/// Check version numbers in `html_root_url` attributes in all crate
+/// roots of a package.
+///
+/// The crate roots are found from the targets in the manifest at
+/// `manifest_path`: the library given by `[lib]`, the binaries given
+/// by `[[bin]]`, and the examples given by `[[example]]`. Like Cargo,
+/// the default `src/lib.rs`, `src/main.rs`, `src/bin/*.rs`, and
+/// `examples/*.rs` files are used too, unless target auto-discovery
+/// has been turned off. Each crate root is then checked with
+/// [`check_html_root_url`].
+///
+/// # Errors
+///
+/// If any crate root fails the check, an [`Error::Failed`] is
+/// returned with the error from each crate root. Status information
+/// has then already been printed on `stdout`.
+pub fn check_package_html_root_urls(
+    manifest_path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+) -> Result<()> {
+    check_package_html_root_urls_with_options(
+        manifest_path,
+        pkg_name,
+        pkg_version,
+        UrlOptions::new(),
+    )
+}
+
+/// Check version numbers in `html_root_url` attributes in all crate
+/// roots of a package and send status information to `reporter`.
+///
+/// This works like [`check_package_html_root_urls`], but nothing is
+/// printed on `stdout`.
+///
+/// # Errors
+///
+/// If any crate root fails the check, an [`Error::Failed`] is
+/// returned with the error from each crate root.
+pub fn check_package_html_root_urls_with_reporter(
+    manifest_path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    check_package_html_root_urls_with_options(
+        manifest_path,
+        pkg_name,
+        pkg_version,
+        UrlOptions::new().reporter(reporter),
+    )
+}
+
+/// Check version numbers in `html_root_url` attributes in all crate
+/// roots of a package with the URL template, the handling of other
+/// hosts, and the reporter in `options`.
+///
+/// This works like [`check_package_html_root_urls`], but each crate
+/// root is checked with [`check_html_root_url_with_options`]. With
+/// [`UrlOptions::required`], the crate root of the library must have
+/// an `html_root_url` attribute, while the binaries and examples may
+/// leave it out.
+///
+/// # Errors
+///
+/// If any crate root fails the check, an [`Error::Failed`] is
+/// returned with the error from each crate root.
+pub fn check_package_html_root_urls_with_options(
+    manifest_path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    options: UrlOptions,
+) -> Result<()> {
+    check_crate_roots(manifest_path, pkg_name, pkg_version, options, false)
+}
+
+/// Update version numbers in `html_root_url` attributes in all crate
+/// roots of a package.
+///
+/// This finds the same crate roots as
+/// [`check_package_html_root_urls`] and fixes each of them with
+/// [`fix_html_root_url`].
+///
+/// # Errors
+///
+/// If any crate root has errors which cannot be fixed automatically,
+/// an [`Error::Failed`] is returned with the error from each crate
+/// root. Status information has then already been printed on
+/// `stdout`.
+pub fn fix_package_html_root_urls(
+    manifest_path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+) -> Result<()> {
+    fix_package_html_root_urls_with_options(manifest_path, pkg_name, pkg_version, UrlOptions::new())
+}
+
+/// Update version numbers in `html_root_url` attributes in all crate
+/// roots of a package and send status information to `reporter`.
+///
+/// This works like [`fix_package_html_root_urls`], but nothing is
+/// printed on `stdout`.
+///
+/// # Errors
+///
+/// If any crate root has errors which cannot be fixed automatically,
+/// an [`Error::Failed`] is returned with the error from each crate
+/// root.
+pub fn fix_package_html_root_urls_with_reporter(
+    manifest_path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    fix_package_html_root_urls_with_options(
+        manifest_path,
+        pkg_name,
+        pkg_version,
+        UrlOptions::new().reporter(reporter),
+    )
+}
+
+/// Update version numbers in `html_root_url` attributes in all crate
+/// roots of a package with the URL template, the handling of other
+/// hosts, and the reporter in `options`.
+///
+/// This finds the same crate roots as
+/// [`check_package_html_root_urls_with_options`] and fixes each of
+/// them with [`fix_html_root_url_with_options`].
+///
+/// # Errors
+///
+/// If any crate root has errors which cannot be fixed automatically,
+/// an [`Error::Failed`] is returned with the error from each crate
+/// root.
+pub fn fix_package_html_root_urls_with_options(
+    manifest_path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    options: UrlOptions,
+) -> Result<()> {
+    check_crate_roots(manifest_path, pkg_name, pkg_version, options, true)
+}
+
 #[cfg(test)]
 mod test_url_matches {
     use super::*;
 
+    fn url_matches(value: &str, pkg_name: &str, version: &Version) -> Result<(), String> {
+        let template = UrlTemplate::parse(DEFAULT_URL_TEMPLATE, OtherHosts::Allow).unwrap();
+        template
+            .matches(value, pkg_name, version)
+            .map_err(|mismatch| mismatch.message)
+    }
+
+    fn template_matches(
+        value: &str,
+        url_template: &str,
+        other_hosts: OtherHosts,
+    ) -> Result<(), String> {
+        let ver = Version::parse("1.2.3").unwrap();
+        let template = UrlTemplate::parse(url_template, other_hosts).unwrap();
+        template
+            .matches(value, "foo", &ver)
+            .map_err(|mismatch| mismatch.message)
+    }
+
     #[test]
     fn good_url() {
         let ver = Version::parse("1.2.3").unwrap();
@@ -191,6 +1440,34 @@ mod test_url_matches {
         );
     }
 
+    #[test]
+    fn ip_address_host() {
+        let ver = Version::parse("1.2.3").unwrap();
+        assert_eq!(
+            url_matches("http://127.0.0.1:8080/foo/1.0.0", "foo", &ver),
+            Ok(())
+        );
+        assert_eq!(url_matches("https://[::1]/foo/1.0.0", "foo", &ver), Ok(()));
+        assert_eq!(
+            template_matches(
+                "http://127.0.0.1:8080/foo/1.2.3",
+                "http://127.0.0.1:8080/{name}/{version}",
+                OtherHosts::Deny
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            template_matches(
+                "http://10.0.0.2/foo/1.2.3",
+                "http://10.0.0.1/{name}/{version}",
+                OtherHosts::Deny
+            ),
+            Err(String::from(
+                "expected host \"10.0.0.1\", found \"10.0.0.2\""
+            ))
+        );
+    }
+
     #[test]
     fn http_url() {
         let ver = Version::parse("1.2.3").unwrap();
@@ -274,11 +1551,210 @@ mod test_url_matches {
             Err(String::from("expected package \"bar\", found \"foo\""))
         );
     }
+
+    #[test]
+    fn custom_template() {
+        let template = "http://docs.internal.example:8080/rust/{name}/v{version}/";
+        assert_eq!(
+            template_matches(
+                "http://docs.internal.example:8080/rust/foo/v1.2.3/",
+                template,
+                OtherHosts::Allow
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            template_matches(
+                "http://docs.internal.example:8080/rust/foo/v1.0.0/",
+                template,
+                OtherHosts::Allow
+            ),
+            Err(String::from("expected minor version 2, found 0"))
+        );
+        assert_eq!(
+            template_matches(
+                "http://docs.internal.example:8080/doc/foo/v1.2.3/",
+                template,
+                OtherHosts::Allow
+            ),
+            Err(String::from("expected \"rust\" in path, found \"doc\""))
+        );
+        assert_eq!(
+            template_matches(
+                "http://docs.internal.example:8080/rust/foo/1.2.3/",
+                template,
+                OtherHosts::Allow
+            ),
+            Err(String::from(
+                "expected \"v{version}\" in path, found \"1.2.3\""
+            ))
+        );
+    }
+
+    #[test]
+    fn name_in_host() {
+        assert_eq!(
+            template_matches(
+                "https://foo.example.net/1.2.3/",
+                "https://{name}.example.net/{version}",
+                OtherHosts::Deny
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn other_hosts() {
+        let template = "https://docs.internal.example/{name}/{version}/";
+        assert_eq!(
+            template_matches("https://docs.rs/foo/1.0.0", template, OtherHosts::Allow),
+            Ok(())
+        );
+        assert_eq!(
+            template_matches("https://docs.rs/foo/1.0.0", template, OtherHosts::Deny),
+            Err(String::from(
+                "expected host \"docs.internal.example\", found \"docs.rs\""
+            ))
+        );
+    }
+
+    #[test]
+    fn bad_templates() {
+        let parse = |template| {
+            UrlTemplate::parse(template, OtherHosts::Allow)
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        };
+        assert_eq!(
+            parse("docs.rs/{name}/{version}"),
+            Err(String::from(
+                "could not parse template: no scheme in URL template \"docs.rs/{name}/{version}\""
+            ))
+        );
+        assert_eq!(
+            parse("https:///{name}/{version}"),
+            Err(String::from(
+                "could not parse template: no host in URL template \"https:///{name}/{version}\""
+            ))
+        );
+        assert_eq!(
+            parse("https://{version}.docs.rs/{name}"),
+            Err(String::from(
+                "could not parse template: {version} placeholder in the host \
+                 in URL template \"https://{version}.docs.rs/{name}\""
+            ))
+        );
+        assert_eq!(parse("https://play.rust-lang.org/"), Ok(()));
+    }
+
+    #[test]
+    fn unversioned_templates() {
+        let parse = |key, template| {
+            parse_url_templates(&[(key, template)], OtherHosts::Allow)
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        };
+        assert_eq!(
+            parse("html_root_url", "https://docs.rs/{name}"),
+            Err(String::from(
+                "could not parse template: no {version} placeholder in the path \
+                 in URL template \"https://docs.rs/{name}\""
+            ))
+        );
+        assert_eq!(
+            parse("html_playground_url", "https://play.rust-lang.org/"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn unversioned_template_matches() {
+        let template = "https://play.example.net/{name}/";
+        assert_eq!(
+            template_matches(
+                "https://play.example.net/foo/?edition=2021",
+                template,
+                OtherHosts::Deny
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            template_matches("https://play.example.net/bar/", template, OtherHosts::Deny),
+            Err(String::from("expected package \"foo\", found \"bar\""))
+        );
+        assert_eq!(
+            template_matches("https://play.rust-lang.org/", template, OtherHosts::Deny),
+            Err(String::from(
+                "expected host \"play.example.net\", found \"play.rust-lang.org\""
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_updated_url {
+    use super::*;
+
+    fn updated_url(value: &str, pkg_name: &str, version: &Version) -> String {
+        let template = UrlTemplate::parse(DEFAULT_URL_TEMPLATE, OtherHosts::Allow).unwrap();
+        template.updated(value, pkg_name, version)
+    }
+
+    #[test]
+    fn docs_rs() {
+        let ver = Version::parse("1.2.3").unwrap();
+        assert_eq!(
+            updated_url("https://docs.rs/foo/1.0.0", "foo", &ver),
+            "https://docs.rs/foo/1.2.3"
+        );
+    }
+
+    #[test]
+    fn trailing_slash() {
+        let ver = Version::parse("1.2.3").unwrap();
+        assert_eq!(
+            updated_url("http://docs.rs/foo/1.0/", "foo", &ver),
+            "https://docs.rs/foo/1.2.3/"
+        );
+    }
+
+    #[test]
+    fn other_host() {
+        let ver = Version::parse("1.2.3").unwrap();
+        assert_eq!(
+            updated_url("http://127.0.0.1:8080/foo/1.0.0", "foo", &ver),
+            "https://docs.rs/foo/1.2.3"
+        );
+    }
+
+    #[test]
+    fn custom_template() {
+        let ver = Version::parse("1.2.3").unwrap();
+        let template = UrlTemplate::parse(
+            "https://docs.internal.example/{name}/{version}/",
+            OtherHosts::Deny,
+        )
+        .unwrap();
+        assert_eq!(
+            template.updated("https://docs.rs/foo/1.0.0", "foo", &ver),
+            "https://docs.internal.example/foo/1.2.3/"
+        );
+    }
+
+    #[test]
+    fn bad_url() {
+        let ver = Version::parse("1.2.3").unwrap();
+        assert_eq!(
+            updated_url("docs.rs/foo", "foo", &ver),
+            "https://docs.rs/foo/1.2.3"
+        );
+    }
 }
 
 #[cfg(test)]
 mod test_check_html_root_url {
     use super::*;
+    use std::io::Write;
 
     #[test]
     fn bad_path() {
@@ -289,7 +1765,8 @@ mod test_check_html_root_url {
         };
         let errmsg = format!("could not read no-such-file.md: {no_such_file}");
         assert_eq!(
-            check_html_root_url("no-such-file.md", "foobar", "1.2.3"),
+            check_html_root_url("no-such-file.md", "foobar", "1.2.3")
+                .map_err(|err| err.to_string()),
             Err(errmsg)
         );
     }
@@ -298,10 +1775,354 @@ mod test_check_html_root_url {
     fn bad_pkg_version() {
         // This uses the src/lib.rs file from this crate.
         assert_eq!(
-            check_html_root_url("src/lib.rs", "foobar", "1.2"),
+            check_html_root_url("src/lib.rs", "foobar", "1.2").map_err(|err| err.to_string()),
             Err(String::from(
                 "bad package version \"1.2\": unexpected end of input while parsing minor version number"
             ))
         );
     }
+
+    #[test]
+    fn fix_multi_line_attribute() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(
+            b"//! Crate docs.\r\n\
+              #![doc(\r\n\
+              \x20   html_logo_url = \"https://example.net/logo.png\",\r\n\
+              \x20   html_root_url = \"https://docs.rs/foo/0.1.2\"\r\n\
+              )]\r\n\
+              #![doc(html_root_url = \"http://docs.rs/foo/0.2.0/\")]\r\n",
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert_eq!(
+            fix_html_root_url(path, "foo", "0.2.0").map_err(|err| err.to_string()),
+            Ok(())
+        );
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "//! Crate docs.\r\n\
+             #![doc(\r\n\
+             \x20   html_logo_url = \"https://example.net/logo.png\",\r\n\
+             \x20   html_root_url = \"https://docs.rs/foo/0.2.0\"\r\n\
+             )]\r\n\
+             #![doc(html_root_url = \"https://docs.rs/foo/0.2.0/\")]\r\n"
+        );
+        assert_eq!(
+            check_html_root_url(path, "foo", "0.2.0").map_err(|err| err.to_string()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn fix_without_url() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"#![doc(html_root_url)]\n").unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert_eq!(
+            fix_html_root_url(path, "foo", "0.2.0").map_err(|err| err.to_string()),
+            Err(format!("{path} ... found 1 error"))
+        );
+    }
+
+    #[test]
+    fn mismatch_location() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"#![doc(html_root_url = \"https://docs.rs/foo/0.1.0\")]\n")
+            .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let errors = match check_html_root_url(path, "foo", "0.2.0") {
+            Err(Error::Failed { errors, .. }) => errors,
+            result => panic!("unexpected result: {result:?}"),
+        };
+        match &errors[..] {
+            [Error::Mismatch {
+                line,
+                column,
+                found,
+                expected,
+                ..
+            }] => {
+                // Only the version number is pointed at.
+                assert_eq!((*line, *column), (1, 45));
+                assert_eq!(found, "0.1.0");
+                assert_eq!(expected, "0.2.0");
+            }
+            errors => panic!("unexpected errors: {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn fix_with_template() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"#![doc(html_root_url = \"https://docs.rs/foo/0.1.0\")]\n")
+            .unwrap();
+        let path = file.path().to_str().unwrap();
+        let template = "https://docs.internal.example/{name}/{version}/";
+
+        let options = || UrlOptions::new().template(template);
+        assert!(check_html_root_url_with_options(path, "foo", "0.2.0", options()).is_ok());
+        let options = || options().other_hosts(OtherHosts::Deny);
+        assert!(check_html_root_url_with_options(path, "foo", "0.2.0", options()).is_err());
+        assert!(fix_html_root_url_with_options(path, "foo", "0.2.0", options()).is_ok());
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "#![doc(html_root_url = \"https://docs.internal.example/foo/0.2.0/\")]\n"
+        );
+    }
+
+    #[test]
+    fn logo_and_favicon_urls() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(
+            b"#![doc(\n\
+              \x20   html_logo_url = \"https://raw.githubusercontent.com/example/foo/v0.1.0/logo.svg\",\n\
+              \x20   html_favicon_url = \"https://raw.githubusercontent.com/example/foo/v0.2.0/icon.ico\",\n\
+              \x20   html_playground_url = \"https://play.rust-lang.org/\"\n\
+              )]\n",
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+        let url_templates = [
+            (
+                "html_logo_url",
+                "https://raw.githubusercontent.com/example/{name}/v{version}/logo.svg",
+            ),
+            (
+                "html_favicon_url",
+                "https://raw.githubusercontent.com/example/{name}/v{version}/icon.ico",
+            ),
+        ];
+
+        let errors = match check_doc_urls(path, &url_templates, OtherHosts::Allow, "foo", "0.2.0") {
+            Err(Error::Failed { errors, .. }) => errors,
+            result => panic!("unexpected result: {result:?}"),
+        };
+        match &errors[..] {
+            [Error::Mismatch {
+                line,
+                column,
+                found,
+                expected,
+                ..
+            }] => {
+                assert_eq!((*line, *column), (2, 69));
+                assert_eq!((found.as_str(), expected.as_str()), ("0.1.0", "0.2.0"));
+            }
+            errors => panic!("unexpected errors: {errors:?}"),
+        }
+
+        assert!(fix_doc_urls(path, &url_templates, OtherHosts::Allow, "foo", "0.2.0").is_ok());
+        assert!(check_doc_urls(path, &url_templates, OtherHosts::Allow, "foo", "0.2.0").is_ok());
+        // The html_root_url check ignores the other keys.
+        assert!(check_html_root_url(path, "foo", "0.2.0").is_ok());
+    }
+
+    #[test]
+    fn playground_url() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"#![doc(html_playground_url = \"https://play.example.net/\")]\n")
+            .unwrap();
+        let path = file.path().to_str().unwrap();
+        let options = || {
+            UrlOptions::new()
+                .template("https://play.rust-lang.org/")
+                .other_hosts(OtherHosts::Deny)
+        };
+
+        match check_doc_url_with_options(path, "html_playground_url", "foo", "0.2.0", options()) {
+            Err(Error::Failed { errors, .. }) => match &errors[..] {
+                [Error::Mismatch { message, .. }] => assert_eq!(
+                    message,
+                    "expected host \"play.rust-lang.org\", found \"play.example.net\""
+                ),
+                errors => panic!("unexpected errors: {errors:?}"),
+            },
+            result => panic!("unexpected result: {result:?}"),
+        }
+        assert!(
+            fix_doc_url_with_options(path, "html_playground_url", "foo", "0.2.0", options())
+                .is_ok()
+        );
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "#![doc(html_playground_url = \"https://play.rust-lang.org/\")]\n"
+        );
+        assert!(
+            check_doc_url_with_options(path, "html_playground_url", "foo", "0.2.0", options())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn required_attribute() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"//! Crate docs.\n").unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert!(check_html_root_url(path, "foo", "0.2.0").is_ok());
+        assert_eq!(
+            check_html_root_url_required(path, "foo", "0.2.0").map_err(|err| err.to_string()),
+            Err(format!("could not find html_root_url attribute in {path}"))
+        );
+    }
+
+    #[test]
+    fn bad_rust_code() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"fn main() {").unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert!(matches!(
+            check_html_root_url(path, "foo", "0.2.0"),
+            Err(Error::Rust { .. })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test_crate_roots {
+    use super::*;
+    use crate::reporter::Silent;
+
+    fn package(manifest: &str, files: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = format!("[package]\nname = \"foo\"\nversion = \"0.2.0\"\n{manifest}");
+        fs::write(dir.path().join("Cargo.toml"), manifest).unwrap();
+        for file in files {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(
+                path,
+                "#![doc(html_root_url = \"https://docs.rs/foo/0.1.0\")]\n",
+            )
+            .unwrap();
+        }
+        dir
+    }
+
+    fn roots(dir: &tempfile::TempDir) -> Vec<String> {
+        let dir = dir.path().to_str().unwrap();
+        let manifest = read_toml(&resolve(dir, "Cargo.toml")).unwrap();
+        crate_roots(dir, manifest.as_table())
+            .iter()
+            .map(|root| String::from(&root[dir.len() + 1..]))
+            .collect()
+    }
+
+    #[test]
+    fn default_targets() {
+        let dir = package(
+            "",
+            &[
+                "src/lib.rs",
+                "src/main.rs",
+                "src/bin/b.rs",
+                "src/bin/a/main.rs",
+                "examples/demo.rs",
+            ],
+        );
+        assert_eq!(
+            roots(&dir),
+            [
+                "src/lib.rs",
+                "src/main.rs",
+                "src/bin/a/main.rs",
+                "src/bin/b.rs",
+                "examples/demo.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn explicit_targets() {
+        let dir = package(
+            "autobins = false\n\
+             [lib]\npath = \"lib.rs\"\n\
+             [[bin]]\nname = \"tool\"\npath = \"tools/tool.rs\"\n\
+             [[bin]]\nname = \"other\"\n\
+             [[example]]\nname = \"demo\"\n",
+            &[
+                "lib.rs",
+                "src/lib.rs",
+                "src/main.rs",
+                "tools/tool.rs",
+                "src/bin/other.rs",
+                "src/bin/skipped.rs",
+                "examples/demo.rs",
+            ],
+        );
+        assert_eq!(
+            roots(&dir),
+            [
+                "lib.rs",
+                "tools/tool.rs",
+                "src/bin/other.rs",
+                "examples/demo.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn check_and_fix_package() {
+        let dir = package("", &["src/lib.rs", "src/bin/tool.rs"]);
+        let manifest_path = resolve(dir.path().to_str().unwrap(), "Cargo.toml");
+        match check_package_html_root_urls_with_reporter(
+            &manifest_path,
+            "foo",
+            "0.2.0",
+            &mut Silent,
+        ) {
+            Err(Error::Failed { path, errors }) => {
+                assert_eq!(path, manifest_path);
+                assert_eq!(errors.len(), 2);
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+
+        assert!(fix_package_html_root_urls_with_reporter(
+            &manifest_path,
+            "foo",
+            "0.2.0",
+            &mut Silent
+        )
+        .is_ok());
+        assert!(check_package_html_root_urls_with_reporter(
+            &manifest_path,
+            "foo",
+            "0.2.0",
+            &mut Silent
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn required_lib_attribute() {
+        let dir = package("", &["src/lib.rs"]);
+        fs::write(dir.path().join("src/lib.rs"), "//! No attribute.\n").unwrap();
+        fs::create_dir_all(dir.path().join("src/bin")).unwrap();
+        fs::write(dir.path().join("src/bin/tool.rs"), "fn main() {}\n").unwrap();
+        let manifest_path = resolve(dir.path().to_str().unwrap(), "Cargo.toml");
+
+        let mut silent = Silent;
+        let options = UrlOptions::new().reporter(&mut silent);
+        assert!(
+            check_package_html_root_urls_with_options(&manifest_path, "foo", "0.2.0", options)
+                .is_ok()
+        );
+        let options = UrlOptions::new().required(true).reporter(&mut silent);
+        match check_package_html_root_urls_with_options(&manifest_path, "foo", "0.2.0", options) {
+            Err(Error::Failed { errors, .. }) => match errors.as_slice() {
+                [Error::MissingAttribute { path, name }] => {
+                    assert!(path.ends_with("lib.rs"));
+                    assert_eq!(name, "html_root_url");
+                }
+                errors => panic!("unexpected errors: {errors:?}"),
+            },
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
 }
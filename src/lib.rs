@@ -11,9 +11,13 @@
 //! * TOML examples in the `README.md` files that show how to add a
 //!   dependency on your crate. See [`assert_markdown_deps_updated`].
 //!
+//! * The same TOML examples in the doc comments of your crate. See
+//!   [`assert_rustdoc_deps_updated`].
+//!
 //! * A `Changelog.md` file that should at least mention the current
 //!   version. See [`assert_contains_regex`] and
-//!   [`assert_contains_substring`].
+//!   [`assert_contains_substring`], or [`assert_changelog_updated`] to
+//!   check that the newest entry is for the current version.
 //!
 //! * A `README.md` file which should only mention the current
 //!   version. See    [`assert_only_contains_regex`].
@@ -61,40 +65,213 @@
 //! When you run `cargo test`, your version numbers will be
 //! automatically checked.
 //!
+//! Each macro panics on the first check that fails. Use [`checks!`]
+//! to run several checks and get a single summary of all outdated
+//! files.
+//!
+//! # Templates
+//!
+//! The templates given to [`assert_contains_substring`],
+//! [`assert_contains_regex`], and [`assert_only_contains_regex`], as
+//! well as the URL templates for the [`html_root_url`] attribute, can
+//! use these placeholders:
+//!
+//! * `{name}`: the package name.
+//! * `{version}`: the full package version, such as `1.4.2-rc.1`.
+//! * `{major}`, `{minor}`, and `{patch}`: the numeric parts of the
+//!   version, such as `1`, `4`, and `2`.
+//! * `{major_minor}`: the major and minor version, such as `1.4`.
+//! * `{pre}` and `{build}`: the pre-release and build metadata, or
+//!   nothing if the version has none.
+//! * `{date}`: today's date in UTC, such as `2026-10-16`. Use
+//!   `{date:%d.%m.%Y}` for other formats with `%Y`, `%m`, and `%d`.
+//! * `{year}`: the current year.
+//! * `{env:FOO}`: the value of the environment variable `FOO`, e.g.,
+//!   a variable set by your CI system.
+//!
+//! The macros add `{msrv}`, `{repository}`, `{homepage}`, and
+//! `{license}` from your `Cargo.toml` file, see
+//! [`cargo_placeholders`]. Your own placeholders can be added with
+//! [`Placeholders`] and passed to the checks with [`TemplateOptions`].
+//!
+//! The date is taken from the `SOURCE_DATE_EPOCH` environment
+//! variable when it is set, so that the checks are reproducible. In
+//! regular expressions, the values are escaped so that they only
+//! match themselves. Other text in braces, such as `\d{2,3}`, is left
+//! untouched. This includes unknown placeholders and unset
+//! environment variables.
+//!
+//! A changelog with a heading like `## [1.4] - 2026-10-16` can be
+//! checked with
+//!
+//! ```rust
+//! #[test]
+//! # fn fake_hidden_test_case() {}
+//! # #[cfg(feature = "contains_regex")]
+//! fn test_changelog_mentions_version() {
+//!     version_sync::assert_contains_regex!("CHANGELOG.md", r"^## \[{major_minor}\] - \d{4}-");
+//! }
+//! # fn main() {}
+//! ```
+//!
 //! # Cargo Features
 //!
 //! In case you only need some of the macros above, you can disable
 //! them individually using Cargo features. The features are:
 //!
 //! * `markdown_deps_updated` enables [`assert_markdown_deps_updated`].
-//! * `html_root_url_updated` enables [`assert_html_root_url_updated`].
+//! * `rustdoc_deps_updated` enables [`assert_rustdoc_deps_updated`],
+//!   [`assert_rustdoc_links_updated`], and [`assert_crate_docs_updated`].
+//! * `html_root_url_updated` enables [`assert_html_root_url_updated`],
+//!   [`assert_html_root_url_required`],
+//!   [`assert_package_html_root_urls_updated`], and
+//!   [`assert_deprecated_since_valid`].
 //! * `contains_regex` enables [`assert_contains_regex`] and
 //!   [`assert_only_contains_regex`].
+//! * `changelog` enables [`assert_changelog_updated`].
+//! * `config` enables [`assert_all_from_config`], which runs checks
+//!   listed in `Cargo.toml` or a `version-sync.toml` file. It enables
+//!   all the features above.
+//! * `workspace` enables [`assert_workspace_updated`], which checks
+//!   all members of a Cargo workspace.
+//! * `cli` builds the `version-sync` command-line program, see
+//!   below. It enables all the other features.
+//!
+//! The `markdown_deps_updated`, `rustdoc_deps_updated`,
+//! `html_root_url_updated`, `contains_regex`, and `changelog`
+//! features are enabled by default. If you disable all of them, you
+//! can still use [`assert_contains_substring`] to quickly check that
+//! a given file contains the current crate version.
+//!
+//! # Machine-Readable Output
+//!
+//! The macros print their progress as text by default. Set the
+//! `VERSION_SYNC_FORMAT` environment variable to `json` to get the
+//! results as JSON Lines instead, or to `sarif` for a SARIF 2.1.0 log
+//! with the failed locations. Set `VERSION_SYNC_OUTPUT` to a file name
+//! to collect the results of all macros in that file; otherwise the
+//! output is printed when the check is done. Use a [`Report`] with
+//! the `_with_reporter` functions to get the results in your own
+//! code.
+//!
+//! In GitHub Actions, the default format is `github`, which prints the
+//! text followed by workflow commands that annotate the failed lines
+//! in pull requests. The `gitlab` format gives a GitLab Code Quality
+//! report, see [`Report::to_gitlab_code_quality`], which is also
+//! collected in the `VERSION_SYNC_OUTPUT` file.
+//!
+//! # Command-Line Program
+//!
+//! The checks can also be run outside of `cargo test`, e.g., from a
+//! release script. Install the `version-sync` program with
+//!
+//! ```text
+//! $ cargo install version-sync --features cli
+//! ```
+//!
+//! and run it from the directory with your `Cargo.toml` file:
 //!
-//! All of these features are enabled by default. If you disable all
-//! of them, you can still use [`assert_contains_substring`] to
-//! quickly check that a given file contains the current crate
-//! version.
+//! ```text
+//! $ version-sync markdown-deps README.md
+//! $ version-sync contains-regex CHANGELOG.md "^## Version {version}"
+//! ```
+//!
+//! The package name and version are read from `Cargo.toml`. Run
+//! `version-sync --help` to see all commands and options.
 //!
 //! [`html_root_url`]: https://rust-lang-nursery.github.io/api-guidelines/documentation.html#crate-sets-html_root_url-attribute-c-html-root
 
 #![doc(html_root_url = "https://docs.rs/version-sync/0.9.5")]
 #![forbid(unsafe_code)]
 #![deny(missing_docs)]
+// The examples deliberately show how to write #[test] functions.
+#![allow(clippy::test_attr_in_doctest)]
 
+mod changelog;
+mod checks;
+mod config;
 mod contains_regex;
 mod contains_substring;
+mod deprecated_since;
+mod diagnostic;
+mod error;
 mod helpers;
 mod html_root_url;
+mod manifest;
 mod markdown_deps;
+mod report;
+mod reporter;
+mod rustdoc_deps;
+mod rustdoc_links;
+mod template;
+mod workspace;
 
+#[cfg(feature = "changelog")]
+pub use crate::changelog::{
+    check_changelog, check_changelog_unreleased, check_changelog_unreleased_with_reporter,
+    check_changelog_with_reporter,
+};
+pub use crate::checks::{CheckOutcome, Checks};
+#[cfg(feature = "config")]
+pub use crate::config::{
+    check_config, check_config_with_reporter, fix_config, fix_config_with_reporter,
+};
 #[cfg(feature = "contains_regex")]
-pub use crate::contains_regex::{check_contains_regex, check_only_contains_regex};
-pub use crate::contains_substring::check_contains_substring;
+pub use crate::contains_regex::{
+    check_contains_regex, check_contains_regex_with_options, check_contains_regex_with_reporter,
+    check_only_contains_regex, check_only_contains_regex_with_options,
+    check_only_contains_regex_with_reporter,
+};
+pub use crate::contains_substring::{
+    check_contains_substring, check_contains_substring_with_options,
+    check_contains_substring_with_reporter,
+};
+#[cfg(feature = "html_root_url_updated")]
+pub use crate::deprecated_since::{
+    check_deprecated_since, check_deprecated_since_required,
+    check_deprecated_since_required_with_reporter, check_deprecated_since_with_reporter,
+};
+pub use crate::error::Error;
 #[cfg(feature = "html_root_url_updated")]
-pub use crate::html_root_url::check_html_root_url;
+pub use crate::html_root_url::{
+    check_doc_url_with_options, check_doc_urls, check_doc_urls_with_reporter, check_html_root_url,
+    check_html_root_url_required, check_html_root_url_required_with_reporter,
+    check_html_root_url_with_options, check_html_root_url_with_reporter,
+    check_package_html_root_urls, check_package_html_root_urls_with_options,
+    check_package_html_root_urls_with_reporter, fix_doc_url_with_options, fix_doc_urls,
+    fix_doc_urls_with_reporter, fix_html_root_url, fix_html_root_url_with_options,
+    fix_html_root_url_with_reporter, fix_package_html_root_urls,
+    fix_package_html_root_urls_with_options, fix_package_html_root_urls_with_reporter, OtherHosts,
+    UrlOptions,
+};
+#[cfg(any(feature = "cli", feature = "config", feature = "workspace"))]
+pub use crate::manifest::Package;
 #[cfg(feature = "markdown_deps_updated")]
-pub use crate::markdown_deps::check_markdown_deps;
+pub use crate::markdown_deps::{
+    check_markdown_deps, check_markdown_deps_with_reporter, fix_markdown_deps,
+    fix_markdown_deps_with_reporter,
+};
+pub use crate::report::{CheckResult, Format, Report, Status};
+pub use crate::reporter::{CheckKind, Reporter, StdoutReporter};
+#[cfg(feature = "rustdoc_deps_updated")]
+pub use crate::rustdoc_deps::{
+    check_crate_docs, check_crate_docs_with_reporter, check_rustdoc_deps,
+    check_rustdoc_deps_with_reporter,
+};
+#[cfg(feature = "rustdoc_deps_updated")]
+pub use crate::rustdoc_links::{check_rustdoc_links, check_rustdoc_links_with_reporter};
+pub use crate::template::{Placeholders, TemplateOptions};
+#[cfg(feature = "workspace")]
+pub use crate::workspace::{
+    check_workspace, check_workspace_path_deps, check_workspace_path_deps_with_reporter,
+    check_workspace_with_reporter, fix_workspace, fix_workspace_with_reporter,
+};
+
+#[doc(hidden)]
+#[cfg(any(feature = "html_root_url_updated", feature = "markdown_deps_updated"))]
+pub use crate::helpers::fix_requested;
+#[doc(hidden)]
+pub use crate::report::run_reported;
 
 /// Assert that dependencies on the current package are up to date.
 ///
@@ -129,16 +306,177 @@ pub use crate::markdown_deps::check_markdown_deps;
 /// your `Cargo.toml` file is, so this will find a `README.md` file
 /// next to your `Cargo.toml` file.
 ///
+/// # Fixing Outdated Dependencies
+///
+/// If the `VERSION_SYNC_FIX` environment variable is set, the macro
+/// will call [`fix_markdown_deps`] instead. This updates the outdated
+/// version numbers in place, so you can update all files with
+///
+/// ```text
+/// $ VERSION_SYNC_FIX=1 cargo test
+/// ```
+///
+/// after bumping the version in your `Cargo.toml` file.
+///
 /// # Panics
 ///
 /// If any TOML code block fails the check, `panic!` will be invoked.
+/// When fixing, only blocks which cannot be updated automatically
+/// will make the macro panic.
 #[macro_export]
 #[cfg(feature = "markdown_deps_updated")]
 macro_rules! assert_markdown_deps_updated {
     ($path:expr) => {
         let pkg_name = env!("CARGO_PKG_NAME");
         let pkg_version = env!("CARGO_PKG_VERSION");
-        if let Err(err) = $crate::check_markdown_deps($path, pkg_name, pkg_version) {
+        let result = if $crate::fix_requested() {
+            $crate::run_reported(|reporter| {
+                $crate::fix_markdown_deps_with_reporter($path, pkg_name, pkg_version, reporter)
+            })
+        } else {
+            $crate::run_reported(|reporter| {
+                $crate::check_markdown_deps_with_reporter($path, pkg_name, pkg_version, reporter)
+            })
+        };
+        if let Err(err) = result {
+            panic!("{}", err);
+        }
+    };
+}
+
+/// Assert that dependencies in doc comments are up to date.
+///
+/// The macro will call [`check_rustdoc_deps`] on the Rust file given
+/// in order to check that the TOML examples in its doc comments all
+/// depend on a current version of your package. The package name and
+/// version are taken from the `$CARGO_PKG_NAME` and
+/// `$CARGO_PKG_VERSION` environment variables, see
+/// [`assert_markdown_deps_updated`].
+///
+/// This macro is enabled by the `rustdoc_deps_updated` feature.
+///
+/// # Usage
+///
+/// The typical way to use this macro is from an integration test:
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_rustdoc_deps is compiled.
+/// fn test_rustdoc_deps() {
+///     version_sync::assert_rustdoc_deps_updated!("src/lib.rs");
+/// }
+///
+/// # fn main() {
+/// #     test_rustdoc_deps();
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// If any TOML code block fails the check, `panic!` will be invoked.
+#[macro_export]
+#[cfg(feature = "rustdoc_deps_updated")]
+macro_rules! assert_rustdoc_deps_updated {
+    ($path:expr) => {
+        let pkg_name = env!("CARGO_PKG_NAME");
+        let pkg_version = env!("CARGO_PKG_VERSION");
+        if let Err(err) = $crate::run_reported(|reporter| {
+            $crate::check_rustdoc_deps_with_reporter($path, pkg_name, pkg_version, reporter)
+        }) {
+            panic!("{}", err);
+        }
+    };
+}
+
+/// Assert that links in doc comments are up to date.
+///
+/// The macro will call [`check_rustdoc_links`] on the Rust file or
+/// directory given in order to check that links to docs.rs and
+/// crates.io in the doc comments point to the current version of your
+/// package. The package name and version are taken from the
+/// `$CARGO_PKG_NAME` and `$CARGO_PKG_VERSION` environment variables,
+/// see [`assert_markdown_deps_updated`].
+///
+/// This macro is enabled by the `rustdoc_deps_updated` feature.
+///
+/// # Usage
+///
+/// The typical way to use this macro is from an integration test:
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_rustdoc_links is compiled.
+/// fn test_rustdoc_links() {
+///     version_sync::assert_rustdoc_links_updated!("src");
+/// }
+///
+/// # fn main() {
+/// #     test_rustdoc_links();
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// If any link fails the check, `panic!` will be invoked.
+#[macro_export]
+#[cfg(feature = "rustdoc_deps_updated")]
+macro_rules! assert_rustdoc_links_updated {
+    ($path:expr) => {
+        let pkg_name = env!("CARGO_PKG_NAME");
+        let pkg_version = env!("CARGO_PKG_VERSION");
+        if let Err(err) = $crate::run_reported(|reporter| {
+            $crate::check_rustdoc_links_with_reporter($path, pkg_name, pkg_version, reporter)
+        }) {
+            panic!("{}", err);
+        }
+    };
+}
+
+/// Assert that everything rustdoc renders for a crate is up to date.
+///
+/// The macro will call [`check_crate_docs`] on the crate root given.
+/// This checks the TOML examples in the doc comments like
+/// [`assert_rustdoc_deps_updated`] and follows
+/// `#![doc = include_str!("...")]` attributes to check the included
+/// Markdown files like [`assert_markdown_deps_updated`]. With the
+/// `contains_regex` feature, links to docs.rs in the included files
+/// are checked too.
+///
+/// This macro is enabled by the `rustdoc_deps_updated` feature.
+///
+/// # Usage
+///
+/// A crate which uses its `README.md` file as crate documentation
+/// needs a single integration test:
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_crate_docs is compiled.
+/// fn test_crate_docs() {
+///     version_sync::assert_crate_docs_updated!("src/lib.rs");
+/// }
+///
+/// # fn main() {
+/// #     test_crate_docs();
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// If any check fails, `panic!` will be invoked after all checks have
+/// run.
+#[macro_export]
+#[cfg(feature = "rustdoc_deps_updated")]
+macro_rules! assert_crate_docs_updated {
+    ($path:expr) => {
+        let pkg_name = env!("CARGO_PKG_NAME");
+        let pkg_version = env!("CARGO_PKG_VERSION");
+        if let Err(err) = $crate::run_reported(|reporter| {
+            $crate::check_crate_docs_with_reporter($path, pkg_name, pkg_version, reporter)
+        }) {
             panic!("{}", err);
         }
     };
@@ -184,6 +522,12 @@ macro_rules! assert_markdown_deps_updated {
 /// your `Cargo.toml` file is, so this will find the `src/lib.rs`
 /// crate root.
 ///
+/// # Fixing Outdated URLs
+///
+/// If the `VERSION_SYNC_FIX` environment variable is set, the macro
+/// will call [`fix_html_root_url`] instead. This replaces outdated
+/// URLs in place, see [`assert_markdown_deps_updated`] for details.
+///
 /// # Panics
 ///
 /// If the `html_root_url` fails the check, `panic!` will be invoked.
@@ -195,12 +539,329 @@ macro_rules! assert_html_root_url_updated {
     ($path:expr) => {
         let pkg_name = env!("CARGO_PKG_NAME");
         let pkg_version = env!("CARGO_PKG_VERSION");
-        if let Err(err) = $crate::check_html_root_url($path, pkg_name, pkg_version) {
+        let result = if $crate::fix_requested() {
+            $crate::run_reported(|reporter| {
+                $crate::fix_html_root_url_with_reporter($path, pkg_name, pkg_version, reporter)
+            })
+        } else {
+            $crate::run_reported(|reporter| {
+                $crate::check_html_root_url_with_reporter($path, pkg_name, pkg_version, reporter)
+            })
+        };
+        if let Err(err) = result {
             panic!("{}", err);
         }
     };
 }
 
+/// Assert that the `html_root_url` attribute is present and up to
+/// date.
+///
+/// This works like [`assert_html_root_url_updated!`], but it calls
+/// [`check_html_root_url_required`] so that a crate root without an
+/// `html_root_url` attribute fails the check.
+///
+/// This macro is enabled by the `html_root_url_updated` feature.
+///
+/// # Usage
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_html_root_url is compiled.
+/// fn test_html_root_url() {
+///     version_sync::assert_html_root_url_required!("src/lib.rs");
+/// }
+///
+/// # fn main() {
+/// #     test_html_root_url();
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// If the `html_root_url` is missing or fails the check, `panic!`
+/// will be invoked.
+#[macro_export]
+#[cfg(feature = "html_root_url_updated")]
+macro_rules! assert_html_root_url_required {
+    ($path:expr) => {
+        let pkg_name = env!("CARGO_PKG_NAME");
+        let pkg_version = env!("CARGO_PKG_VERSION");
+        if let Err(err) = $crate::run_reported(|reporter| {
+            $crate::check_html_root_url_required_with_reporter(
+                $path,
+                pkg_name,
+                pkg_version,
+                reporter,
+            )
+        }) {
+            panic!("{}", err);
+        }
+    };
+}
+
+/// Assert that the URL attribute for a doc key is up to date.
+///
+/// The macro will call [`check_doc_url_with_options`] on the crate
+/// root given in order to check the `#![doc(...)]` attribute for the
+/// key, such as `html_logo_url` or `html_playground_url`, against the
+/// URL template. URLs on other hosts than the template fail the
+/// check. The template needs no `{version}` placeholder, in which
+/// case only the host and the start of the path are checked.
+///
+/// This macro is enabled by the `html_root_url_updated` feature.
+///
+/// # Usage
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_playground_url is compiled.
+/// fn test_playground_url() {
+///     version_sync::assert_doc_url_updated!(
+///         "src/lib.rs",
+///         "html_playground_url",
+///         "https://play.rust-lang.org/"
+///     );
+/// }
+///
+/// # fn main() {
+/// #     test_playground_url();
+/// # }
+/// ```
+///
+/// # Fixing Outdated URLs
+///
+/// If the `VERSION_SYNC_FIX` environment variable is set, the macro
+/// will call [`fix_doc_url_with_options`] instead.
+///
+/// # Panics
+///
+/// If the attribute fails the check, `panic!` will be invoked.
+#[macro_export]
+#[cfg(feature = "html_root_url_updated")]
+macro_rules! assert_doc_url_updated {
+    ($path:expr, $key:expr, $template:expr) => {
+        let pkg_name = env!("CARGO_PKG_NAME");
+        let pkg_version = env!("CARGO_PKG_VERSION");
+        let result = $crate::run_reported(|reporter| {
+            let options = $crate::UrlOptions::new()
+                .template($template)
+                .other_hosts($crate::OtherHosts::Deny)
+                .reporter(reporter);
+            if $crate::fix_requested() {
+                $crate::fix_doc_url_with_options($path, $key, pkg_name, pkg_version, options)
+            } else {
+                $crate::check_doc_url_with_options($path, $key, pkg_name, pkg_version, options)
+            }
+        });
+        if let Err(err) = result {
+            panic!("{}", err);
+        }
+    };
+}
+
+/// Assert that the `html_root_url` attributes in all crate roots are
+/// up to date.
+///
+/// Packages with several binaries or examples have a crate root for
+/// each target. The macro calls [`check_package_html_root_urls`] on
+/// the `Cargo.toml` file of your package, which finds the targets and
+/// checks the `html_root_url` attributes in each of them.
+///
+/// This macro is enabled by the `html_root_url_updated` feature.
+///
+/// # Usage
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_html_root_url is compiled.
+/// fn test_html_root_url() {
+///     version_sync::assert_package_html_root_urls_updated!();
+/// }
+///
+/// # fn main() {
+/// #     test_html_root_url();
+/// # }
+/// ```
+///
+/// # Fixing Outdated URLs
+///
+/// If the `VERSION_SYNC_FIX` environment variable is set, the macro
+/// will call [`fix_package_html_root_urls`] instead.
+///
+/// # Panics
+///
+/// If any `html_root_url` fails the check, `panic!` will be invoked.
+#[macro_export]
+#[cfg(feature = "html_root_url_updated")]
+macro_rules! assert_package_html_root_urls_updated {
+    () => {
+        let pkg_name = env!("CARGO_PKG_NAME");
+        let pkg_version = env!("CARGO_PKG_VERSION");
+        let result = if $crate::fix_requested() {
+            $crate::run_reported(|reporter| {
+                $crate::fix_package_html_root_urls_with_reporter(
+                    "Cargo.toml",
+                    pkg_name,
+                    pkg_version,
+                    reporter,
+                )
+            })
+        } else {
+            $crate::run_reported(|reporter| {
+                $crate::check_package_html_root_urls_with_reporter(
+                    "Cargo.toml",
+                    pkg_name,
+                    pkg_version,
+                    reporter,
+                )
+            })
+        };
+        if let Err(err) = result {
+            panic!("{}", err);
+        }
+    };
+}
+
+/// Assert that `deprecated` attributes have a valid `since` version.
+///
+/// The macro will call [`check_deprecated_since`] on the Rust file or
+/// directory given in order to check that the `since` version of
+/// every `deprecated` attribute is a valid version number which is no
+/// greater than the current version of your package. The version is
+/// taken from the `$CARGO_PKG_VERSION` environment variable.
+///
+/// This macro is enabled by the `html_root_url_updated` feature.
+///
+/// # Usage
+///
+/// The typical way to use this macro is from an integration test:
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_deprecated_since is compiled.
+/// fn test_deprecated_since() {
+///     version_sync::assert_deprecated_since_valid!("src");
+/// }
+///
+/// # fn main() {
+/// #     test_deprecated_since();
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// If any `deprecated` attribute fails the check, `panic!` will be
+/// invoked.
+#[macro_export]
+#[cfg(feature = "html_root_url_updated")]
+macro_rules! assert_deprecated_since_valid {
+    ($path:expr) => {
+        let pkg_version = env!("CARGO_PKG_VERSION");
+        if let Err(err) = $crate::run_reported(|reporter| {
+            $crate::check_deprecated_since_with_reporter($path, pkg_version, reporter)
+        }) {
+            panic!("{}", err);
+        }
+    };
+}
+
+/// Create [`Placeholders`] from the metadata in `Cargo.toml`.
+///
+/// The placeholders are taken from the environment variables which
+/// Cargo sets when compiling your crate:
+///
+/// * `{msrv}`: the `rust-version` field, from `$CARGO_PKG_RUST_VERSION`.
+/// * `{repository}`: the `repository` field, from
+///   `$CARGO_PKG_REPOSITORY`.
+/// * `{homepage}`: the `homepage` field, from `$CARGO_PKG_HOMEPAGE`.
+/// * `{license}`: the `license` field, from `$CARGO_PKG_LICENSE`.
+///
+/// Fields which are not set give no placeholders. The
+/// [`assert_contains_substring`], [`assert_contains_regex`], and
+/// [`assert_only_contains_regex`] macros use these placeholders by
+/// default. Add your own placeholders to them if needed:
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_readme_mentions_msrv is
+/// # // compiled.
+/// fn test_readme_mentions_msrv() {
+///     let mut placeholders = version_sync::cargo_placeholders!();
+///     placeholders.insert("channel", "stable");
+///     version_sync::assert_contains_substring!("README.md", "Rust {msrv}", &placeholders);
+/// }
+///
+/// # fn main() {}
+/// ```
+///
+/// Values from `[package.metadata]` are not available at compile
+/// time. Use `Placeholders::from_manifest`, which is enabled by the
+/// `config` feature, to read them.
+#[macro_export]
+macro_rules! cargo_placeholders {
+    () => {{
+        let mut placeholders = $crate::Placeholders::new();
+        for (name, value) in [
+            ("msrv", option_env!("CARGO_PKG_RUST_VERSION")),
+            ("repository", option_env!("CARGO_PKG_REPOSITORY")),
+            ("homepage", option_env!("CARGO_PKG_HOMEPAGE")),
+            ("license", option_env!("CARGO_PKG_LICENSE")),
+        ] {
+            // Cargo sets the variables to the empty string for
+            // missing fields.
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                placeholders.insert(name, value);
+            }
+        }
+        placeholders
+    }};
+}
+
+/// Create [`Checks`] for the current package.
+///
+/// The package name and version are taken from the `$CARGO_PKG_NAME`
+/// and `$CARGO_PKG_VERSION` environment variables, and the
+/// placeholders from [`cargo_placeholders`]. Add the checks and call
+/// [`Checks::assert`] to run them all and fail once with a summary of
+/// every outdated file:
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_version_numbers is compiled.
+/// # #[cfg(all(feature = "markdown_deps_updated", feature = "html_root_url_updated"))]
+/// fn test_version_numbers() {
+///     version_sync::checks!()
+///         .markdown_deps("README.md")
+///         .html_root_url("src/lib.rs")
+///         .contains_substring("README.md", "### Version {version}")
+///         .assert();
+/// }
+///
+/// # fn main() {
+/// #     #[cfg(all(feature = "markdown_deps_updated", feature = "html_root_url_updated"))]
+/// #     test_version_numbers();
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// The `assert` method panics after running all checks if any of them
+/// failed. The panic message has a row for each failed location.
+#[macro_export]
+macro_rules! checks {
+    () => {
+        $crate::Checks::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+            .placeholders($crate::cargo_placeholders!())
+    };
+}
+
 /// Assert that versions numbers are up to date via substring matching.
 ///
 /// This macro allows you verify that the current version number is
@@ -243,6 +904,12 @@ macro_rules! assert_html_root_url_updated {
 ///
 /// * `{version}`: the current version number of your package.
 /// * `{name}`: the name of your package.
+/// * `{msrv}`, `{repository}`, `{homepage}`, and `{license}`: the
+///   fields from your `Cargo.toml`, see [`cargo_placeholders`].
+///
+/// The other placeholders are listed in the [crate
+/// documentation](crate#templates). Pass your own [`Placeholders`]
+/// as a third argument to use other placeholders.
 ///
 /// This way you can search for things like `"Latest version of {name}
 /// is: {version}"` and make sure you update your READMEs and
@@ -258,9 +925,22 @@ macro_rules! assert_html_root_url_updated {
 #[macro_export]
 macro_rules! assert_contains_substring {
     ($path:expr, $format:expr) => {
+        $crate::assert_contains_substring!($path, $format, &$crate::cargo_placeholders!())
+    };
+    ($path:expr, $format:expr, $placeholders:expr) => {
         let pkg_name = env!("CARGO_PKG_NAME");
         let pkg_version = env!("CARGO_PKG_VERSION");
-        if let Err(err) = $crate::check_contains_substring($path, $format, pkg_name, pkg_version) {
+        if let Err(err) = $crate::run_reported(|reporter| {
+            $crate::check_contains_substring_with_options(
+                $path,
+                $format,
+                pkg_name,
+                pkg_version,
+                $crate::TemplateOptions::new()
+                    .placeholders($placeholders)
+                    .reporter(reporter),
+            )
+        }) {
             panic!("{}", err);
         }
     };
@@ -309,6 +989,12 @@ macro_rules! assert_contains_substring {
 ///
 /// * `{version}`: the current version number of your package.
 /// * `{name}`: the name of your package.
+/// * `{msrv}`, `{repository}`, `{homepage}`, and `{license}`: the
+///   fields from your `Cargo.toml`, see [`cargo_placeholders`].
+///
+/// The other placeholders are listed in the [crate
+/// documentation](crate#templates). Pass your own [`Placeholders`]
+/// as a third argument to use other placeholders.
 ///
 /// This way you can search for things like `"Latest version of {name}
 /// is: {version}"` and make sure you update your READMEs and
@@ -322,9 +1008,22 @@ macro_rules! assert_contains_substring {
 #[cfg(feature = "contains_regex")]
 macro_rules! assert_contains_regex {
     ($path:expr, $format:expr) => {
+        $crate::assert_contains_regex!($path, $format, &$crate::cargo_placeholders!())
+    };
+    ($path:expr, $format:expr, $placeholders:expr) => {
         let pkg_name = env!("CARGO_PKG_NAME");
         let pkg_version = env!("CARGO_PKG_VERSION");
-        if let Err(err) = $crate::check_contains_regex($path, $format, pkg_name, pkg_version) {
+        if let Err(err) = $crate::run_reported(|reporter| {
+            $crate::check_contains_regex_with_options(
+                $path,
+                $format,
+                pkg_name,
+                pkg_version,
+                $crate::TemplateOptions::new()
+                    .placeholders($placeholders)
+                    .reporter(reporter),
+            )
+        }) {
             panic!("{}", err);
         }
     };
@@ -375,6 +1074,12 @@ macro_rules! assert_contains_regex {
 ///
 /// * `{version}`: the version number of your package.
 /// * `{name}`: the name of your package.
+/// * `{msrv}`, `{repository}`, `{homepage}`, and `{license}`: the
+///   fields from your `Cargo.toml`, see [`cargo_placeholders`].
+///
+/// The other placeholders are listed in the [crate
+/// documentation](crate#templates). Pass your own [`Placeholders`]
+/// as a third argument to use other placeholders.
 ///
 /// The `{version}` placeholder will match compatible versions,
 /// meaning that `{version}` will match all of `1.2.3`, `1.2`, and `1`
@@ -389,9 +1094,186 @@ macro_rules! assert_contains_regex {
 #[cfg(feature = "contains_regex")]
 macro_rules! assert_only_contains_regex {
     ($path:expr, $format:expr) => {
+        $crate::assert_only_contains_regex!($path, $format, &$crate::cargo_placeholders!())
+    };
+    ($path:expr, $format:expr, $placeholders:expr) => {
         let pkg_name = env!("CARGO_PKG_NAME");
         let pkg_version = env!("CARGO_PKG_VERSION");
-        if let Err(err) = $crate::check_only_contains_regex($path, $format, pkg_name, pkg_version) {
+        if let Err(err) = $crate::run_reported(|reporter| {
+            $crate::check_only_contains_regex_with_options(
+                $path,
+                $format,
+                pkg_name,
+                pkg_version,
+                $crate::TemplateOptions::new()
+                    .placeholders($placeholders)
+                    .reporter(reporter),
+            )
+        }) {
+            panic!("{}", err);
+        }
+    };
+}
+
+/// Assert that the changelog has an entry for the current version.
+///
+/// The macro will call [`check_changelog`] on the Markdown file given
+/// in order to check that the topmost release entry is for the
+/// current version of your package, that the entries are in
+/// descending order with valid dates, and that the link references
+/// point at the right tags. The version is taken from the
+/// `$CARGO_PKG_VERSION` environment variable.
+///
+/// This macro is enabled by the `changelog` feature.
+///
+/// # Usage
+///
+/// The typical way to use this macro is from an integration test:
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_changelog is compiled.
+/// fn test_changelog() {
+///     version_sync::assert_changelog_updated!("README.md");
+/// }
+///
+/// # fn main() {
+/// #     test_changelog();
+/// # }
+/// ```
+///
+/// Unlike [`assert_contains_regex`] with a template such as
+/// `"^### Version {version}"`, this also fails when the entry for the
+/// current version is not the topmost entry.
+///
+/// # Panics
+///
+/// If the changelog fails the check, `panic!` will be invoked.
+#[macro_export]
+#[cfg(feature = "changelog")]
+macro_rules! assert_changelog_updated {
+    ($path:expr) => {
+        let pkg_version = env!("CARGO_PKG_VERSION");
+        if let Err(err) = $crate::run_reported(|reporter| {
+            $crate::check_changelog_with_reporter($path, pkg_version, reporter)
+        }) {
+            panic!("{}", err);
+        }
+    };
+}
+
+/// Assert that all configured checks pass.
+///
+/// The macro calls [`check_config`] on the current directory. This
+/// runs all the checks listed in a `version-sync.toml` file or in the
+/// `[package.metadata.version-sync]` table of your `Cargo.toml` file.
+/// The package name and version are read from `Cargo.toml`.
+///
+/// This macro is enabled by the `config` feature.
+///
+/// # Usage
+///
+/// List the checks in your `Cargo.toml` file:
+///
+/// ```toml,no_sync
+/// [[package.metadata.version-sync.checks]]
+/// kind = "markdown-deps"
+/// files = ["README.md"]
+///
+/// [[package.metadata.version-sync.checks]]
+/// kind = "html-root-url"
+/// files = ["src/lib.rs"]
+/// ```
+///
+/// and run them all from a single integration test:
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_version_numbers is compiled.
+/// fn test_version_numbers() {
+///     version_sync::assert_all_from_config!();
+/// }
+///
+/// # fn main() {}
+/// ```
+///
+/// The same test file can then be used unchanged by all crates in a
+/// repository. See [`check_config`] for the supported checks.
+///
+/// # Fixing Outdated Version Numbers
+///
+/// If the `VERSION_SYNC_FIX` environment variable is set, the macro
+/// will call [`fix_config`] instead. This works like
+/// [`assert_markdown_deps_updated`] and
+/// [`assert_html_root_url_updated`] do in this case.
+///
+/// # Panics
+///
+/// If the configuration is invalid or if any check fails, `panic!`
+/// will be invoked after all checks have run.
+#[macro_export]
+#[cfg(feature = "config")]
+macro_rules! assert_all_from_config {
+    () => {
+        let result = if $crate::fix_requested() {
+            $crate::run_reported(|reporter| $crate::fix_config_with_reporter(".", reporter))
+        } else {
+            $crate::run_reported(|reporter| $crate::check_config_with_reporter(".", reporter))
+        };
+        if let Err(err) = result {
+            panic!("{}", err);
+        }
+    };
+}
+
+/// Assert that all members of a workspace are up to date.
+///
+/// The macro calls [`check_workspace`] on the workspace root
+/// directory given. This checks the README and the `html_root_url`
+/// attribute of every workspace member against the name and version
+/// found in the member's `Cargo.toml` file.
+///
+/// This macro is enabled by the `workspace` feature.
+///
+/// # Usage
+///
+/// Tests are run with the current directory set to the package
+/// directory, so a test in a member at `crates/foo` would use:
+///
+/// ```rust
+/// #[test]
+/// # fn fake_hidden_test_case() {}
+/// # // The above function ensures test_workspace is compiled.
+/// fn test_workspace() {
+///     version_sync::assert_workspace_updated!("../..");
+/// }
+///
+/// # fn main() {}
+/// ```
+///
+/// Use `"."` if the workspace root is itself a package with tests.
+///
+/// # Fixing Outdated Version Numbers
+///
+/// If the `VERSION_SYNC_FIX` environment variable is set, the macro
+/// will call [`fix_workspace`] instead.
+///
+/// # Panics
+///
+/// If a manifest is invalid or if any check fails, `panic!` will be
+/// invoked after all members have been checked.
+#[macro_export]
+#[cfg(feature = "workspace")]
+macro_rules! assert_workspace_updated {
+    ($root:expr) => {
+        let result = if $crate::fix_requested() {
+            $crate::run_reported(|reporter| $crate::fix_workspace_with_reporter($root, reporter))
+        } else {
+            $crate::run_reported(|reporter| $crate::check_workspace_with_reporter($root, reporter))
+        };
+        if let Err(err) = result {
             panic!("{}", err);
         }
     };
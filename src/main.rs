@@ -0,0 +1,389 @@
+//! The `version-sync` command-line program.
+//!
+//! This runs the checks from the library on the package described by
+//! a `Cargo.toml` file. It is built when the `cli` feature is enabled.
+
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+
+use version_sync::{
+    check_changelog_with_reporter, check_contains_regex_with_options,
+    check_contains_substring_with_options, check_crate_docs_with_reporter,
+    check_deprecated_since_with_reporter, check_html_root_url_with_reporter,
+    check_markdown_deps_with_reporter, check_only_contains_regex_with_options,
+    check_rustdoc_deps_with_reporter, check_rustdoc_links_with_reporter,
+    fix_html_root_url_with_reporter, fix_markdown_deps_with_reporter, Error, Format, Package,
+    Placeholders, Report, Reporter, TemplateOptions,
+};
+
+const USAGE: &str = "\
+Usage: version-sync [OPTIONS] <COMMAND> <ARGS>...
+
+Commands:
+  markdown-deps <FILE>...                Check dependencies in Markdown code blocks
+  rustdoc-deps <FILE>...                 Check dependencies in code blocks in Rust doc comments
+  rustdoc-links <PATH>...                Check docs.rs and crates.io links in Rust doc comments
+  crate-docs <FILE>...                   Check doc comments and included Markdown files
+  html-root-url <FILE>...                Check html_root_url attributes in Rust files
+  deprecated-since <PATH>...             Check the since version of deprecated attributes
+  changelog <FILE>...                    Check that a changelog has an entry for the current version
+  contains <FILE> <TEMPLATE>             Check that FILE contains TEMPLATE
+  contains-regex <FILE> <TEMPLATE>       Check that FILE matches the regex TEMPLATE
+  only-contains-regex <FILE> <TEMPLATE>  Check that all matches of TEMPLATE use the current version
+
+Options:
+  --manifest-path <PATH>  Read package name and version from PATH [default: Cargo.toml]
+  --fix                   Update outdated version numbers (markdown-deps and html-root-url)
+  --format <FORMAT>       Print results as text, json, sarif, github, or gitlab [default: text,
+                          or github in GitHub Actions]
+  --output <PATH>         Write the json, sarif, github, or gitlab results to PATH
+  -h, --help              Print this help
+  -V, --version           Print the version of version-sync
+
+Templates can use {name} and {version} for the package name and version,
+{major}, {minor}, {patch}, {major_minor}, {pre}, and {build} for their parts,
+{date} and {year} for today's date, {env:VAR} for environment variables, and
+{msrv}, {repository}, {homepage}, {license}, and {metadata.KEY} for the fields
+in the manifest.
+
+Exit status is 0 if all checks pass, 1 if a check fails, and 2 if
+the command line or the manifest is invalid.";
+
+/// Exit status for invalid command lines and manifests.
+const USAGE_ERROR: u8 = 2;
+
+/// The parsed command line.
+#[derive(Debug, PartialEq, Eq)]
+struct Args {
+    manifest_path: String,
+    fix: bool,
+    format: Option<Format>,
+    output: Option<String>,
+    command: String,
+    operands: Vec<String>,
+}
+
+impl Args {
+    /// Parse command line arguments, excluding the program name.
+    ///
+    /// Returns `Ok(None)` if the arguments asked for help or the
+    /// version, which has already been printed.
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Args>, String> {
+        let mut manifest_path = String::from("Cargo.toml");
+        let mut fix = false;
+        let mut format = None;
+        let mut output = None;
+        let mut positional = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    return Ok(None);
+                }
+                "-V" | "--version" => {
+                    println!("version-sync {}", env!("CARGO_PKG_VERSION"));
+                    return Ok(None);
+                }
+                "--fix" => fix = true,
+                "--manifest-path" => {
+                    manifest_path = args
+                        .next()
+                        .ok_or_else(|| String::from("--manifest-path needs a value"))?;
+                }
+                "--format" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| String::from("--format needs a value"))?;
+                    format = Some(value.parse().map_err(|err: Error| err.to_string())?);
+                }
+                "--output" => {
+                    output = Some(
+                        args.next()
+                            .ok_or_else(|| String::from("--output needs a value"))?,
+                    );
+                }
+                "--" => {
+                    positional.extend(args.by_ref());
+                }
+                _ => {
+                    if let Some(path) = arg.strip_prefix("--manifest-path=") {
+                        manifest_path = String::from(path);
+                    } else if let Some(value) = arg.strip_prefix("--format=") {
+                        format = Some(value.parse().map_err(|err: Error| err.to_string())?);
+                    } else if let Some(path) = arg.strip_prefix("--output=") {
+                        output = Some(String::from(path));
+                    } else if arg.starts_with('-') && arg.len() > 1 {
+                        return Err(format!("unknown option {arg}"));
+                    } else {
+                        positional.push(arg);
+                    }
+                }
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        let command = positional
+            .next()
+            .ok_or_else(|| String::from("no command given"))?;
+        let operands: Vec<String> = positional.collect();
+
+        match command.as_str() {
+            "markdown-deps" | "html-root-url" => {
+                if operands.is_empty() {
+                    return Err(format!("{command} needs at least one file"));
+                }
+            }
+            "rustdoc-deps" | "rustdoc-links" | "crate-docs" | "deprecated-since" | "changelog" => {
+                if operands.is_empty() {
+                    return Err(format!("{command} needs at least one file"));
+                }
+                if fix {
+                    return Err(format!("{command} does not support --fix"));
+                }
+            }
+            "contains" | "contains-regex" | "only-contains-regex" => {
+                if operands.len() != 2 {
+                    return Err(format!("{command} needs a file and a template"));
+                }
+                if fix {
+                    return Err(format!("{command} does not support --fix"));
+                }
+            }
+            _ => return Err(format!("unknown command {command}")),
+        }
+
+        Ok(Some(Args {
+            manifest_path,
+            fix,
+            format,
+            output,
+            command,
+            operands,
+        }))
+    }
+
+    /// Run the command on `package` and return the result for each
+    /// file checked. Status information is sent to `reporter`.
+    fn run(
+        &self,
+        package: &Package,
+        placeholders: &Placeholders,
+        reporter: &mut dyn Reporter,
+    ) -> Vec<Result<(), Error>> {
+        let name = package.name.as_str();
+        let version = package.version.as_str();
+        let files = self.operands.iter();
+        match (self.command.as_str(), self.fix) {
+            ("markdown-deps", false) => files
+                .map(|path| check_markdown_deps_with_reporter(path, name, version, reporter))
+                .collect(),
+            ("markdown-deps", true) => files
+                .map(|path| fix_markdown_deps_with_reporter(path, name, version, reporter))
+                .collect(),
+            ("rustdoc-deps", _) => files
+                .map(|path| check_rustdoc_deps_with_reporter(path, name, version, reporter))
+                .collect(),
+            ("rustdoc-links", _) => files
+                .map(|path| check_rustdoc_links_with_reporter(path, name, version, reporter))
+                .collect(),
+            ("crate-docs", _) => files
+                .map(|path| check_crate_docs_with_reporter(path, name, version, reporter))
+                .collect(),
+            ("deprecated-since", _) => files
+                .map(|path| check_deprecated_since_with_reporter(path, version, reporter))
+                .collect(),
+            ("changelog", _) => files
+                .map(|path| check_changelog_with_reporter(path, version, reporter))
+                .collect(),
+            ("html-root-url", false) => files
+                .map(|path| check_html_root_url_with_reporter(path, name, version, reporter))
+                .collect(),
+            ("html-root-url", true) => files
+                .map(|path| fix_html_root_url_with_reporter(path, name, version, reporter))
+                .collect(),
+            (command, _) => {
+                let (path, template) = (&self.operands[0], &self.operands[1]);
+                vec![match command {
+                    "contains" => check_contains_substring_with_options(
+                        path,
+                        template,
+                        name,
+                        version,
+                        TemplateOptions::new()
+                            .placeholders(placeholders)
+                            .reporter(reporter),
+                    ),
+                    "contains-regex" => check_contains_regex_with_options(
+                        path,
+                        template,
+                        name,
+                        version,
+                        TemplateOptions::new()
+                            .placeholders(placeholders)
+                            .reporter(reporter),
+                    ),
+                    _ => check_only_contains_regex_with_options(
+                        path,
+                        template,
+                        name,
+                        version,
+                        TemplateOptions::new()
+                            .placeholders(placeholders)
+                            .reporter(reporter),
+                    ),
+                }]
+            }
+        }
+    }
+}
+
+/// Format `err` for the terminal.
+///
+/// Parse errors in TOML files do not mention the file by themselves.
+fn describe(err: &Error) -> String {
+    match err {
+        Error::Toml {
+            path,
+            line,
+            message,
+        } => format!("could not parse {path} (line {line}): {message}"),
+        _ => err.to_string(),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => return ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            return ExitCode::from(USAGE_ERROR);
+        }
+    };
+
+    let manifest = Package::from_manifest(&args.manifest_path).and_then(|package| {
+        Placeholders::from_manifest(&args.manifest_path).map(|placeholders| (package, placeholders))
+    });
+    let (package, placeholders) = match manifest {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            eprintln!("error: {}", describe(&err));
+            return ExitCode::from(USAGE_ERROR);
+        }
+    };
+
+    // The format from the command line takes precedence over the
+    // environment.
+    let format = match args.format.map_or_else(Format::from_env, Ok) {
+        Ok(format) => format,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::from(USAGE_ERROR);
+        }
+    };
+
+    let mut report = Report::for_format(format);
+    let mut failed = false;
+    for result in args.run(&package, &placeholders, &mut report) {
+        if let Err(err) = result {
+            eprintln!("error: {}", describe(&err));
+            failed = true;
+        }
+    }
+
+    let rendered = report.render(format);
+    match &args.output {
+        Some(path) => {
+            if let Err(err) = fs::write(path, rendered) {
+                eprintln!("error: could not write {path}: {err}");
+                return ExitCode::from(USAGE_ERROR);
+            }
+        }
+        None => print!("{rendered}"),
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        Args::parse(args.iter().map(|arg| String::from(*arg)))
+    }
+
+    #[test]
+    fn parse_options() {
+        assert_eq!(
+            parse(&[
+                "--fix",
+                "markdown-deps",
+                "README.md",
+                "--manifest-path=foo.toml"
+            ]),
+            Ok(Some(Args {
+                manifest_path: String::from("foo.toml"),
+                fix: true,
+                format: None,
+                output: None,
+                command: String::from("markdown-deps"),
+                operands: vec![String::from("README.md")],
+            }))
+        );
+    }
+
+    #[test]
+    fn parse_template_after_double_dash() {
+        assert_eq!(
+            parse(&["contains", "--", "README.md", "-{version}"]),
+            Ok(Some(Args {
+                manifest_path: String::from("Cargo.toml"),
+                fix: false,
+                format: None,
+                output: None,
+                command: String::from("contains"),
+                operands: vec![String::from("README.md"), String::from("-{version}")],
+            }))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse(&[]), Err(String::from("no command given")));
+        assert_eq!(
+            parse(&["frobnicate"]),
+            Err(String::from("unknown command frobnicate"))
+        );
+        assert_eq!(parse(&["-x"]), Err(String::from("unknown option -x")));
+        assert_eq!(
+            parse(&["--manifest-path"]),
+            Err(String::from("--manifest-path needs a value"))
+        );
+        assert_eq!(
+            parse(&["--format", "xml", "html-root-url", "src/lib.rs"]),
+            Err(String::from(
+                "unknown output format \"xml\", expected text, json, sarif, github, or gitlab"
+            ))
+        );
+        assert_eq!(
+            parse(&["html-root-url"]),
+            Err(String::from("html-root-url needs at least one file"))
+        );
+        assert_eq!(
+            parse(&["contains", "README.md"]),
+            Err(String::from("contains needs a file and a template"))
+        );
+        assert_eq!(
+            parse(&["--fix", "contains-regex", "README.md", "{version}"]),
+            Err(String::from("contains-regex does not support --fix"))
+        );
+    }
+}
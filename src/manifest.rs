@@ -0,0 +1,315 @@
+#![cfg(any(feature = "cli", feature = "config", feature = "workspace"))]
+use std::path::{Path, PathBuf};
+
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+use crate::error::{Error, Result};
+use crate::helpers::read_toml;
+use crate::template::Placeholders;
+
+/// The name and version of a package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    /// The package name.
+    pub name: String,
+    /// The package version.
+    pub version: String,
+}
+
+impl Package {
+    /// Read the package name and version from a `Cargo.toml` file.
+    ///
+    /// This is useful when version-sync is used outside of `cargo
+    /// test`, where the `CARGO_PKG_NAME` and `CARGO_PKG_VERSION`
+    /// environment variables are not available.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Toml`] is returned if the file cannot be parsed
+    /// and an [`Error::Manifest`] if the name or version is missing.
+    ///
+    /// A version given as `version.workspace = true` is read from the
+    /// `[workspace.package]` table of the workspace root manifest,
+    /// which is found the same way as Cargo finds it.
+    pub fn from_manifest(path: &str) -> Result<Package> {
+        let document = read_toml(path)?;
+        Package::from_document(path, &document)
+    }
+
+    /// Read the package name and version from an already parsed
+    /// manifest.
+    pub(crate) fn from_document(path: &str, document: &ImDocument<String>) -> Result<Package> {
+        let package = document.get("package");
+        let version = match package.and_then(|package| package.get("version")) {
+            Some(version) if inherited(version) => {
+                let (root_path, root) = find_workspace_root(path, document)?;
+                let workspace_package = root
+                    .get("workspace")
+                    .and_then(|workspace| workspace.get("package"));
+                string_field(
+                    &root_path,
+                    workspace_package,
+                    "workspace.package",
+                    "version",
+                )?
+            }
+            _ => string_field(path, package, "package", "version")?,
+        };
+        Ok(Package {
+            name: string_field(path, package, "package", "name")?,
+            version,
+        })
+    }
+}
+
+/// Placeholders for the fields of the `[package]` table.
+const PACKAGE_PLACEHOLDERS: [(&str, &str); 4] = [
+    ("msrv", "rust-version"),
+    ("repository", "repository"),
+    ("homepage", "homepage"),
+    ("license", "license"),
+];
+
+impl Placeholders {
+    /// Read placeholders from the package metadata in a `Cargo.toml`
+    /// file.
+    ///
+    /// The placeholders are `{msrv}` for the `rust-version` field and
+    /// `{repository}`, `{homepage}`, and `{license}` for the fields
+    /// with the same names. Fields inherited from the
+    /// workspace are supported. The values in `[package.metadata]` are
+    /// available as `{metadata.key}`, with a dot between the keys of
+    /// nested tables, such as `{metadata.release.tag}`. Missing fields
+    /// give no placeholders.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Toml`] is returned if the file cannot be parsed
+    /// and an [`Error::Manifest`] if an inherited field cannot be
+    /// found in the workspace.
+    pub fn from_manifest(path: &str) -> Result<Placeholders> {
+        let document = read_toml(path)?;
+        Placeholders::from_document(path, &document)
+    }
+
+    /// Read placeholders from an already parsed manifest.
+    pub(crate) fn from_document(path: &str, document: &ImDocument<String>) -> Result<Placeholders> {
+        let mut placeholders = Placeholders::new();
+        let package = match document.get("package") {
+            Some(package) => package,
+            None => return Ok(placeholders),
+        };
+
+        for (name, key) in PACKAGE_PLACEHOLDERS {
+            let value = match package.get(key) {
+                Some(value) if inherited(value) => {
+                    let (root_path, root) = find_workspace_root(path, document)?;
+                    let workspace_package = root
+                        .get("workspace")
+                        .and_then(|workspace| workspace.get("package"));
+                    string_field(&root_path, workspace_package, "workspace.package", key)?
+                }
+                Some(value) => match value.as_str() {
+                    Some(value) => String::from(value),
+                    None => continue,
+                },
+                None => continue,
+            };
+            placeholders.insert(name, &value);
+        }
+
+        if let Some(metadata) = package.get("metadata").and_then(Item::as_table_like) {
+            insert_metadata(&mut placeholders, "metadata", metadata);
+        }
+        Ok(placeholders)
+    }
+}
+
+/// Add the string, number, and boolean values in `table` as
+/// placeholders named `{prefix.key}`.
+fn insert_metadata(placeholders: &mut Placeholders, prefix: &str, table: &dyn TableLike) {
+    for (key, item) in table.iter() {
+        let name = format!("{prefix}.{key}");
+        if let Some(table) = item.as_table_like() {
+            insert_metadata(placeholders, &name, table);
+            continue;
+        }
+        let value = match item.as_value() {
+            Some(Value::String(value)) => value.value().clone(),
+            Some(Value::Integer(value)) => value.value().to_string(),
+            Some(Value::Float(value)) => value.value().to_string(),
+            Some(Value::Boolean(value)) => value.value().to_string(),
+            _ => continue,
+        };
+        placeholders.insert(&name, &value);
+    }
+}
+
+/// Check if a manifest field is inherited from the workspace with
+/// `field.workspace = true`.
+pub fn inherited(item: &Item) -> bool {
+    item.get("workspace").and_then(|value| value.as_bool()) == Some(true)
+}
+
+/// Find the workspace root manifest for the package manifest in
+/// `path`.
+///
+/// Like Cargo, we use the `package.workspace` key if present, and
+/// otherwise search upwards for a manifest with a `[workspace]`
+/// table, starting with the package manifest itself.
+pub fn find_workspace_root(
+    path: &str,
+    document: &ImDocument<String>,
+) -> Result<(String, ImDocument<String>)> {
+    let manifest_error = |message: &str| Error::Manifest {
+        path: String::from(path),
+        message: String::from(message),
+    };
+    let dir = match Path::new(path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let explicit_root = document
+        .get("package")
+        .and_then(|package| package.get("workspace"))
+        .and_then(|workspace| workspace.as_str());
+    if let Some(root) = explicit_root {
+        let root_path = dir.join(root).join("Cargo.toml");
+        let root_path = root_path.to_string_lossy().into_owned();
+        let root_document = read_toml(&root_path)?;
+        return match root_document.get("workspace") {
+            Some(_) => Ok((root_path, root_document)),
+            None => Err(manifest_error(
+                "package.workspace does not point to a workspace",
+            )),
+        };
+    }
+
+    if document.get("workspace").is_some() {
+        return Ok((String::from(path), document.clone()));
+    }
+    let dir = dir.canonicalize().map_err(|source| Error::Io {
+        path: dir.to_string_lossy().into_owned(),
+        source,
+    })?;
+    for ancestor in dir.ancestors().skip(1) {
+        let candidate: PathBuf = ancestor.join("Cargo.toml");
+        if !candidate.is_file() {
+            continue;
+        }
+        let candidate = candidate.to_string_lossy().into_owned();
+        let candidate_document = read_toml(&candidate)?;
+        if candidate_document.get("workspace").is_some() {
+            return Ok((candidate, candidate_document));
+        }
+    }
+    Err(manifest_error(
+        "no workspace root found for inherited field",
+    ))
+}
+
+/// Look up a string `key` in the `table_name` table of a manifest.
+fn string_field(path: &str, table: Option<&Item>, table_name: &str, key: &str) -> Result<String> {
+    table
+        .and_then(|table| table.get(key))
+        .and_then(|value| value.as_str())
+        .map(String::from)
+        .ok_or_else(|| Error::Manifest {
+            path: String::from(path),
+            message: format!("no string value for {table_name}.{key}"),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn this_package() {
+        let package = Package::from_manifest("Cargo.toml").unwrap();
+        assert_eq!(package.name, env!("CARGO_PKG_NAME"));
+        assert_eq!(package.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn missing_version() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"[package]\nname = 'foo'\n").unwrap();
+        let path = file.path().to_str().unwrap();
+        assert_eq!(
+            Package::from_manifest(path).map_err(|err| err.to_string()),
+            Err(format!(
+                "invalid manifest {path}: no string value for package.version"
+            ))
+        );
+    }
+
+    #[test]
+    fn inherited_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = ['crates/*']\n\n[workspace.package]\nversion = '2.0.0'\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("crates/foo")).unwrap();
+        let manifest = root.join("crates/foo/Cargo.toml");
+        for version in ["version.workspace = true", "version = { workspace = true }"] {
+            std::fs::write(&manifest, format!("[package]\nname = 'foo'\n{version}\n")).unwrap();
+            let package = Package::from_manifest(manifest.to_str().unwrap()).unwrap();
+            assert_eq!(package.version, "2.0.0");
+        }
+    }
+
+    #[test]
+    fn inherited_version_without_workspace() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"[package]\nname = 'foo'\nversion.workspace = true\n")
+            .unwrap();
+        let path = file.path().to_str().unwrap();
+        assert!(matches!(
+            Package::from_manifest(path),
+            Err(Error::Manifest { .. })
+        ));
+    }
+
+    #[test]
+    fn placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\n\n[workspace.package]\nrust-version = '1.70'\n\n\
+             [package]\nname = 'foo'\nversion = '1.0.0'\nlicense = 'MIT'\n\
+             rust-version.workspace = true\n\n\
+             [package.metadata]\nchannel = 'stable'\nlevel = 3\n\
+             release = { tag = 'v1', sign = true }\nfeatures = ['a']\n",
+        )
+        .unwrap();
+        let placeholders =
+            Placeholders::from_manifest(root.join("Cargo.toml").to_str().unwrap()).unwrap();
+
+        let mut expected = Placeholders::new();
+        expected.insert("msrv", "1.70");
+        expected.insert("license", "MIT");
+        expected.insert("metadata.channel", "stable");
+        expected.insert("metadata.level", "3");
+        expected.insert("metadata.release.tag", "v1");
+        expected.insert("metadata.release.sign", "true");
+        assert_eq!(placeholders, expected);
+    }
+
+    #[test]
+    fn bad_toml() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"[package]\nname = foo\n").unwrap();
+        let path = file.path().to_str().unwrap();
+        assert!(matches!(
+            Package::from_manifest(path),
+            Err(Error::Toml { line: 2, .. })
+        ));
+    }
+}
@@ -1,10 +1,29 @@
 #![cfg(feature = "markdown_deps_updated")]
+use std::fs;
+use std::ops::Range;
+
 use pulldown_cmark::CodeBlockKind::Fenced;
 use pulldown_cmark::{Event, Parser, Tag, TagEnd};
 use semver::{Version, VersionReq};
-use toml::Value;
+use toml_edit::{ImDocument, TomlError};
+
+use crate::error::{Error, Result};
+use crate::helpers::{
+    dependency_tables, line_column, parse_version, read_path, source_lines, string_contents,
+    update_request, version_matches_request,
+};
+use crate::reporter::{CheckKind, Reporter, StdoutReporter};
 
-use crate::helpers::{indent, read_file, version_matches_request, Result};
+/// The kind of code found in a fenced code block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    /// TOML code, such as a `[dependencies]` table.
+    Toml,
+    /// Shell commands, such as `cargo add foo@1.2`.
+    Shell,
+    /// A console session where commands are prefixed with `$ `.
+    Console,
+}
 
 /// A fenced code block.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -13,64 +32,289 @@ struct CodeBlock {
     content: String,
     /// Line number starting with 1.
     first_line: usize,
+    /// The kind of code in the block.
+    kind: BlockKind,
+}
+
+impl CodeBlock {
+    /// Line number of the last line of the content, starting with 1.
+    fn last_line(&self) -> usize {
+        self.first_line + self.content.lines().count().max(1) - 1
+    }
+}
+
+/// A version requirement found in a code block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VersionRequest {
+    /// The requirement as written in the code block.
+    text: String,
+    /// Byte range of `text` in the code block. This is `None` when
+    /// the requirement is implied, such as for Git dependencies.
+    span: Option<Range<usize>>,
+}
+
+impl VersionRequest {
+    /// Parse the requirement.
+    fn parse(&self) -> Result<VersionReq, String> {
+        VersionReq::parse(&self.text).map_err(|err| format!("could not parse dependency: {err}"))
+    }
+}
+
+/// Check if two crate names are equal when `-` and `_` are treated
+/// as the same character, as Cargo does.
+pub(crate) fn same_crate_name(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .all(|(a, b)| a == b || (a == b'-' || a == b'_') && (b == b'-' || b == b'_'))
+}
+
+/// Check if the dependency `dep` found under `key` is a dependency on
+/// `pkg_name`.
+///
+/// A renamed dependency, such as `foo1 = { package = "foo" }`, is
+/// matched using its `package` field instead of the key.
+fn depends_on(pkg_name: &str, key: &str, dep: &toml_edit::Item) -> bool {
+    match dep.get("package").and_then(|package| package.as_str()) {
+        Some(package) => same_crate_name(pkg_name, package),
+        None => same_crate_name(pkg_name, key),
+    }
 }
 
-/// Extract a dependency on the given package from a TOML code block.
-fn extract_version_request(pkg_name: &str, block: &str) -> Result<VersionReq> {
-    match block.parse::<Value>() {
-        Ok(value) => {
-            let version = value
-                .get("dependencies")
-                .or_else(|| value.get("dev-dependencies"))
-                .and_then(|deps| deps.get(pkg_name))
-                .and_then(|dep| {
-                    dep.get("version")
-                        // pkg_name = { version = "1.2.3" }
-                        .and_then(|version| version.as_str())
-                        // pkg_name = { git = "..." }
-                        .or_else(|| dep.get("git").and(Some("*")))
-                        // pkg_name = "1.2.3"
-                        .or_else(|| dep.as_str())
+/// Extract all dependencies on the given package from a TOML code
+/// block, in the order they appear in the block.
+///
+/// All dependency tables known by Cargo are searched, see
+/// [`dependency_tables`]. Renamed dependencies are found using their
+/// `package` field. Returns an empty vector if the block has no
+/// dependency on the package.
+fn extract_version_requests(pkg_name: &str, block: &str) -> Result<Vec<VersionRequest>, TomlError> {
+    let document = ImDocument::parse(block)?;
+    let mut requests = dependency_tables(document.as_table())
+        .into_iter()
+        .filter_map(|deps| deps.as_table_like())
+        .flat_map(|deps| deps.iter())
+        .filter(|(key, dep)| depends_on(pkg_name, key, dep))
+        .filter_map(|(_, dep)| {
+            match dep.get("version") {
+                // pkg_name = { version = "1.2.3" }
+                Some(version) => version.as_str().map(|text| (text, version.span())),
+                // pkg_name = { git = "..." }
+                None if dep.get("git").is_some() => Some(("*", None)),
+                // pkg_name = "1.2.3"
+                None => dep.as_str().map(|text| (text, dep.span())),
+            }
+        })
+        .map(|(text, span)| VersionRequest {
+            text: String::from(text),
+            span: span.map(|span| string_contents(block, span)),
+        })
+        .collect::<Vec<_>>();
+    requests.sort_by_key(|request| request.span.as_ref().map(|span| span.start));
+    Ok(requests)
+}
+
+/// Flags of `cargo add` and `cargo install` which take their value
+/// as a separate argument. The value must not be mistaken for a crate
+/// name.
+const CARGO_VALUE_FLAGS: &[&str] = &[
+    "--bin",
+    "--branch",
+    "--color",
+    "--config",
+    "--example",
+    "--features",
+    "--git",
+    "--index",
+    "--jobs",
+    "--lockfile-path",
+    "--manifest-path",
+    "--package",
+    "--path",
+    "--profile",
+    "--registry",
+    "--rename",
+    "--rev",
+    "--root",
+    "--tag",
+    "--target",
+    "--target-dir",
+    "--vers",
+    "--version",
+    "-F",
+    "-Z",
+    "-j",
+    "-p",
+];
+
+/// Remove matching quotes around `word`, which starts at byte
+/// `offset`. The unquoted word is returned with its offset.
+fn unquote(word: &str, offset: usize) -> (&str, usize) {
+    let unquoted = ['"', '\''].iter().find_map(|&quote| {
+        word.strip_prefix(quote)
+            .and_then(|word| word.strip_suffix(quote))
+    });
+    match unquoted {
+        Some(unquoted) => (unquoted, offset + 1),
+        None => (word, offset),
+    }
+}
+
+/// Split a command line into words with their byte offsets in the
+/// line.
+///
+/// Words are separated by whitespace and matching quotes around a
+/// word are removed. A trailing `;` is split into a separate word and
+/// a word starting with `#` ends the line.
+fn split_words(line: &str) -> Vec<(&str, usize)> {
+    let mut words = Vec::new();
+    let mut offset = 0;
+    for word in line.split_whitespace() {
+        let start = offset + line[offset..].find(word).unwrap_or(0);
+        offset = start + word.len();
+        if word.starts_with('#') {
+            break;
+        }
+
+        let (word, separator) = match word.strip_suffix(';') {
+            Some(word) if !word.is_empty() => (word, true),
+            _ => (word, false),
+        };
+        words.push(unquote(word, start));
+        if separator {
+            words.push((";", start + word.len()));
+        }
+    }
+    words
+}
+
+/// Extract the version requirements on `pkg_name` from a `cargo add`
+/// or `cargo install` command. The `words` are the words of the
+/// command with their byte offsets in the code block.
+fn extract_command_requests(pkg_name: &str, words: &[(&str, usize)]) -> Vec<VersionRequest> {
+    let mut words = words.iter().skip_while(|(word, _)| word.contains('='));
+    if words.next().map(|(word, _)| *word) != Some("cargo") {
+        return Vec::new();
+    }
+    let mut words = words.skip_while(|(word, _)| word.starts_with('+'));
+    let subcommand = match words.next() {
+        Some((subcommand @ ("add" | "install"), _)) => *subcommand,
+        _ => return Vec::new(),
+    };
+
+    let mut requests = Vec::new();
+    let mut has_pkg = false;
+    let mut version_flag = None;
+    while let Some(&(word, offset)) = words.next() {
+        if let Some((flag, value)) = word.split_once('=').filter(|_| word.starts_with('-')) {
+            if flag == "--version" || flag == "--vers" {
+                version_flag = Some(unquote(value, offset + flag.len() + 1));
+            }
+        } else if CARGO_VALUE_FLAGS.contains(&word) {
+            let value = words.next();
+            if word == "--version" || word == "--vers" {
+                version_flag = value.copied();
+            }
+        } else if !word.starts_with('-') {
+            let (name, request) = match word.split_once('@') {
+                Some((name, request)) => (name, Some(request)),
+                None => (word, None),
+            };
+            if !same_crate_name(pkg_name, name) {
+                continue;
+            }
+            has_pkg = true;
+            if let Some(request) = request {
+                let start = offset + name.len() + 1;
+                requests.push(VersionRequest {
+                    text: String::from(request),
+                    span: Some(start..start + request.len()),
                 });
-            match version {
-                Some(version) => VersionReq::parse(version)
-                    .map_err(|err| format!("could not parse dependency: {err}")),
-                None => Err(format!("no dependency on {pkg_name}")),
             }
         }
-        Err(err) => Err(format!("{err}")),
     }
+
+    // Only `cargo install` takes the requirement from --version.
+    if subcommand == "install" && has_pkg && requests.is_empty() {
+        if let Some((request, start)) = version_flag {
+            requests.push(VersionRequest {
+                text: String::from(request),
+                span: Some(start..start + request.len()),
+            });
+        }
+    }
+    requests
 }
 
-/// Check if a code block language line says the block is TOML code.
-fn is_toml_block(lang: &str) -> bool {
+/// Extract the version requirements on `pkg_name` from the `cargo
+/// add` and `cargo install` commands in a shell code block.
+///
+/// Several commands can be given on one line when separated by `&&`,
+/// `||`, or `;`. In a console block, only lines starting with a `$ `
+/// prompt are commands. Commands without an explicit requirement are
+/// not returned.
+fn extract_shell_requests(pkg_name: &str, block: &str, kind: BlockKind) -> Vec<VersionRequest> {
+    let mut requests = Vec::new();
+    let mut line_start = 0;
+    for line in block.split_inclusive('\n') {
+        let offset = line_start;
+        line_start += line.len();
+        let command_line = match line.trim_start().strip_prefix("$ ") {
+            Some(command_line) => command_line,
+            None if kind == BlockKind::Console => continue,
+            None => line,
+        };
+        let command_start = offset + line.len() - command_line.len();
+
+        let words = split_words(command_line)
+            .into_iter()
+            .map(|(word, start)| (word, command_start + start))
+            .collect::<Vec<_>>();
+        for command in words.split(|(word, _)| matches!(*word, "&&" | "||" | ";")) {
+            requests.extend(extract_command_requests(pkg_name, command));
+        }
+    }
+    requests
+}
+
+/// Find the kind of a code block from its language line.
+///
+/// Returns `None` for blocks which should not be checked.
+fn block_kind(lang: &str) -> Option<BlockKind> {
     // Split the language line as LangString::parse from rustdoc:
     // https://github.com/rust-lang/rust/blob/1.20.0/src/librustdoc/html/markdown.rs#L922
-    let mut has_toml = false;
+    let mut kind = None;
     for token in lang.split(|c: char| !(c == '_' || c == '-' || c.is_alphanumeric())) {
         match token.trim() {
-            "no_sync" => return false,
-            "toml" => has_toml = true,
+            "no_sync" => return None,
+            "toml" => kind = Some(BlockKind::Toml),
+            "sh" | "bash" | "shell" => kind = Some(BlockKind::Shell),
+            "console" => kind = Some(BlockKind::Console),
             _ => {}
         }
     }
-    has_toml
+    kind
 }
 
-/// Find all TOML code blocks in a Markdown text.
-fn find_toml_blocks(text: &str) -> Vec<CodeBlock> {
+/// Find all TOML and shell code blocks in a Markdown text.
+fn find_code_blocks(text: &str) -> Vec<CodeBlock> {
     let parser = Parser::new(text);
     let mut code_blocks = Vec::new();
     let mut current_block = None;
     for (event, range) in parser.into_offset_iter() {
         match event {
-            Event::Start(Tag::CodeBlock(Fenced(lang))) if is_toml_block(&lang) => {
+            Event::Start(Tag::CodeBlock(Fenced(lang))) => {
+                let kind = match block_kind(&lang) {
+                    Some(kind) => kind,
+                    None => continue,
+                };
                 // Count number of newlines before the ```. This gives
                 // us the line number of the fence, counted from 0.
                 let line_count = text[..range.start].chars().filter(|&ch| ch == '\n').count();
                 current_block = Some(CodeBlock {
                     first_line: line_count + 2,
                     content: String::new(),
+                    kind,
                 });
             }
             Event::Text(code) => {
@@ -109,8 +353,26 @@ fn find_toml_blocks(text: &str) -> Vec<CodeBlock> {
 /// ```
 /// ~~~
 ///
-/// Both `dependencies` and `dev-dependencies` are examined. If you
-/// want to skip a block, add `no_sync` to the language line:
+/// All dependency tables are examined: `dependencies`,
+/// `dev-dependencies`, and `build-dependencies`, also when nested in
+/// a `target.'cfg(...)'` table, as well as `workspace.dependencies`.
+/// Every dependency on `pkg_name` in a block is checked, including
+/// renamed dependencies such as `foo1 = { package = "foo" }`. Names
+/// are compared with `-` and `_` treated as equal.
+///
+/// Shell code blocks marked with `sh`, `bash`, `shell`, or `console`
+/// are also examined. Here `cargo add foo@1.2` and `cargo install foo
+/// --version 1.2` commands are checked, also when written after a `$ `
+/// prompt. Shell blocks without such commands for `pkg_name` are
+/// ignored:
+///
+/// ~~~markdown
+/// ```sh
+/// $ cargo add foo@1.2
+/// ```
+/// ~~~
+///
+/// If you want to skip a block, add `no_sync` to the language line:
 ///
 /// ~~~markdown
 /// ```toml,no_sync
@@ -123,31 +385,375 @@ fn find_toml_blocks(text: &str) -> Vec<CodeBlock> {
 ///
 /// # Errors
 ///
-/// If any block fails the check, an `Err` is returned with a succinct
-/// error message. Status information has then already been printed on
-/// `stdout`.
+/// If any block fails the check, an [`Error::Failed`] is returned with
+/// an error per outdated dependency or invalid block. Status
+/// information has then already been printed on `stdout`.
 pub fn check_markdown_deps(path: &str, pkg_name: &str, pkg_version: &str) -> Result<()> {
-    let text = read_file(path).map_err(|err| format!("could not read {path}: {err}"))?;
-    let version = Version::parse(pkg_version)
-        .map_err(|err| format!("bad package version {pkg_version:?}: {err}"))?;
-
-    println!("Checking code blocks in {path}...");
-    let mut failed = false;
-    for block in find_toml_blocks(&text) {
-        let result = extract_version_request(pkg_name, &block.content)
-            .and_then(|request| version_matches_request(&version, &request));
-        match result {
+    check_markdown_deps_with_reporter(path, pkg_name, pkg_version, &mut StdoutReporter)
+}
+
+/// Check dependencies in Markdown code blocks and send status
+/// information to `reporter`.
+///
+/// This works like [`check_markdown_deps`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If any block fails the check, an [`Error::Failed`] is returned with
+/// an error per outdated dependency or invalid block.
+pub fn check_markdown_deps_with_reporter(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    reporter.check_started(CheckKind::MarkdownDeps, path, None);
+    let result = check_blocks(path, pkg_name, pkg_version, reporter);
+    reporter.check_finished(CheckKind::MarkdownDeps, path, &result);
+    result
+}
+
+/// Check all TOML and shell code blocks in `path`.
+fn check_blocks(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let text = read_path(path)?;
+    let version = parse_version(pkg_version)?;
+
+    let errors = check_text(
+        path,
+        &text,
+        &text,
+        pkg_name,
+        &version,
+        reporter,
+        &|line, column| (line, column),
+    );
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
+    }
+    Ok(())
+}
+
+/// Check all TOML and shell code blocks in the Markdown `text` from
+/// `path` and return the errors found.
+///
+/// The `position` function maps a line and column in `text` to the
+/// line and column reported for `path`, whose contents are `source`.
+/// This allows checking Markdown which was extracted from another
+/// file, such as Rust doc comments.
+pub(crate) fn check_text(
+    path: &str,
+    source: &str,
+    text: &str,
+    pkg_name: &str,
+    version: &Version,
+    reporter: &mut dyn Reporter,
+    position: &dyn Fn(usize, usize) -> (usize, usize),
+) -> Vec<Error> {
+    let mut errors = Vec::new();
+    for block in find_code_blocks(text) {
+        let block_errors = match block_requests(path, text, &block, pkg_name) {
+            Ok(requests) if requests.is_empty() => continue,
+            Ok(requests) => requests
+                .iter()
+                .filter_map(|request| check_request(path, text, &block, request, version).err())
+                .collect(),
+            Err(err) => vec![err],
+        };
+        let first_line = position(block.first_line, 1).0;
+        if block_errors.is_empty() {
+            reporter.location_ok(path, first_line);
+        }
+        let last_line = position(block.last_line(), 1).0;
+        let block_source = source_lines(source, first_line, last_line);
+        for err in block_errors {
+            let err = relocate(err, position);
+            reporter.location_mismatch(path, first_line, &err, block_source);
+            errors.push(err);
+        }
+    }
+    errors
+}
+
+/// Map the line and column of `err` using `position`.
+fn relocate(err: Error, position: &dyn Fn(usize, usize) -> (usize, usize)) -> Error {
+    match err {
+        Error::Mismatch {
+            path,
+            line,
+            column,
+            found,
+            expected,
+            message,
+        } => {
+            let (line, column) = position(line, column);
+            Error::Mismatch {
+                path,
+                line,
+                column,
+                found,
+                expected,
+                message,
+            }
+        }
+        Error::Toml {
+            path,
+            line,
+            message,
+        } => Error::Toml {
+            path,
+            line: position(line, 1).0,
+            message,
+        },
+        Error::MissingDependency {
+            path,
+            line,
+            pkg_name,
+        } => Error::MissingDependency {
+            path,
+            line: position(line, 1).0,
+            pkg_name,
+        },
+        err => err,
+    }
+}
+
+/// Find the dependencies on `pkg_name` in a single code block.
+///
+/// A TOML block without a dependency on `pkg_name` is an error, but
+/// a shell block without commands for `pkg_name` is simply ignored.
+fn block_requests(
+    path: &str,
+    text: &str,
+    block: &CodeBlock,
+    pkg_name: &str,
+) -> Result<Vec<VersionRequest>> {
+    if block.kind != BlockKind::Toml {
+        return Ok(extract_shell_requests(pkg_name, &block.content, block.kind));
+    }
+    match extract_version_requests(pkg_name, &block.content) {
+        Ok(requests) if requests.is_empty() => Err(Error::MissingDependency {
+            path: String::from(path),
+            line: block.first_line,
+            pkg_name: String::from(pkg_name),
+        }),
+        Ok(requests) => Ok(requests),
+        Err(err) => {
+            let offset = err.span().map_or(0, |span| span.start);
+            Err(Error::Toml {
+                path: String::from(path),
+                line: location(text, block, offset).0,
+                message: String::from(err.message().trim_end()),
+            })
+        }
+    }
+}
+
+/// Check a single dependency found in `block`.
+fn check_request(
+    path: &str,
+    text: &str,
+    block: &CodeBlock,
+    request: &VersionRequest,
+    version: &Version,
+) -> Result<()> {
+    let result = request
+        .parse()
+        .and_then(|request| version_matches_request(version, &request));
+    if let Err(message) = result {
+        let (line, column) = match request.span {
+            Some(ref span) => location(text, block, span.start),
+            None => (block.first_line, 1),
+        };
+        return Err(Error::Mismatch {
+            path: String::from(path),
+            line,
+            column,
+            found: request.text.clone(),
+            // Suggest the same requirement as the fix writes.
+            expected: update_request(&request.text, version).unwrap_or_else(|| version.to_string()),
+            message,
+        });
+    }
+    Ok(())
+}
+
+/// Find the line and column in `text` of `offset` in the content of
+/// `block`.
+fn location(text: &str, block: &CodeBlock, offset: usize) -> (usize, usize) {
+    match source_offset(text, block, offset) {
+        Some(offset) => line_column(text, offset),
+        None => {
+            let (line, column) = line_column(&block.content, offset);
+            (block.first_line + line - 1, column)
+        }
+    }
+}
+
+/// Find the byte offset in `text` which corresponds to `offset` in
+/// the content of `block`.
+///
+/// The content of a code block is the text between the fences with
+/// indentation and blockquote markers removed. We map an offset back
+/// to the Markdown text by finding the source line and checking that
+/// the content line is a suffix of it. `None` is returned if this is
+/// not the case, e.g., because tabs were expanded.
+fn source_offset(text: &str, block: &CodeBlock, offset: usize) -> Option<usize> {
+    let before = &block.content[..offset];
+    let content_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    let content_line = block.content[content_start..].split('\n').next()?;
+    let content_line = content_line.strip_suffix('\r').unwrap_or(content_line);
+
+    let line_idx = block.first_line - 1 + before.matches('\n').count();
+    let source_start = match line_idx {
+        0 => 0,
+        _ => text.match_indices('\n').nth(line_idx - 1)?.0 + 1,
+    };
+    let source_line = text[source_start..].split('\n').next()?;
+    let source_line = source_line.strip_suffix('\r').unwrap_or(source_line);
+
+    if !source_line.ends_with(content_line) {
+        return None;
+    }
+    Some(source_start + source_line.len() - content_line.len() + offset - content_start)
+}
+
+/// Update outdated dependencies in Markdown code blocks.
+///
+/// This function finds the same TOML and shell code blocks as
+/// [`check_markdown_deps`], but instead of merely reporting outdated
+/// dependencies on `pkg_name`, it rewrites the version requirement in
+/// place so that it matches `pkg_version`. The precision of the
+/// requirement is kept, so `"0.1"` becomes `"0.2"` and `"0.1.2"`
+/// becomes `"0.2.0"` when `pkg_version` is `"0.2.0"`.
+///
+/// Only the version strings are changed: the rest of the file is left
+/// untouched, including quoting style, inline tables, and comments.
+/// The file is only written if at least one block was updated.
+///
+/// This is used by [`assert_markdown_deps_updated`] when the
+/// `VERSION_SYNC_FIX` environment variable is set.
+///
+/// [`assert_markdown_deps_updated`]: crate::assert_markdown_deps_updated
+///
+/// # Errors
+///
+/// Blocks which cannot be fixed automatically, such as blocks without
+/// a dependency on `pkg_name` or with invalid TOML, make this
+/// function return an [`Error::Failed`] with an error per block. The
+/// other blocks are still updated. Status information has then
+/// already been printed on `stdout`.
+pub fn fix_markdown_deps(path: &str, pkg_name: &str, pkg_version: &str) -> Result<()> {
+    fix_markdown_deps_with_reporter(path, pkg_name, pkg_version, &mut StdoutReporter)
+}
+
+/// Update outdated dependencies in Markdown code blocks and send
+/// status information to `reporter`.
+///
+/// This works like [`fix_markdown_deps`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// Blocks which cannot be fixed automatically make this function
+/// return an [`Error::Failed`] with an error per block.
+pub fn fix_markdown_deps_with_reporter(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    reporter.check_started(CheckKind::MarkdownDeps, path, None);
+    let result = fix_blocks(path, pkg_name, pkg_version, reporter);
+    reporter.check_finished(CheckKind::MarkdownDeps, path, &result);
+    result
+}
+
+/// Fix all TOML and shell code blocks in `path`.
+fn fix_blocks(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    // We read the file directly since read_file would normalize the
+    // line endings, which we want to preserve.
+    let mut text = fs::read_to_string(path).map_err(|source| Error::Io {
+        path: String::from(path),
+        source,
+    })?;
+    let version = parse_version(pkg_version)?;
+
+    let mut errors = Vec::new();
+    let mut edits = Vec::new();
+    for block in find_code_blocks(&text) {
+        let block_source = source_lines(&text, block.first_line, block.last_line());
+        let requests = match block_requests(path, &text, &block, pkg_name) {
+            Ok(requests) if requests.is_empty() => continue,
+            Ok(requests) => requests,
             Err(err) => {
-                failed = true;
-                println!("{} (line {}) ... {} in", path, block.first_line, err);
-                println!("{}\n", indent(&block.content));
+                reporter.location_mismatch(path, block.first_line, &err, block_source);
+                errors.push(err);
+                continue;
+            }
+        };
+
+        let mut block_ok = true;
+        for request in requests {
+            let err = match check_request(path, &text, &block, &request, &version) {
+                Ok(()) => continue,
+                Err(err) => err,
+            };
+            block_ok = false;
+            let edit = request
+                .span
+                .clone()
+                .filter(|_| request.parse().is_ok())
+                .and_then(|span| {
+                    let start = source_offset(&text, &block, span.start)?;
+                    let updated = update_request(&request.text, &version)?;
+                    Some((start..start + span.len(), updated))
+                });
+            match edit {
+                Some((range, updated)) => {
+                    reporter.location_fixed(path, block.first_line, &err, &updated);
+                    edits.push((range, updated));
+                }
+                None => {
+                    reporter.location_mismatch(path, block.first_line, &err, block_source);
+                    errors.push(err);
+                }
             }
-            Ok(()) => println!("{} (line {}) ... ok", path, block.first_line),
+        }
+        if block_ok {
+            reporter.location_ok(path, block.first_line);
         }
     }
 
-    if failed {
-        return Err(format!("dependency errors in {path}"));
+    if !edits.is_empty() {
+        // Apply the edits from the back so the earlier ranges stay valid.
+        for (range, updated) in edits.into_iter().rev() {
+            text.replace_range(range, &updated);
+        }
+        fs::write(path, text).map_err(|source| Error::Write {
+            path: String::from(path),
+            source,
+        })?;
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
     }
     Ok(())
 }
@@ -155,24 +761,26 @@ pub fn check_markdown_deps(path: &str, pkg_name: &str, pkg_version: &str) -> Res
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Write;
 
     #[test]
     fn empty_markdown_file() {
-        assert_eq!(find_toml_blocks(""), vec![]);
+        assert_eq!(find_code_blocks(""), vec![]);
     }
 
     #[test]
     fn indented_code_block() {
-        assert_eq!(find_toml_blocks("    code block\n"), vec![]);
+        assert_eq!(find_code_blocks("    code block\n"), vec![]);
     }
 
     #[test]
     fn empty_toml_block() {
         assert_eq!(
-            find_toml_blocks("```toml\n```"),
+            find_code_blocks("```toml\n```"),
             vec![CodeBlock {
                 content: String::new(),
-                first_line: 2
+                first_line: 2,
+                kind: BlockKind::Toml
             }]
         );
     }
@@ -180,10 +788,11 @@ mod tests {
     #[test]
     fn no_close_fence() {
         assert_eq!(
-            find_toml_blocks("```toml\n"),
+            find_code_blocks("```toml\n"),
             vec![CodeBlock {
                 content: String::new(),
-                first_line: 2
+                first_line: 2,
+                kind: BlockKind::Toml
             }]
         );
     }
@@ -196,10 +805,11 @@ mod tests {
                     ```\n\
                     Trailing text";
         assert_eq!(
-            find_toml_blocks(text),
+            find_code_blocks(text),
             vec![CodeBlock {
                 content: String::from("foo\n"),
-                first_line: 3
+                first_line: 3,
+                kind: BlockKind::Toml
             }]
         );
     }
@@ -216,44 +826,192 @@ mod tests {
                     > ```\n\
                     ";
         assert_eq!(
-            find_toml_blocks(text),
+            find_code_blocks(text),
             vec![CodeBlock {
                 content: String::from("foo\n\n  bar\n\n"),
-                first_line: 4
+                first_line: 4,
+                kind: BlockKind::Toml
             }]
         );
     }
 
     #[test]
-    fn is_toml_block_simple() {
-        assert!(!is_toml_block("rust"));
+    fn block_kind_simple() {
+        assert_eq!(block_kind("rust"), None);
+    }
+
+    #[test]
+    fn block_kind_comma() {
+        assert_eq!(block_kind("foo,toml"), Some(BlockKind::Toml));
     }
 
     #[test]
-    fn is_toml_block_comma() {
-        assert!(is_toml_block("foo,toml"));
+    fn block_kind_no_sync() {
+        assert_eq!(block_kind("toml,no_sync"), None);
+        assert_eq!(block_kind("toml, no_sync"), None);
+        assert_eq!(block_kind("sh,no_sync"), None);
     }
 
     #[test]
-    fn is_toml_block_no_sync() {
-        assert!(!is_toml_block("toml,no_sync"));
-        assert!(!is_toml_block("toml, no_sync"));
+    fn block_kind_shell() {
+        assert_eq!(block_kind("sh"), Some(BlockKind::Shell));
+        assert_eq!(block_kind("bash"), Some(BlockKind::Shell));
+        assert_eq!(block_kind("shell"), Some(BlockKind::Shell));
+        assert_eq!(block_kind("console"), Some(BlockKind::Console));
+    }
+
+    /// Extract the requirement texts from a shell block.
+    fn shell_requests(block: &str, kind: BlockKind) -> Vec<String> {
+        extract_shell_requests("foobar", block, kind)
+            .into_iter()
+            .map(|request| {
+                assert_eq!(&block[request.span.unwrap()], request.text);
+                request.text
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cargo_add() {
+        assert_eq!(
+            shell_requests("cargo add foobar@1.5\n", BlockKind::Shell),
+            vec!["1.5"]
+        );
+    }
+
+    #[test]
+    fn cargo_add_without_request() {
+        assert_eq!(
+            shell_requests("cargo add foobar\n", BlockKind::Shell),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn cargo_add_flags() {
+        let block = "cargo add --dev -F foobar foobar@1.5 --rename foobar baz@2.0\n";
+        assert_eq!(shell_requests(block, BlockKind::Shell), vec!["1.5"]);
+    }
+
+    #[test]
+    fn cargo_add_normalized_name() {
+        assert_eq!(
+            extract_shell_requests("foo-bar", "cargo add foo_bar@1.5", BlockKind::Shell).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn cargo_install_version() {
+        let block = "cargo install foobar --version 1.5\n\
+                     cargo install --version=\"=1.6\" foobar\n\
+                     cargo install baz --version 2.0\n\
+                     cargo install foobar@1.7 --locked\n";
+        assert_eq!(
+            shell_requests(block, BlockKind::Shell),
+            vec!["1.5", "=1.6", "1.7"]
+        );
+    }
+
+    #[test]
+    fn cargo_multiple_commands() {
+        let block = "$ cargo new hello && cd hello; cargo add foobar@1.5; cargo add foobar@1.6\n\
+                     cargo +nightly add foobar@1.7 # A comment with foobar@2.0\n";
+        assert_eq!(
+            shell_requests(block, BlockKind::Shell),
+            vec!["1.5", "1.6", "1.7"]
+        );
+    }
+
+    #[test]
+    fn console_prompt() {
+        let block = "$ cargo add foobar@1.5\n\
+                     cargo add foobar@1.6\n\
+                     \x20 $ cargo add foobar@1.7\n";
+        assert_eq!(
+            shell_requests(block, BlockKind::Console),
+            vec!["1.5", "1.7"]
+        );
+    }
+
+    #[test]
+    fn unrelated_shell_block() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(
+            b"```sh\n\
+              cargo build --release\n\
+              ```\n",
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+        assert_eq!(
+            check_markdown_deps(path, "foobar", "0.2.0").map_err(|err| err.to_string()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn fix_shell_blocks() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(
+            b"```sh\n\
+              $ cargo add foobar@0.1 --features baz\n\
+              $ cargo install foobar --version '=0.1.2'\n\
+              ```\n",
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let errors = match check_markdown_deps(path, "foobar", "0.2.0") {
+            Err(Error::Failed { errors, .. }) => errors,
+            result => panic!("unexpected result: {result:?}"),
+        };
+        match &errors[..] {
+            [Error::Mismatch {
+                line: 2,
+                column: 20,
+                ..
+            }, Error::Mismatch {
+                line: 3,
+                column: 35,
+                ..
+            }] => {}
+            errors => panic!("unexpected errors: {errors:?}"),
+        }
+
+        assert_eq!(
+            fix_markdown_deps(path, "foobar", "0.2.0").map_err(|err| err.to_string()),
+            Ok(())
+        );
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "```sh\n\
+             $ cargo add foobar@0.2 --features baz\n\
+             $ cargo install foobar --version '=0.2.0'\n\
+             ```\n"
+        );
     }
 
     #[test]
     fn simple() {
         let block = "[dependencies]\n\
                      foobar = '1.5'";
-        let request = extract_version_request("foobar", block);
-        assert_eq!(request.unwrap(), VersionReq::parse("1.5").unwrap());
+        let request = extract_version_requests("foobar", block);
+        assert_eq!(
+            request.unwrap()[0].parse(),
+            Ok(VersionReq::parse("1.5").unwrap())
+        );
     }
 
     #[test]
     fn table() {
         let block = "[dependencies]\n\
                      foobar = { version = '1.5', default-features = false }";
-        let request = extract_version_request("foobar", block);
-        assert_eq!(request.unwrap(), VersionReq::parse("1.5").unwrap());
+        let request = extract_version_requests("foobar", block);
+        assert_eq!(
+            request.unwrap()[0].parse(),
+            Ok(VersionReq::parse("1.5").unwrap())
+        );
     }
 
     #[test]
@@ -262,27 +1020,116 @@ mod tests {
         // and are thus always accepted.
         let block = "[dependencies]\n\
                      foobar = { git = 'https://example.net/foobar.git' }";
-        let request = extract_version_request("foobar", block);
-        assert_eq!(request.unwrap(), VersionReq::parse("*").unwrap());
+        let request = extract_version_requests("foobar", block);
+        assert_eq!(
+            request.unwrap()[0].parse(),
+            Ok(VersionReq::parse("*").unwrap())
+        );
     }
 
     #[test]
     fn dev_dependencies() {
         let block = "[dev-dependencies]\n\
                      foobar = '1.5'";
-        let request = extract_version_request("foobar", block);
-        assert_eq!(request.unwrap(), VersionReq::parse("1.5").unwrap());
+        let request = extract_version_requests("foobar", block);
+        assert_eq!(
+            request.unwrap()[0].parse(),
+            Ok(VersionReq::parse("1.5").unwrap())
+        );
+    }
+
+    #[test]
+    fn build_dependencies() {
+        let block = "[build-dependencies]\n\
+                     foobar = '1.5'";
+        let request = extract_version_requests("foobar", block);
+        assert_eq!(
+            request.unwrap()[0].parse(),
+            Ok(VersionReq::parse("1.5").unwrap())
+        );
+    }
+
+    #[test]
+    fn target_dependencies() {
+        let block = "[target.'cfg(unix)'.dependencies]\n\
+                     foobar = '1.5'";
+        let request = extract_version_requests("foobar", block);
+        assert_eq!(
+            request.unwrap()[0].parse(),
+            Ok(VersionReq::parse("1.5").unwrap())
+        );
+    }
+
+    #[test]
+    fn workspace_dependencies() {
+        let block = "[workspace.dependencies]\n\
+                     foobar = { version = '1.5' }";
+        let request = extract_version_requests("foobar", block);
+        assert_eq!(
+            request.unwrap()[0].parse(),
+            Ok(VersionReq::parse("1.5").unwrap())
+        );
+    }
+
+    #[test]
+    fn multiple_tables() {
+        let block = "[dev-dependencies]\n\
+                     foobar = '1.5'\n\
+                     [dependencies]\n\
+                     foobar = '1.6'";
+        let texts = extract_version_requests("foobar", block)
+            .unwrap()
+            .into_iter()
+            .map(|request| request.text)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["1.5", "1.6"]);
+    }
+
+    #[test]
+    fn renamed_dependency() {
+        let block = "[dependencies]\n\
+                     foobar1 = { package = 'foobar', version = '1.5' }\n\
+                     foobar = { package = 'other', version = '2.0' }";
+        let texts = extract_version_requests("foobar", block)
+            .unwrap()
+            .into_iter()
+            .map(|request| request.text)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["1.5"]);
+    }
+
+    #[test]
+    fn normalized_name() {
+        let block = "[dependencies]\n\
+                     foo_bar = '1.5'\n\
+                     baz = { package = 'foo-bar', version = '1.6' }";
+        let texts = extract_version_requests("foo-bar", block)
+            .unwrap()
+            .into_iter()
+            .map(|request| request.text)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["1.5", "1.6"]);
+    }
+
+    #[test]
+    fn same_crate_name_normalization() {
+        assert!(same_crate_name("foo-bar", "foo_bar"));
+        assert!(same_crate_name("foo_bar", "foo_bar"));
+        assert!(!same_crate_name("foo-bar", "foobar"));
+        assert!(!same_crate_name("foo-bar", "foo-baz"));
     }
 
     #[test]
     fn bad_version() {
         let block = "[dependencies]\n\
                      foobar = '1.5.bad'";
-        let request = extract_version_request("foobar", block);
+        let request = extract_version_requests("foobar", block);
         assert_eq!(
-            request.unwrap_err(),
-            "could not parse dependency: \
-             unexpected character 'b' while parsing patch version number"
+            request.unwrap()[0].parse(),
+            Err(String::from(
+                "could not parse dependency: \
+                 unexpected character 'b' while parsing patch version number"
+            ))
         );
     }
 
@@ -290,21 +1137,21 @@ mod tests {
     fn missing_dependency() {
         let block = "[dependencies]\n\
                      baz = '1.5.8'";
-        let request = extract_version_request("foobar", block);
-        assert_eq!(request.unwrap_err(), "no dependency on foobar");
+        let request = extract_version_requests("foobar", block);
+        assert_eq!(request.unwrap(), vec![]);
     }
 
     #[test]
     fn empty() {
-        let request = extract_version_request("foobar", "");
-        assert_eq!(request.unwrap_err(), "no dependency on foobar");
+        let request = extract_version_requests("foobar", "");
+        assert_eq!(request.unwrap(), vec![]);
     }
 
     #[test]
     fn bad_toml() {
         let block = "[dependencies]\n\
                      foobar = 1.5.8";
-        let request = extract_version_request("foobar", block);
+        let request = extract_version_requests("foobar", block);
         assert!(request.is_err());
     }
 
@@ -317,7 +1164,8 @@ mod tests {
         };
         let errmsg = format!("could not read no-such-file.md: {no_such_file}");
         assert_eq!(
-            check_markdown_deps("no-such-file.md", "foobar", "1.2.3"),
+            check_markdown_deps("no-such-file.md", "foobar", "1.2.3")
+                .map_err(|err| err.to_string()),
             Err(errmsg)
         );
     }
@@ -326,10 +1174,207 @@ mod tests {
     fn bad_pkg_version() {
         // This uses the README.md file from this crate.
         assert_eq!(
-            check_markdown_deps("README.md", "foobar", "1.2"),
+            check_markdown_deps("README.md", "foobar", "1.2").map_err(|err| err.to_string()),
             Err(String::from(
                 "bad package version \"1.2\": unexpected end of input while parsing minor version number"
             ))
         );
     }
+
+    #[test]
+    fn version_span() {
+        let block = "[dependencies]\n\
+                     foobar = { version = '1.5', default-features = false }";
+        let request = extract_version_requests("foobar", block).unwrap().remove(0);
+        assert_eq!(&block[request.span.unwrap()], "1.5");
+    }
+
+    #[test]
+    fn source_offset_blockquote() {
+        let text = "> ```toml\n\
+                    > foobar = '1.5'\n\
+                    > ```\n";
+        let block = &find_code_blocks(text)[0];
+        let offset = source_offset(text, block, "foobar = '".len()).unwrap();
+        assert_eq!(&text[offset..offset + 3], "1.5");
+    }
+
+    #[test]
+    fn fix_outdated_blocks() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(
+            b"Intro text.\r\n\
+              \r\n\
+              ```toml\r\n\
+              [dependencies]\r\n\
+              foobar = \"0.1\" # A comment.\r\n\
+              ```\r\n\
+              \r\n\
+              > ```toml\r\n\
+              > [dev-dependencies]\r\n\
+              > foobar = { version = '=0.1.2', features = [\"baz\"] }\r\n\
+              > ```\r\n\
+              \r\n\
+              ```toml\r\n\
+              [dependencies]\r\n\
+              foobar = \"0.2\"\r\n\
+              ```\r\n",
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert_eq!(
+            fix_markdown_deps(path, "foobar", "0.2.0").map_err(|err| err.to_string()),
+            Ok(())
+        );
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "Intro text.\r\n\
+             \r\n\
+             ```toml\r\n\
+             [dependencies]\r\n\
+             foobar = \"0.2\" # A comment.\r\n\
+             ```\r\n\
+             \r\n\
+             > ```toml\r\n\
+             > [dev-dependencies]\r\n\
+             > foobar = { version = '=0.2.0', features = [\"baz\"] }\r\n\
+             > ```\r\n\
+             \r\n\
+             ```toml\r\n\
+             [dependencies]\r\n\
+             foobar = \"0.2\"\r\n\
+             ```\r\n"
+        );
+        assert_eq!(
+            check_markdown_deps(path, "foobar", "0.2.0").map_err(|err| err.to_string()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn fix_unsatisfiable_range() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let text = "```toml\n\
+                    [dependencies]\n\
+                    foobar = \">= 1.2.0, < 2.0\"\n\
+                    ```\n";
+        file.write_all(text.as_bytes()).unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert_eq!(
+            fix_markdown_deps(path, "foobar", "2.1.0").map_err(|err| err.to_string()),
+            Err(format!("{path} ... found 1 error"))
+        );
+        assert_eq!(fs::read_to_string(path).unwrap(), text);
+    }
+
+    #[test]
+    fn fix_missing_dependency() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(
+            b"```toml\n\
+              [dependencies]\n\
+              baz = \"0.1\"\n\
+              ```\n\
+              ```toml\n\
+              [dependencies]\n\
+              foobar = \"0.1\"\n\
+              ```\n",
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert_eq!(
+            fix_markdown_deps(path, "foobar", "0.2.0").map_err(|err| err.to_string()),
+            Err(format!("{path} ... found 1 error"))
+        );
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "```toml\n\
+             [dependencies]\n\
+             baz = \"0.1\"\n\
+             ```\n\
+             ```toml\n\
+             [dependencies]\n\
+             foobar = \"0.2\"\n\
+             ```\n"
+        );
+    }
+
+    #[test]
+    fn mismatch_location() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(
+            b"```toml\n\
+              [dependencies]\n\
+              foobar = { version = \"0.1\" }\n\
+              ```\n\
+              ```toml\n\
+              foobar = \"0.2\"\n\
+              ```\n",
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let errors = match check_markdown_deps(path, "foobar", "0.2.0") {
+            Err(Error::Failed { errors, .. }) => errors,
+            result => panic!("unexpected result: {result:?}"),
+        };
+        assert_eq!(errors.len(), 2);
+        match &errors[0] {
+            Error::Mismatch {
+                line,
+                column,
+                found,
+                expected,
+                message,
+                ..
+            } => {
+                assert_eq!((*line, *column), (3, 23));
+                assert_eq!(found, "0.1");
+                // The suggestion keeps the precision, like the fix.
+                assert_eq!(expected, "0.2");
+                assert_eq!(message, "expected minor version 2, found 1");
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+        match &errors[1] {
+            Error::MissingDependency { line, pkg_name, .. } => {
+                assert_eq!(*line, 6);
+                assert_eq!(pkg_name, "foobar");
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn every_dependency_checked() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(
+            b"```toml\n\
+              [dependencies]\n\
+              foobar = \"0.2\"\n\
+              [build-dependencies]\n\
+              foobar = \"0.1\"\n\
+              [target.'cfg(unix)'.dev-dependencies]\n\
+              foobar = \"0.1\"\n\
+              ```\n",
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let errors = match check_markdown_deps(path, "foobar", "0.2.0") {
+            Err(Error::Failed { errors, .. }) => errors,
+            result => panic!("unexpected result: {result:?}"),
+        };
+        let lines = errors
+            .iter()
+            .map(|err| match err {
+                Error::Mismatch { line, .. } => *line,
+                err => panic!("unexpected error: {err:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![5, 7]);
+    }
 }
@@ -0,0 +1,903 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use crate::error::{Error, Result};
+use crate::reporter::{CheckKind, Reporter, StdoutReporter};
+
+/// The outcome of checking a location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Status {
+    /// The location passed the check.
+    Ok,
+    /// The location failed the check.
+    Mismatch,
+    /// The location failed the check, but was fixed.
+    Fixed,
+    /// The check could not be run, e.g., because a file is missing or
+    /// a template is not found at all.
+    Error,
+}
+
+impl Status {
+    /// The name used for the status in the JSON output, such as
+    /// `"mismatch"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Mismatch => "mismatch",
+            Status::Fixed => "fixed",
+            Status::Error => "error",
+        }
+    }
+}
+
+/// The result of checking a single location in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    /// The kind of check.
+    pub kind: CheckKind,
+    /// The file checked.
+    pub path: String,
+    /// Line number of the location, starting with 1.
+    pub line: Option<usize>,
+    /// Column of the version number, starting with 1.
+    pub column: Option<usize>,
+    /// The version number or URL found in the file.
+    pub found: Option<String>,
+    /// The version number or URL we expected to find, or what a fixed
+    /// location was updated to.
+    pub expected: Option<String>,
+    /// The outcome of the check.
+    pub status: Status,
+    /// Description of the problem, if any.
+    pub message: Option<String>,
+}
+
+impl CheckResult {
+    /// Create a result for a location which failed the check with
+    /// `error`. The line and column in `error` take precedence over
+    /// `line`.
+    pub(crate) fn from_error(
+        kind: CheckKind,
+        path: &str,
+        line: Option<usize>,
+        error: &Error,
+        status: Status,
+    ) -> CheckResult {
+        let mut result = CheckResult {
+            kind,
+            path: String::from(path),
+            line,
+            column: None,
+            found: None,
+            expected: None,
+            status,
+            message: Some(error.to_string()),
+        };
+        match error {
+            Error::Mismatch {
+                line,
+                column,
+                found,
+                expected,
+                ..
+            } => {
+                result.line = Some(*line);
+                result.column = Some(*column);
+                result.found = Some(found.clone());
+                result.expected = Some(expected.clone());
+            }
+            Error::MissingDependency { line, .. } | Error::Toml { line, .. } => {
+                result.line = Some(*line);
+            }
+            _ => {}
+        }
+        result
+    }
+
+    /// Render the result as a JSON object on a single line.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{");
+        json_field(&mut json, "kind", Some(&json_string(self.kind.name())));
+        json_field(&mut json, "path", Some(&json_string(&self.path)));
+        json_field(
+            &mut json,
+            "line",
+            self.line.map(|n| n.to_string()).as_deref(),
+        );
+        json_field(
+            &mut json,
+            "column",
+            self.column.map(|n| n.to_string()).as_deref(),
+        );
+        json_field(
+            &mut json,
+            "found",
+            self.found.as_deref().map(json_string).as_deref(),
+        );
+        let expected = self.expected.as_deref().map(json_string);
+        json_field(&mut json, "expected", expected.as_deref());
+        json_field(&mut json, "status", Some(&json_string(self.status.name())));
+        let message = self.message.as_deref().map(json_string);
+        json_field(&mut json, "message", message.as_deref());
+        json.push('}');
+        json
+    }
+}
+
+/// Append `"key": value` to the JSON object in `json`, using `null`
+/// for missing values.
+fn json_field(json: &mut String, key: &str, value: Option<&str>) {
+    if !json.ends_with('{') {
+        json.push_str(", ");
+    }
+    let _ = write!(json, "\"{key}\": {}", value.unwrap_or("null"));
+}
+
+/// Quote and escape `text` as a JSON string.
+fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Short description of a kind of check, used for the SARIF rules.
+fn description(kind: CheckKind) -> &'static str {
+    match kind {
+        CheckKind::MarkdownDeps => "Dependencies in Markdown code blocks use the package version",
+        CheckKind::RustdocDeps => "Dependencies in Rust doc comments use the package version",
+        CheckKind::RustdocLinks => "Links in Rust doc comments use the package version",
+        CheckKind::HtmlRootUrl => "Documentation URLs use the package version",
+        CheckKind::DeprecatedSince => "Deprecated items give a released version",
+        CheckKind::ContainsRegex | CheckKind::ContainsSubstring => {
+            "The file mentions the package version"
+        }
+        CheckKind::OnlyContainsRegex => "The file only mentions the package version",
+        CheckKind::Changelog => "The changelog has an entry for the package version",
+        CheckKind::WorkspaceDeps => "Dependencies between workspace members use their versions",
+    }
+}
+
+/// Collects the results of the checks for machine-readable output.
+///
+/// Pass a `Report` to the `_with_reporter` variants of the check
+/// functions and render the results afterwards:
+///
+/// ```rust
+/// use version_sync::{check_contains_substring_with_reporter, Report};
+///
+/// let mut report = Report::new();
+/// let result = check_contains_substring_with_reporter(
+///     "README.md",
+///     "version-sync",
+///     "version-sync",
+///     "0.9.5",
+///     &mut report,
+/// );
+/// assert!(result.is_ok());
+/// assert_eq!(report.results().len(), 1);
+/// println!("{}", report.to_sarif());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Report {
+    /// Print the progress on `stdout` as well.
+    progress: bool,
+    /// The kinds of the checks currently running.
+    running: Vec<CheckKind>,
+    results: Vec<CheckResult>,
+}
+
+impl Report {
+    /// Create an empty report.
+    pub fn new() -> Report {
+        Report::default()
+    }
+
+    /// Create an empty report for rendering in `format`.
+    ///
+    /// For [`Format::Text`] and [`Format::Github`], the report prints
+    /// the progress on `stdout` like [`StdoutReporter`] while
+    /// collecting the results.
+    pub fn for_format(format: Format) -> Report {
+        Report {
+            progress: matches!(format, Format::Text | Format::Github),
+            ..Report::default()
+        }
+    }
+
+    /// The results collected so far, in the order they were reported.
+    pub fn results(&self) -> &[CheckResult] {
+        &self.results
+    }
+
+    /// The kind of the innermost running check.
+    fn kind(&self) -> CheckKind {
+        self.running
+            .last()
+            .copied()
+            .unwrap_or(CheckKind::ContainsSubstring)
+    }
+
+    /// Render the results as JSON Lines, with one JSON object per
+    /// result.
+    pub fn to_json_lines(&self) -> String {
+        let mut output = String::new();
+        for result in &self.results {
+            output.push_str(&result.to_json());
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Render the results as a SARIF 2.1.0 log.
+    ///
+    /// Only locations which failed the check or were fixed are
+    /// included. Each kind of check is a rule with the same id as the
+    /// kind in the JSON output, such as `markdown-deps`.
+    pub fn to_sarif(&self) -> String {
+        let findings = self
+            .results
+            .iter()
+            .filter(|result| result.status != Status::Ok)
+            .collect::<Vec<_>>();
+        let mut kinds = Vec::new();
+        for result in &findings {
+            if !kinds.contains(&result.kind) {
+                kinds.push(result.kind);
+            }
+        }
+
+        let rules = kinds
+            .iter()
+            .map(|&kind| {
+                format!(
+                    "{{\"id\": {}, \"shortDescription\": {{\"text\": {}}}}}",
+                    json_string(kind.name()),
+                    json_string(description(kind))
+                )
+            })
+            .collect::<Vec<_>>();
+        let results = findings
+            .iter()
+            .map(|result| {
+                let level = match result.status {
+                    Status::Fixed => "note",
+                    _ => "error",
+                };
+                let mut region = Vec::new();
+                if let Some(line) = result.line {
+                    region.push(format!("\"startLine\": {line}"));
+                }
+                if let Some(column) = result.column {
+                    region.push(format!("\"startColumn\": {column}"));
+                }
+                let region = match region.is_empty() {
+                    true => String::new(),
+                    false => format!(", \"region\": {{{}}}", region.join(", ")),
+                };
+                format!(
+                    "{{\"ruleId\": {}, \"ruleIndex\": {}, \"level\": \"{level}\", \
+                     \"message\": {{\"text\": {}}}, \"locations\": [{{\"physicalLocation\": \
+                     {{\"artifactLocation\": {{\"uri\": {}}}{region}}}}}]}}",
+                    json_string(result.kind.name()),
+                    kinds.iter().position(|&kind| kind == result.kind).unwrap(),
+                    json_string(result.message.as_deref().unwrap_or(result.status.name())),
+                    json_string(&result.path.replace('\\', "/")),
+                )
+            })
+            .collect::<Vec<_>>();
+        sarif_log(&rules, &results)
+    }
+
+    /// Render the failed locations as GitHub Actions workflow
+    /// commands.
+    ///
+    /// Each failure becomes a line such as
+    ///
+    /// ```text
+    /// ::error file=README.md,line=12,col=5::expected minor version 3, found 2
+    /// ```
+    ///
+    /// which GitHub shows as an annotation on the line in the diff
+    /// view. Fixed locations become `::notice` commands.
+    pub fn to_github_annotations(&self) -> String {
+        let mut output = String::new();
+        for result in &self.results {
+            let command = match result.status {
+                Status::Ok => continue,
+                Status::Fixed => "notice",
+                _ => "error",
+            };
+            let mut properties = vec![format!("file={}", github_escape(&result.path, true))];
+            if let Some(line) = result.line {
+                properties.push(format!("line={line}"));
+            }
+            if let Some(column) = result.column {
+                properties.push(format!("col={column}"));
+            }
+            let message = result.message.as_deref().unwrap_or(result.status.name());
+            let _ = writeln!(
+                output,
+                "::{command} {}::{}",
+                properties.join(","),
+                github_escape(message, false)
+            );
+        }
+        output
+    }
+
+    /// Render the failed locations as a GitLab Code Quality report.
+    ///
+    /// Save the report as an artifact with the `codequality` report
+    /// type to see the failures in the merge request diff. Each issue
+    /// has a fingerprint computed from the kind of check, the file,
+    /// the line, and the message.
+    pub fn to_gitlab_code_quality(&self) -> String {
+        let issues = self
+            .results
+            .iter()
+            .filter(|result| result.status != Status::Ok)
+            .map(|result| {
+                let message = result.message.as_deref().unwrap_or(result.status.name());
+                let line = result.line.unwrap_or(1);
+                let fingerprint = fnv1a(&format!(
+                    "{}\0{}\0{line}\0{message}",
+                    result.kind.name(),
+                    result.path
+                ));
+                let severity = match result.status {
+                    Status::Fixed => "info",
+                    _ => "major",
+                };
+                format!(
+                    "{{\"description\": {}, \"check_name\": {}, \
+                     \"fingerprint\": \"{fingerprint:016x}\", \
+                     \"severity\": \"{severity}\", \"location\": {{\"path\": {}, \
+                     \"lines\": {{\"begin\": {line}}}}}}}",
+                    json_string(message),
+                    json_string(result.kind.name()),
+                    json_string(&result.path.replace('\\', "/")),
+                )
+            })
+            .collect::<Vec<_>>();
+        format!("[{}]\n", list(&issues, 2))
+    }
+
+    /// Render the results in `format`.
+    ///
+    /// Nothing is rendered for [`Format::Text`] since the text is
+    /// printed while the checks run.
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Text => String::new(),
+            Format::JsonLines => self.to_json_lines(),
+            Format::Sarif => self.to_sarif(),
+            Format::Github => self.to_github_annotations(),
+            Format::Gitlab => self.to_gitlab_code_quality(),
+        }
+    }
+}
+
+/// Format a SARIF 2.1.0 log with a single run from the rendered
+/// `rules` and `results`.
+fn sarif_log(rules: &[String], results: &[String]) -> String {
+    format!(
+        "{{\n  \"$schema\": \"https://json.schemastore.org/sarif-2.1.0.json\",\n  \
+         \"version\": \"2.1.0\",\n  \"runs\": [\n    {{\n      \"tool\": {{\n        \
+         \"driver\": {{\n          \"name\": \"version-sync\",\n          \
+         \"version\": \"{}\",\n          \
+         \"informationUri\": \"https://github.com/mgeisler/version-sync\",\n          \
+         \"rules\": [{}]\n        }}\n      }},\n      \"results\": [{}]\n    }}\n  ]\n}}\n",
+        env!("CARGO_PKG_VERSION"),
+        list(rules, 12),
+        list(results, 8),
+    )
+}
+
+/// Escape `text` for a GitHub Actions workflow command. Property
+/// values need more escaping than the message.
+fn github_escape(text: &str, property: bool) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '%' => escaped.push_str("%25"),
+            '\r' => escaped.push_str("%0D"),
+            '\n' => escaped.push_str("%0A"),
+            ':' if property => escaped.push_str("%3A"),
+            ',' if property => escaped.push_str("%2C"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The 64-bit FNV-1a hash of `text`.
+///
+/// Unlike the hashers in the standard library, the hash is stable
+/// across Rust versions, so the fingerprints stay the same.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Format `items` as the lines of a JSON array indented by `indent`
+/// spaces.
+fn list(items: &[String], indent: usize) -> String {
+    if items.is_empty() {
+        return String::new();
+    }
+    let separator = format!(",\n{:indent$}", "");
+    format!(
+        "\n{:indent$}{}\n{:outer$}",
+        "",
+        items.join(&separator),
+        "",
+        outer = indent - 2
+    )
+}
+
+impl Reporter for Report {
+    fn check_started(&mut self, kind: CheckKind, path: &str, pattern: Option<&str>) {
+        if self.progress {
+            StdoutReporter.check_started(kind, path, pattern);
+        }
+        self.running.push(kind);
+    }
+
+    fn location_ok(&mut self, path: &str, line: usize) {
+        if self.progress {
+            StdoutReporter.location_ok(path, line);
+        }
+        self.results.push(CheckResult {
+            kind: self.kind(),
+            path: String::from(path),
+            line: Some(line),
+            column: None,
+            found: None,
+            expected: None,
+            status: Status::Ok,
+            message: None,
+        });
+    }
+
+    fn location_mismatch(&mut self, path: &str, line: usize, error: &Error, source: &str) {
+        if self.progress {
+            StdoutReporter.location_mismatch(path, line, error, source);
+        }
+        let result =
+            CheckResult::from_error(self.kind(), path, Some(line), error, Status::Mismatch);
+        self.results.push(result);
+    }
+
+    fn location_fixed(&mut self, path: &str, line: usize, error: &Error, updated: &str) {
+        if self.progress {
+            StdoutReporter.location_fixed(path, line, error, updated);
+        }
+        let mut result =
+            CheckResult::from_error(self.kind(), path, Some(line), error, Status::Fixed);
+        result.expected = Some(String::from(updated));
+        self.results.push(result);
+    }
+
+    fn check_finished(&mut self, kind: CheckKind, path: &str, result: &Result<()>) {
+        // The locations in a failed check have already been reported,
+        // but other errors stop the check before it gets that far.
+        match result {
+            Ok(()) | Err(Error::Failed { .. }) => {}
+            Err(err) => {
+                let result = CheckResult::from_error(kind, path, None, err, Status::Error);
+                self.results.push(result);
+            }
+        }
+        self.running.pop();
+        if self.progress {
+            StdoutReporter.check_finished(kind, path, result);
+        }
+    }
+}
+
+/// The output format for the check results.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Format {
+    /// Human-readable text printed while the checks run, see
+    /// [`StdoutReporter`].
+    #[default]
+    Text,
+    /// JSON Lines with a [`CheckResult`] per line.
+    JsonLines,
+    /// A SARIF 2.1.0 log with the failed locations.
+    Sarif,
+    /// The text, followed by GitHub Actions workflow commands which
+    /// annotate the failed locations.
+    Github,
+    /// A GitLab Code Quality report with the failed locations.
+    Gitlab,
+}
+
+impl Format {
+    /// Read the format from the `VERSION_SYNC_FORMAT` environment
+    /// variable.
+    ///
+    /// If the variable is unset or empty, the format is `github` when
+    /// running in GitHub Actions, where `GITHUB_ACTIONS` is `true`, and
+    /// `text` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Format`] is returned for an unknown format.
+    pub fn from_env() -> Result<Format> {
+        match env::var("VERSION_SYNC_FORMAT") {
+            Ok(value) if !value.is_empty() => value.parse(),
+            _ if env::var_os("GITHUB_ACTIONS").is_some_and(|value| value == "true") => {
+                Ok(Format::Github)
+            }
+            _ => Ok(Format::Text),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    /// Parse `text`, `json`, `sarif`, `github`, or `gitlab`. The name
+    /// `jsonl` is accepted for JSON Lines too.
+    fn from_str(value: &str) -> Result<Format> {
+        match value {
+            "text" => Ok(Format::Text),
+            "json" | "jsonl" => Ok(Format::JsonLines),
+            "sarif" => Ok(Format::Sarif),
+            "github" => Ok(Format::Github),
+            "gitlab" => Ok(Format::Gitlab),
+            _ => Err(Error::Format {
+                format: String::from(value),
+            }),
+        }
+    }
+}
+
+/// Find the items of the JSON array after `"key": ` in `text`, or of
+/// the array at the start of `text` if there is no `key`.
+///
+/// Only arrays formatted by [`list`] are understood, so this returns
+/// `None` for files written by other tools.
+fn list_items(text: &str, key: Option<&str>) -> Option<Vec<String>> {
+    let start = match key {
+        Some(key) => text.find(&format!("\"{key}\": ["))? + key.len() + 4,
+        None => text.find('[')?,
+    };
+    let rest = &text[start + 1..];
+    if rest.starts_with(']') {
+        return Some(Vec::new());
+    }
+    let mut items = Vec::new();
+    for line in rest.lines().skip(1) {
+        let line = line.trim();
+        if line.starts_with(']') {
+            return Some(items);
+        }
+        items.push(String::from(line.strip_suffix(',').unwrap_or(line)));
+    }
+    None
+}
+
+/// Merge the output in `rendered` into the `existing` output, both
+/// in `format`.
+///
+/// Entries which are already in `existing` are not repeated. Returns
+/// `None` if `existing` cannot be merged into.
+fn merge(format: Format, existing: &str, rendered: &str) -> Option<String> {
+    match format {
+        Format::JsonLines => {
+            let mut merged = String::from(existing);
+            for line in rendered.lines() {
+                if !existing.lines().any(|existing| existing == line) {
+                    merged.push_str(line);
+                    merged.push('\n');
+                }
+            }
+            Some(merged)
+        }
+        Format::Sarif => {
+            let mut rules = list_items(existing, Some("rules"))?;
+            let mut results = list_items(existing, Some("results"))?;
+            let new_rules = list_items(rendered, Some("rules"))?;
+            for result in list_items(rendered, Some("results"))? {
+                // Point the result at its rule in the merged rules.
+                let index_start = result.find("\"ruleIndex\": ")? + 13;
+                let index_end = index_start + result[index_start..].find(',')?;
+                let rule = new_rules.get(result[index_start..index_end].parse::<usize>().ok()?)?;
+                let index = match rules.iter().position(|existing| existing == rule) {
+                    Some(index) => index,
+                    None => {
+                        rules.push(rule.clone());
+                        rules.len() - 1
+                    }
+                };
+                let result = format!("{}{index}{}", &result[..index_start], &result[index_end..]);
+                if !results.contains(&result) {
+                    results.push(result);
+                }
+            }
+            Some(sarif_log(&rules, &results))
+        }
+        Format::Gitlab => {
+            let mut issues = list_items(existing, None)?;
+            for issue in list_items(rendered, None)? {
+                if !issues.contains(&issue) {
+                    issues.push(issue);
+                }
+            }
+            Some(format!("[{}]\n", list(&issues, 2)))
+        }
+        _ => None,
+    }
+}
+
+/// Add the output in `rendered` to the file in `path`, keeping the
+/// results which are already in the file.
+fn write_merged(path: &str, format: Format, rendered: &str) -> Result<()> {
+    // The tests in a test binary run in parallel, so the file is
+    // updated by one of them at a time.
+    static LOCK: Mutex<()> = Mutex::new(());
+    let _guard = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+    let merged = fs::read_to_string(path)
+        .ok()
+        .and_then(|existing| merge(format, &existing, rendered))
+        .unwrap_or_else(|| String::from(rendered));
+    fs::write(path, merged).map_err(|source| Error::Write {
+        path: String::from(path),
+        source,
+    })
+}
+
+/// Run a check with a reporter for the format in the
+/// `VERSION_SYNC_FORMAT` environment variable.
+///
+/// This is used by the macros. For the other formats than text, the
+/// results are rendered when the check is done. The test harness
+/// captures what the tests print, so the JSON Lines, SARIF, and GitLab
+/// output is added to the file in the `VERSION_SYNC_OUTPUT`
+/// environment variable if it is set, and printed on `stdout`
+/// otherwise.
+#[doc(hidden)]
+pub fn run_reported(check: impl FnOnce(&mut dyn Reporter) -> Result<()>) -> Result<()> {
+    let format = Format::from_env()?;
+    if format == Format::Text {
+        return check(&mut StdoutReporter);
+    }
+
+    let mut report = Report::for_format(format);
+    let result = check(&mut report);
+    let rendered = report.render(format);
+    // GitHub Actions reads the workflow commands from the output of
+    // the job, so they are always printed.
+    let output = env::var("VERSION_SYNC_OUTPUT")
+        .ok()
+        .filter(|path| !path.is_empty() && format != Format::Github);
+    match output {
+        Some(path) => write_merged(&path, format, &rendered).and(result),
+        None => {
+            print!("{rendered}");
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mismatch() -> Error {
+        Error::Mismatch {
+            path: String::from("README.md"),
+            line: 12,
+            column: 5,
+            found: String::from("0.1"),
+            expected: String::from("0.2.0"),
+            message: String::from("expected minor version 2, found 1"),
+        }
+    }
+
+    fn report() -> Report {
+        let mut report = Report::new();
+        report.check_started(CheckKind::MarkdownDeps, "README.md", None);
+        report.location_ok("README.md", 3);
+        report.location_mismatch("README.md", 10, &mismatch(), "[dependencies]");
+        let err = Error::Failed {
+            path: String::from("README.md"),
+            errors: vec![mismatch()],
+        };
+        report.check_finished(CheckKind::MarkdownDeps, "README.md", &Err(err));
+        report.check_started(CheckKind::ContainsSubstring, "NEWS \"1\".md", Some("x"));
+        let err = Error::NoMatch {
+            path: String::from("NEWS \"1\".md"),
+            pattern: String::from("x"),
+        };
+        report.check_finished(CheckKind::ContainsSubstring, "NEWS \"1\".md", &Err(err));
+        report
+    }
+
+    #[test]
+    fn json_lines() {
+        assert_eq!(
+            report().to_json_lines(),
+            "{\"kind\": \"markdown-deps\", \"path\": \"README.md\", \"line\": 3, \
+             \"column\": null, \"found\": null, \"expected\": null, \"status\": \"ok\", \
+             \"message\": null}\n\
+             {\"kind\": \"markdown-deps\", \"path\": \"README.md\", \"line\": 12, \
+             \"column\": 5, \"found\": \"0.1\", \"expected\": \"0.2.0\", \
+             \"status\": \"mismatch\", \"message\": \"expected minor version 2, found 1\"}\n\
+             {\"kind\": \"contains\", \"path\": \"NEWS \\\"1\\\".md\", \"line\": null, \
+             \"column\": null, \"found\": null, \"expected\": null, \"status\": \"error\", \
+             \"message\": \"could not find \\\"x\\\" in NEWS \\\"1\\\".md\"}\n"
+        );
+    }
+
+    #[test]
+    fn sarif() {
+        let sarif = report().to_sarif();
+        assert!(sarif.contains("\"version\": \"2.1.0\""));
+        assert!(sarif.contains(
+            "{\"ruleId\": \"markdown-deps\", \"ruleIndex\": 0, \"level\": \"error\", \
+             \"message\": {\"text\": \"expected minor version 2, found 1\"}, \
+             \"locations\": [{\"physicalLocation\": {\"artifactLocation\": \
+             {\"uri\": \"README.md\"}, \"region\": {\"startLine\": 12, \"startColumn\": 5}}}]}"
+        ));
+        assert!(sarif.contains("{\"id\": \"contains\", \"shortDescription\""));
+        assert_eq!(sarif.matches("\"ruleId\"").count(), 2);
+    }
+
+    #[test]
+    fn empty_sarif() {
+        let sarif = Report::new().to_sarif();
+        assert!(sarif.contains("\"rules\": []"));
+        assert!(sarif.contains("\"results\": []"));
+    }
+
+    #[test]
+    fn merged_json_lines() {
+        let first = report().to_json_lines();
+        let mut other = Report::new();
+        other.check_started(CheckKind::Changelog, "CHANGELOG.md", None);
+        other.location_ok("CHANGELOG.md", 7);
+        other.check_finished(CheckKind::Changelog, "CHANGELOG.md", &Ok(()));
+        let second = other.to_json_lines();
+
+        let merged = merge(Format::JsonLines, &first, &second).unwrap();
+        assert_eq!(merged, format!("{first}{second}"));
+        assert_eq!(merge(Format::JsonLines, &merged, &second).unwrap(), merged);
+    }
+
+    #[test]
+    fn merged_sarif() {
+        let first = report().to_sarif();
+        let mut other = Report::new();
+        other.check_started(CheckKind::Changelog, "CHANGELOG.md", None);
+        other.location_mismatch("CHANGELOG.md", 7, &mismatch(), "## [0.1.0]");
+        other.check_finished(CheckKind::Changelog, "CHANGELOG.md", &Ok(()));
+        other.check_started(CheckKind::ContainsSubstring, "NEWS.md", Some("y"));
+        let err = Error::NoMatch {
+            path: String::from("NEWS.md"),
+            pattern: String::from("y"),
+        };
+        other.check_finished(CheckKind::ContainsSubstring, "NEWS.md", &Err(err));
+        let second = other.to_sarif();
+
+        let merged = merge(Format::Sarif, &first, &second).unwrap();
+        assert_eq!(list_items(&merged, Some("rules")).unwrap().len(), 3);
+        let results = list_items(&merged, Some("results")).unwrap();
+        let rules = results
+            .iter()
+            .map(|result| {
+                let end = result.find(", \"level\"").unwrap();
+                &result[..end]
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rules,
+            [
+                "{\"ruleId\": \"markdown-deps\", \"ruleIndex\": 0",
+                "{\"ruleId\": \"contains\", \"ruleIndex\": 1",
+                "{\"ruleId\": \"changelog\", \"ruleIndex\": 2",
+                "{\"ruleId\": \"contains\", \"ruleIndex\": 1",
+            ]
+        );
+        assert_eq!(merge(Format::Sarif, &merged, &second).unwrap(), merged);
+        assert_eq!(merge(Format::Sarif, "not sarif", &second), None);
+    }
+
+    #[test]
+    fn merged_gitlab_code_quality() {
+        let first = report().to_gitlab_code_quality();
+        let mut other = Report::new();
+        other.check_started(CheckKind::Changelog, "CHANGELOG.md", None);
+        other.location_mismatch("CHANGELOG.md", 7, &mismatch(), "## [0.1.0]");
+        other.check_finished(CheckKind::Changelog, "CHANGELOG.md", &Ok(()));
+        let second = other.to_gitlab_code_quality();
+
+        let merged = merge(Format::Gitlab, &first, &second).unwrap();
+        let issues = list_items(&merged, None).unwrap();
+        assert_eq!(issues.len(), 3);
+        assert!(issues[2].contains("\"check_name\": \"changelog\""));
+        assert!(merged.starts_with("[\n  {") && merged.ends_with("}\n]\n"));
+        assert_eq!(merge(Format::Gitlab, &merged, &second).unwrap(), merged);
+        assert_eq!(merge(Format::Gitlab, "[]\n", &second).unwrap(), second);
+        assert_eq!(merge(Format::Gitlab, &first, "[]\n").unwrap(), first);
+    }
+
+    #[test]
+    fn write_merged_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.sarif");
+        let path = path.to_str().unwrap();
+        write_merged(path, Format::Sarif, &report().to_sarif()).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), report().to_sarif());
+        write_merged(path, Format::Sarif, &Report::new().to_sarif()).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), report().to_sarif());
+    }
+
+    #[test]
+    fn github_annotations() {
+        assert_eq!(
+            report().to_github_annotations(),
+            "::error file=README.md,line=12,col=5::expected minor version 2, found 1\n\
+             ::error file=NEWS \"1\".md::could not find \"x\" in NEWS \"1\".md\n"
+        );
+        assert_eq!(github_escape("a:b,c%\n", true), "a%3Ab%2Cc%25%0A");
+        assert_eq!(github_escape("a:b,c%\n", false), "a:b,c%25%0A");
+    }
+
+    #[test]
+    fn gitlab_code_quality() {
+        let report = report().to_gitlab_code_quality();
+        assert!(report.starts_with(
+            "[\n  {\"description\": \"expected minor version 2, found 1\", \
+                                    \"check_name\": \"markdown-deps\", \"fingerprint\": \""
+        ));
+        assert!(report.contains(
+            "\"severity\": \"major\", \"location\": {\"path\": \"README.md\", \
+             \"lines\": {\"begin\": 12}}}"
+        ));
+        assert!(report.contains("\"lines\": {\"begin\": 1}}}\n]\n"));
+        assert_eq!(Report::new().to_gitlab_code_quality(), "[]\n");
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn json_escapes() {
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), r#""a\"b\\c\nd\u0001""#);
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!("sarif".parse::<Format>().unwrap(), Format::Sarif);
+        assert_eq!("jsonl".parse::<Format>().unwrap(), Format::JsonLines);
+        assert_eq!(
+            "xml".parse::<Format>().map_err(|err| err.to_string()),
+            Err(String::from(
+                "unknown output format \"xml\", expected text, json, sarif, github, or gitlab"
+            ))
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use crate::diagnostic::{mismatch_diagnostic, use_color};
+use crate::error::{Error, Result};
+use crate::helpers::indent;
+
+/// The kind of check being run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CheckKind {
+    /// Dependencies in Markdown code blocks, see
+    /// [`check_markdown_deps`](crate::check_markdown_deps).
+    MarkdownDeps,
+    /// Dependencies in code blocks in Rust doc comments, see
+    /// [`check_rustdoc_deps`].
+    ///
+    /// [`check_rustdoc_deps`]: crate::check_rustdoc_deps
+    RustdocDeps,
+    /// Links to docs.rs and crates.io in Rust doc comments, see
+    /// [`check_rustdoc_links`].
+    ///
+    /// [`check_rustdoc_links`]: crate::check_rustdoc_links
+    RustdocLinks,
+    /// The `html_root_url` attribute, see
+    /// [`check_html_root_url`](crate::check_html_root_url).
+    HtmlRootUrl,
+    /// The `since` version of `deprecated` attributes, see
+    /// [`check_deprecated_since`].
+    ///
+    /// [`check_deprecated_since`]: crate::check_deprecated_since
+    DeprecatedSince,
+    /// A regular expression, see
+    /// [`check_contains_regex`](crate::check_contains_regex).
+    ContainsRegex,
+    /// All matches of a regular expression, see
+    /// [`check_only_contains_regex`](crate::check_only_contains_regex).
+    OnlyContainsRegex,
+    /// A substring, see [`check_contains_substring`].
+    ///
+    /// [`check_contains_substring`]: crate::check_contains_substring
+    ContainsSubstring,
+    /// Release entries in a changelog, see [`check_changelog`].
+    ///
+    /// [`check_changelog`]: crate::check_changelog
+    Changelog,
+    /// Dependencies between workspace members, see
+    /// [`check_workspace_path_deps`].
+    ///
+    /// [`check_workspace_path_deps`]: crate::check_workspace_path_deps
+    WorkspaceDeps,
+}
+
+impl CheckKind {
+    /// The name of the check, such as `"markdown-deps"`.
+    ///
+    /// This is the kind used in the `version-sync.toml` configuration
+    /// file.
+    pub fn name(self) -> &'static str {
+        match self {
+            CheckKind::MarkdownDeps => "markdown-deps",
+            CheckKind::RustdocDeps => "rustdoc-deps",
+            CheckKind::RustdocLinks => "rustdoc-links",
+            CheckKind::HtmlRootUrl => "html-root-url",
+            CheckKind::DeprecatedSince => "deprecated-since",
+            CheckKind::ContainsRegex => "contains-regex",
+            CheckKind::OnlyContainsRegex => "only-contains-regex",
+            CheckKind::ContainsSubstring => "contains",
+            CheckKind::Changelog => "changelog",
+            CheckKind::WorkspaceDeps => "workspace-deps",
+        }
+    }
+}
+
+/// Receives status information from the check functions.
+///
+/// The `_with_reporter` variants of the check functions, such as
+/// [`check_markdown_deps_with_reporter`], send their progress to a
+/// `Reporter`. The plain check functions use [`StdoutReporter`],
+/// which prints the progress on `stdout`.
+///
+/// All methods do nothing by default, so an empty implementation
+/// makes the checks silent. Implement the methods you need to collect
+/// the results or to display them in your own way.
+///
+/// [`check_markdown_deps_with_reporter`]: crate::check_markdown_deps_with_reporter
+pub trait Reporter {
+    /// A check of `path` is about to start. The `pattern` is the
+    /// expanded template for checks which search for a template.
+    fn check_started(&mut self, kind: CheckKind, path: &str, pattern: Option<&str>) {
+        let _ = (kind, path, pattern);
+    }
+
+    /// The location starting at `line` in `path` passed the check.
+    fn location_ok(&mut self, path: &str, line: usize) {
+        let _ = (path, line);
+    }
+
+    /// The location starting at `line` in `path` failed the check.
+    /// The `source` is the offending source code from `path`, starting
+    /// at `line` and without trimming, or empty if there is none.
+    fn location_mismatch(&mut self, path: &str, line: usize, error: &Error, source: &str) {
+        let _ = (path, line, error, source);
+    }
+
+    /// The location starting at `line` in `path` failed the check
+    /// with `error`, but was fixed by writing `updated` instead.
+    fn location_fixed(&mut self, path: &str, line: usize, error: &Error, updated: &str) {
+        let _ = (path, line, error, updated);
+    }
+
+    /// The check of `path` is finished.
+    fn check_finished(&mut self, kind: CheckKind, path: &str, result: &Result<()>) {
+        let _ = (kind, path, result);
+    }
+}
+
+/// Ignore all status information, for tests which only look at the
+/// result of a check.
+#[cfg(test)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Silent;
+
+#[cfg(test)]
+impl Reporter for Silent {}
+
+/// Print status information on `stdout`.
+///
+/// This is the reporter used by the check functions which do not
+/// take a [`Reporter`] argument.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdoutReporter;
+
+impl Reporter for StdoutReporter {
+    fn check_started(&mut self, kind: CheckKind, path: &str, pattern: Option<&str>) {
+        match (kind, pattern) {
+            (CheckKind::MarkdownDeps, _) => println!("Checking code blocks in {path}..."),
+            (CheckKind::RustdocDeps, _) => println!("Checking doc comments in {path}..."),
+            (CheckKind::RustdocLinks, _) => println!("Checking doc links in {path}..."),
+            (CheckKind::HtmlRootUrl, _) => println!("Checking doc attributes in {path}..."),
+            (CheckKind::DeprecatedSince, _) => {
+                println!("Checking deprecated attributes in {path}...")
+            }
+            (CheckKind::Changelog, _) => println!("Checking changelog in {path}..."),
+            (CheckKind::WorkspaceDeps, _) => println!("Checking dependencies in {path}..."),
+            (_, Some(pattern)) => println!("Searching for \"{pattern}\" in {path}..."),
+            (_, None) => println!("Checking {path}..."),
+        }
+    }
+
+    fn location_ok(&mut self, path: &str, line: usize) {
+        println!("{path} (line {line}) ... ok");
+    }
+
+    fn location_mismatch(&mut self, path: &str, line: usize, error: &Error, source: &str) {
+        if let Some(diagnostic) = mismatch_diagnostic(error, line, source, use_color()) {
+            println!("{diagnostic}\n");
+        } else if source.is_empty() {
+            println!("{path} (line {line}) ... {error}");
+        } else {
+            println!("{path} (line {line}) ... {error} in");
+            println!("{}\n", indent(source));
+        }
+    }
+
+    fn location_fixed(&mut self, path: &str, line: usize, error: &Error, updated: &str) {
+        match error {
+            Error::Mismatch { found, .. } => {
+                println!("{path} (line {line}) ... {error}, updated \"{found}\" to \"{updated}\"")
+            }
+            _ => println!("{path} (line {line}) ... {error}, updated to \"{updated}\""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check_contains_substring_with_reporter;
+
+    #[derive(Default)]
+    struct Events(Vec<String>);
+
+    impl Reporter for Events {
+        fn check_started(&mut self, kind: CheckKind, path: &str, pattern: Option<&str>) {
+            self.0.push(format!("started {kind:?} {path} {pattern:?}"));
+        }
+
+        fn location_ok(&mut self, path: &str, line: usize) {
+            self.0.push(format!("ok {path} {line}"));
+        }
+
+        fn check_finished(&mut self, kind: CheckKind, path: &str, result: &Result<()>) {
+            self.0
+                .push(format!("finished {kind:?} {path} {}", result.is_ok()));
+        }
+    }
+
+    #[cfg(feature = "rustdoc_deps_updated")]
+    #[test]
+    fn crate_docs_unreadable() {
+        let mut events = Events::default();
+        let result = crate::check_crate_docs_with_reporter(
+            "no-such-file.rs",
+            "foobar",
+            "1.2.3",
+            &mut events,
+        );
+        assert!(matches!(result, Err(Error::Io { .. })));
+        assert_eq!(
+            events.0,
+            vec![
+                "started RustdocDeps no-such-file.rs None",
+                "finished RustdocDeps no-such-file.rs false",
+            ]
+        );
+    }
+
+    #[cfg(feature = "rustdoc_deps_updated")]
+    #[test]
+    fn rustdoc_links_bad_version() {
+        let mut events = Events::default();
+        let result =
+            crate::check_rustdoc_links_with_reporter("src/lib.rs", "foobar", "1.x", &mut events);
+        assert!(matches!(result, Err(Error::PackageVersion { .. })));
+        assert_eq!(
+            events.0,
+            vec![
+                "started RustdocLinks src/lib.rs None",
+                "finished RustdocLinks src/lib.rs false",
+            ]
+        );
+    }
+
+    #[cfg(feature = "html_root_url_updated")]
+    #[test]
+    fn deprecated_since_bad_version() {
+        let mut events = Events::default();
+        let result = crate::check_deprecated_since_with_reporter("src/lib.rs", "1.x", &mut events);
+        assert!(matches!(result, Err(Error::PackageVersion { .. })));
+        assert_eq!(
+            events.0,
+            vec![
+                "started DeprecatedSince src/lib.rs None",
+                "finished DeprecatedSince src/lib.rs false",
+            ]
+        );
+    }
+
+    #[test]
+    fn events() {
+        let mut events = Events::default();
+        let result = check_contains_substring_with_reporter(
+            "README.md",
+            "# Keep your",
+            "foobar",
+            "1.2.3",
+            &mut events,
+        );
+        assert!(result.is_ok());
+        assert_eq!(
+            events.0,
+            vec![
+                "started ContainsSubstring README.md Some(\"# Keep your\")",
+                "ok README.md 1",
+                "finished ContainsSubstring README.md true",
+            ]
+        );
+    }
+
+    #[test]
+    fn finished_on_error() {
+        let mut events = Events::default();
+        let result = check_contains_substring_with_reporter(
+            "no-such-file.md",
+            "foo",
+            "foobar",
+            "1.2.3",
+            &mut events,
+        );
+        assert!(result.is_err());
+        assert_eq!(
+            events.0,
+            vec![
+                "started ContainsSubstring no-such-file.md Some(\"foo\")",
+                "finished ContainsSubstring no-such-file.md false",
+            ]
+        );
+    }
+}
@@ -0,0 +1,548 @@
+#![cfg(feature = "rustdoc_deps_updated")]
+use std::path::Path;
+
+use syn::visit::Visit;
+
+#[cfg(feature = "contains_regex")]
+use crate::contains_regex::check_only_contains_regex_with_reporter;
+use crate::error::{Error, Result};
+use crate::helpers::{parse_rust_file, parse_version};
+use crate::markdown_deps::{check_markdown_deps_with_reporter, check_text};
+use crate::reporter::{CheckKind, Reporter, StdoutReporter};
+
+/// Template for links to the documentation on docs.rs, used when
+/// checking included Markdown files.
+#[cfg(feature = "contains_regex")]
+const DOCS_RS_TEMPLATE: &str = "docs.rs/{name}/{version}/";
+
+/// A line of documentation and where it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DocLine {
+    /// The text of the line.
+    pub(crate) text: String,
+    /// Line number in the source file, starting with 1.
+    pub(crate) line: usize,
+    /// Column in the source file where `text` starts, counted in
+    /// characters from 0.
+    pub(crate) column: usize,
+}
+
+/// The documentation of a single item, assembled from its doc
+/// comments and `doc` attributes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct DocComment {
+    /// The lines of Markdown text.
+    pub(crate) lines: Vec<DocLine>,
+    /// Whether this is inner documentation, i.e., `//!` comments.
+    inner: bool,
+}
+
+impl DocComment {
+    /// The Markdown text of the documentation.
+    fn markdown(&self) -> String {
+        let lines = self.lines.iter().map(|line| line.text.as_str());
+        lines.collect::<Vec<_>>().join("\n")
+    }
+
+    /// Map a line and column in the Markdown text to the line and
+    /// column in the source file.
+    fn position(&self, line: usize, column: usize) -> (usize, usize) {
+        match self.lines.get(line - 1).or(self.lines.last()) {
+            Some(doc_line) => (doc_line.line, doc_line.column + column),
+            None => (line, column),
+        }
+    }
+}
+
+/// Collects the string values of all `doc` attributes in a file.
+struct DocCollector<'a> {
+    /// The source code of the file.
+    code: &'a str,
+    /// Attributes found so far with the lines of their values.
+    attrs: Vec<(bool, Vec<DocLine>)>,
+    /// Files included with `#[doc = include_str!("...")]`.
+    includes: Vec<String>,
+}
+
+impl DocCollector<'_> {
+    /// Find the column where the text of a doc comment or `doc`
+    /// string literal starts.
+    fn text_column(&self, lit: &syn::LitStr) -> usize {
+        let start = lit.span().start();
+        let source_line = self.code.lines().nth(start.line - 1).unwrap_or_default();
+        let source = source_line.chars().skip(start.column).collect::<String>();
+        // A doc comment is turned into a literal with the span of the
+        // whole comment, so we skip the comment marker.
+        if ["///", "//!", "/**", "/*!"]
+            .iter()
+            .any(|marker| source.starts_with(marker))
+        {
+            start.column + 3
+        } else {
+            start.column + 1
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for DocCollector<'_> {
+    fn visit_attribute(&mut self, attr: &'ast syn::Attribute) {
+        if !attr.path().is_ident("doc") {
+            return;
+        }
+        let lit = match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit),
+                        ..
+                    }),
+                ..
+            }) => lit,
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value: syn::Expr::Macro(expr),
+                ..
+            }) if expr.mac.path.is_ident("include_str") => {
+                // Paths built with concat! and env! are skipped.
+                if let Ok(file) = expr.mac.parse_body::<syn::LitStr>() {
+                    self.includes.push(file.value());
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        let start = lit.span().start();
+        let multiline = lit.span().end().line > start.line;
+        let column = self.text_column(lit);
+        let lines = lit
+            .value()
+            .split('\n')
+            .enumerate()
+            .map(|(idx, text)| DocLine {
+                text: String::from(text),
+                // A multi-line literal in a single-line doc attribute
+                // comes from escapes, so all lines map to the start.
+                line: if multiline {
+                    start.line + idx
+                } else {
+                    start.line
+                },
+                column: if idx == 0 { column } else { 0 },
+            })
+            .collect();
+        let inner = matches!(attr.style, syn::AttrStyle::Inner(_));
+        self.attrs.push((inner, lines));
+    }
+}
+
+/// Collect the `doc` attributes in `krate`.
+fn collect_docs<'a>(krate: &syn::File, code: &'a str) -> DocCollector<'a> {
+    let mut collector = DocCollector {
+        code,
+        attrs: Vec::new(),
+        includes: Vec::new(),
+    };
+    collector.visit_file(krate);
+    collector
+}
+
+/// Find the documentation in `code`.
+///
+/// Consecutive doc comments and `doc` attributes with string values
+/// are merged into one [`DocComment`], just like rustdoc does for the
+/// documentation of an item.
+pub(crate) fn find_doc_comments(krate: &syn::File, code: &str) -> Vec<DocComment> {
+    let mut collector = collect_docs(krate, code);
+    collector
+        .attrs
+        .sort_by_key(|(_, lines)| lines.first().map(|line| (line.line, line.column)));
+
+    let mut comments: Vec<DocComment> = Vec::new();
+    for (inner, lines) in collector.attrs {
+        let first_line = lines.first().map_or(0, |line| line.line);
+        match comments.last_mut() {
+            Some(comment)
+                if comment.inner == inner
+                    && comment
+                        .lines
+                        .last()
+                        .is_some_and(|last| first_line <= last.line + 1) =>
+            {
+                comment.lines.extend(lines);
+            }
+            _ => comments.push(DocComment { lines, inner }),
+        }
+    }
+    comments
+}
+
+/// Check dependencies in TOML code blocks in Rust doc comments.
+///
+/// This function parses the Rust source file in `path` and collects
+/// the documentation from `///` and `//!` comments as well as `doc`
+/// attributes with string values, such as `#[doc = "..."]`. The
+/// documentation of each item is then checked like a Markdown file
+/// with [`check_markdown_deps`]. This means that a crate root
+/// documented with
+///
+/// ~~~rust,ignore
+/// //! Add this to your `Cargo.toml`:
+/// //!
+/// //! ```toml
+/// //! [dependencies]
+/// //! foo = "1.2.3"
+/// //! ```
+/// ~~~
+///
+/// passes the check when the package named `foo` has version 1.2.3.
+/// Errors are reported with the line and column in `path`.
+///
+/// [`check_markdown_deps`]: crate::check_markdown_deps
+///
+/// # Errors
+///
+/// An [`Error::Rust`] is returned if `path` cannot be parsed. If any
+/// code block fails the check, an [`Error::Failed`] is returned with
+/// an error per outdated dependency or invalid block. Status
+/// information has then already been printed on `stdout`.
+pub fn check_rustdoc_deps(path: &str, pkg_name: &str, pkg_version: &str) -> Result<()> {
+    check_rustdoc_deps_with_reporter(path, pkg_name, pkg_version, &mut StdoutReporter)
+}
+
+/// Check dependencies in TOML code blocks in Rust doc comments and
+/// send status information to `reporter`.
+///
+/// This works like [`check_rustdoc_deps`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If any block fails the check, an [`Error::Failed`] is returned with
+/// an error per outdated dependency or invalid block.
+pub fn check_rustdoc_deps_with_reporter(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    reporter.check_started(CheckKind::RustdocDeps, path, None);
+    let result = check_doc_comments(path, pkg_name, pkg_version, reporter);
+    reporter.check_finished(CheckKind::RustdocDeps, path, &result);
+    result
+}
+
+/// Check the code blocks in all doc comments in `path`.
+fn check_doc_comments(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let (code, krate) = parse_rust_file(path)?;
+    let version = parse_version(pkg_version)?;
+
+    let mut errors = Vec::new();
+    for comment in find_doc_comments(&krate, &code) {
+        errors.extend(check_text(
+            path,
+            &code,
+            &comment.markdown(),
+            pkg_name,
+            &version,
+            reporter,
+            &|line, column| comment.position(line, column),
+        ));
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
+    }
+    Ok(())
+}
+
+/// Check everything rustdoc renders for the crate root in `path`.
+///
+/// This checks the doc comments in `path` with [`check_rustdoc_deps`]
+/// and finds all Markdown files included as documentation with
+///
+/// ```rust,ignore
+/// #![doc = include_str!("../README.md")]
+/// ```
+///
+/// The paths of the included files are relative to `path`, like for
+/// the [`include_str!`] macro. Each included file is checked with
+/// [`check_markdown_deps`]. With the `contains_regex` feature, links
+/// to the documentation on docs.rs in the included files are also
+/// checked with [`check_only_contains_regex`] using the template
+/// `"docs.rs/{name}/{version}/"`. It is not an error if an included
+/// file has no such links.
+///
+/// All checks are run, even if some of them fail. This function is
+/// used by [`assert_crate_docs_updated`].
+///
+/// [`check_markdown_deps`]: crate::check_markdown_deps
+/// [`check_only_contains_regex`]: crate::check_only_contains_regex
+/// [`assert_crate_docs_updated`]: crate::assert_crate_docs_updated
+///
+/// # Errors
+///
+/// An [`Error::Rust`] is returned if `path` cannot be parsed. If any
+/// check fails, an [`Error::Failed`] is returned with the error from
+/// each failed check. Status information has then already been
+/// printed on `stdout`.
+pub fn check_crate_docs(path: &str, pkg_name: &str, pkg_version: &str) -> Result<()> {
+    check_crate_docs_with_reporter(path, pkg_name, pkg_version, &mut StdoutReporter)
+}
+
+/// Check everything rustdoc renders for the crate root in `path` and
+/// send status information to `reporter`.
+///
+/// This works like [`check_crate_docs`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If any check fails, an [`Error::Failed`] is returned with the error
+/// from each failed check.
+pub fn check_crate_docs_with_reporter(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    reporter.check_started(CheckKind::RustdocDeps, path, None);
+    let result = check_crate_root(path, pkg_name, pkg_version, reporter);
+    reporter.check_finished(CheckKind::RustdocDeps, path, &result);
+    result
+}
+
+/// Check the doc comments in the crate root in `path` and the files it
+/// includes.
+fn check_crate_root(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let (code, krate) = parse_rust_file(path)?;
+    let includes = collect_docs(&krate, &code).includes;
+
+    let mut errors = Vec::new();
+    if let Err(err) = check_doc_comments(path, pkg_name, pkg_version, reporter) {
+        errors.push(err);
+    }
+    for include in includes {
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        let included = dir.join(include).to_string_lossy().into_owned();
+        if let Err(err) =
+            check_markdown_deps_with_reporter(&included, pkg_name, pkg_version, reporter)
+        {
+            errors.push(err);
+        }
+        #[cfg(feature = "contains_regex")]
+        match check_only_contains_regex_with_reporter(
+            &included,
+            DOCS_RS_TEMPLATE,
+            pkg_name,
+            pkg_version,
+            reporter,
+        ) {
+            Ok(()) | Err(Error::NoMatch { .. }) => {}
+            Err(err) => errors.push(err),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reporter::Silent;
+    use std::io::Write;
+
+    fn doc_comments(code: &str) -> Vec<DocComment> {
+        find_doc_comments(&syn::parse_file(code).unwrap(), code)
+    }
+
+    #[test]
+    fn inner_and_outer_comments() {
+        let code = "//! Crate docs.\n\
+                    //! More docs.\n\
+                    \n\
+                    /// Item docs.\n\
+                    #[doc = \"Attribute docs.\"]\n\
+                    fn foo() {}\n";
+        let comments = doc_comments(code);
+        assert_eq!(comments.len(), 2);
+        assert!(comments[0].inner);
+        assert_eq!(comments[0].markdown(), " Crate docs.\n More docs.");
+        assert!(!comments[1].inner);
+        assert_eq!(comments[1].markdown(), " Item docs.\nAttribute docs.");
+    }
+
+    #[test]
+    fn nested_items() {
+        let code = "mod foo {\n\
+                    \x20   /// Function docs.\n\
+                    \x20   fn bar() {}\n\
+                    }\n";
+        let comments = doc_comments(code);
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].position(1, 2), (2, 9));
+    }
+
+    #[test]
+    fn separate_items() {
+        let code = "/// First.\n\
+                    fn foo() {}\n\
+                    /// Second.\n\
+                    fn bar() {}\n";
+        let comments = doc_comments(code);
+        assert_eq!(comments.len(), 2);
+    }
+
+    #[test]
+    fn check_doc_comment_blocks() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(
+            b"//! Add this to your `Cargo.toml`:\n\
+              //!\n\
+              //! ```toml\n\
+              //! [dependencies]\n\
+              //! foobar = \"0.1\"\n\
+              //! ```\n\
+              \n\
+              /// ```toml\n\
+              /// [dev-dependencies]\n\
+              /// foobar = \"0.2\"\n\
+              /// ```\n\
+              fn foo() {}\n",
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let errors = match check_rustdoc_deps_with_reporter(path, "foobar", "0.2.0", &mut Silent) {
+            Err(Error::Failed { errors, .. }) => errors,
+            result => panic!("unexpected result: {result:?}"),
+        };
+        match &errors[..] {
+            [Error::Mismatch {
+                line: 5,
+                column: 15,
+                found,
+                ..
+            }] => assert_eq!(found, "0.1"),
+            errors => panic!("unexpected errors: {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn missing_dependency_line() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(
+            b"fn foo() {}\n\
+              \n\
+              /// ```toml\n\
+              /// [dependencies]\n\
+              /// baz = \"0.2\"\n\
+              /// ```\n\
+              fn bar() {}\n",
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let errors = match check_rustdoc_deps_with_reporter(path, "foobar", "0.2.0", &mut Silent) {
+            Err(Error::Failed { errors, .. }) => errors,
+            result => panic!("unexpected result: {result:?}"),
+        };
+        match &errors[..] {
+            [Error::MissingDependency { line: 4, .. }] => {}
+            errors => panic!("unexpected errors: {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn included_files() {
+        let code = "#![doc = include_str!(\"../README.md\")]\n\
+                    #![doc = include_str!(concat!(env!(\"OUT_DIR\"), \"/docs.md\"))]\n\
+                    #[doc = include_str!(\"foo.md\")]\n\
+                    fn foo() {}\n";
+        let krate = syn::parse_file(code).unwrap();
+        assert_eq!(
+            collect_docs(&krate, code).includes,
+            vec!["../README.md", "foo.md"]
+        );
+    }
+
+    #[test]
+    fn check_included_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "#![doc = include_str!(\"../README.md\")]\n\
+             /// ```toml\n\
+             /// [dependencies]\n\
+             /// foobar = \"0.2\"\n\
+             /// ```\n\
+             fn foo() {}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("README.md"),
+            "```toml\n\
+             [dependencies]\n\
+             foobar = \"0.1\"\n\
+             ```\n\
+             \n\
+             See https://docs.rs/foobar/0.1.0/foobar/.\n",
+        )
+        .unwrap();
+        let path = dir.path().join("src/lib.rs");
+
+        let errors = match check_crate_docs_with_reporter(
+            path.to_str().unwrap(),
+            "foobar",
+            "0.2.0",
+            &mut Silent,
+        ) {
+            Err(Error::Failed { errors, .. }) => errors,
+            result => panic!("unexpected result: {result:?}"),
+        };
+        let readme = dir.path().join("src/../README.md");
+        let failed = errors
+            .iter()
+            .map(|err| match err {
+                Error::Failed { path, .. } => path.as_str(),
+                err => panic!("unexpected error: {err:?}"),
+            })
+            .collect::<Vec<_>>();
+        let expected = readme.to_str().unwrap();
+        if cfg!(feature = "contains_regex") {
+            assert_eq!(failed, vec![expected, expected]);
+        } else {
+            assert_eq!(failed, vec![expected]);
+        }
+    }
+
+    #[test]
+    fn bad_rust_code() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"fn foo(").unwrap();
+        let path = file.path().to_str().unwrap();
+        match check_rustdoc_deps_with_reporter(path, "foobar", "0.2.0", &mut Silent) {
+            Err(Error::Rust { .. }) => {}
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+#![cfg(feature = "rustdoc_deps_updated")]
+use std::path::Path;
+
+use semver::{Version, VersionReq};
+
+use crate::error::{Error, Result};
+use crate::helpers::{parse_rust_file, parse_version, rust_files, version_matches_request};
+use crate::markdown_deps::same_crate_name;
+use crate::reporter::{CheckKind, Reporter, StdoutReporter};
+use crate::rustdoc_deps::find_doc_comments;
+
+/// Hosts with links to a specific version of a crate, followed by
+/// the path which comes before the crate name.
+const LINK_HOSTS: &[(&str, &[&str])] =
+    &[("docs.rs/", &["crate/", ""]), ("crates.io/", &["crates/"])];
+
+/// A link to a specific version of a crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct VersionLink<'a> {
+    /// The crate name in the link.
+    name: &'a str,
+    /// The version number or requirement in the link.
+    version: &'a str,
+    /// Byte offset of `version` in the text.
+    offset: usize,
+}
+
+/// Find links to docs.rs and crates.io with a version number in
+/// `text`.
+///
+/// Links without a version number, or with a version such as
+/// `latest`, are skipped since they cannot go stale.
+fn find_version_links(text: &str) -> Vec<VersionLink<'_>> {
+    let mut links = Vec::new();
+    for (host, prefixes) in LINK_HOSTS {
+        for (idx, _) in text.match_indices(host) {
+            // Skip hosts such as "mydocs.rs".
+            let before = text[..idx].chars().next_back();
+            if before.is_some_and(|c| c.is_alphanumeric() || c == '.' || c == '-') {
+                continue;
+            }
+
+            let after_host = idx + host.len();
+            let link = prefixes.iter().find_map(|prefix| {
+                let name_start = after_host + prefix.len();
+                let rest = text[after_host..].strip_prefix(prefix)?;
+                let (name, rest) = rest.split_once('/')?;
+                let end = rest
+                    .find(|c: char| "/#?)]>\"'`".contains(c) || c.is_whitespace())
+                    .unwrap_or(rest.len());
+                Some(VersionLink {
+                    name,
+                    version: &rest[..end],
+                    offset: name_start + name.len() + 1,
+                })
+            });
+            if let Some(link) = link {
+                if link
+                    .version
+                    .starts_with(|c: char| c.is_ascii_digit() || "^~=".contains(c))
+                {
+                    links.push(link);
+                }
+            }
+        }
+    }
+    links.sort_by_key(|link| link.offset);
+    links
+}
+
+/// Check the version in `link` against `version`.
+fn link_matches(link: &VersionLink, version: &Version) -> Result<(), String> {
+    VersionReq::parse(link.version)
+        .map_err(|err| format!("could not parse version in URL: {err}"))
+        .and_then(|request| version_matches_request(version, &request))
+}
+
+/// Check the links in the doc comments of the Rust file in `path`.
+fn check_file(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let version = parse_version(pkg_version)?;
+    let (code, krate) = parse_rust_file(path)?;
+
+    let mut errors = Vec::new();
+    for comment in find_doc_comments(&krate, &code) {
+        for doc_line in &comment.lines {
+            for link in find_version_links(&doc_line.text) {
+                if !same_crate_name(link.name, pkg_name) {
+                    continue;
+                }
+                match link_matches(&link, &version) {
+                    Ok(()) => reporter.location_ok(path, doc_line.line),
+                    Err(message) => {
+                        let column = doc_line.text[..link.offset].chars().count();
+                        let err = Error::Mismatch {
+                            path: String::from(path),
+                            line: doc_line.line,
+                            column: doc_line.column + column + 1,
+                            found: String::from(link.version),
+                            expected: version.to_string(),
+                            message,
+                        };
+                        let source = code.lines().nth(doc_line.line - 1).unwrap_or_default();
+                        reporter.location_mismatch(path, doc_line.line, &err, source);
+                        errors.push(err);
+                    }
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
+    }
+    Ok(())
+}
+
+/// Check links to docs.rs and crates.io in Rust doc comments.
+///
+/// The `path` is either a Rust source file or a directory, such as
+/// `src`, in which case all `.rs` files in the directory and its
+/// subdirectories are checked. The documentation is collected from
+/// `///` and `//!` comments and `doc` attributes with string values,
+/// just like for [`check_rustdoc_deps`].
+///
+/// Links such as `https://docs.rs/{pkg_name}/1.2.3/...`,
+/// `https://docs.rs/crate/{pkg_name}/1.2.3`, and
+/// `https://crates.io/crates/{pkg_name}/1.2.3` must have a version
+/// number which matches `pkg_version`. Links without a version, or
+/// with `latest` instead of a version, are always accepted.
+///
+/// [`check_rustdoc_deps`]: crate::check_rustdoc_deps
+///
+/// # Errors
+///
+/// If any link fails the check, an [`Error::Failed`] is returned with
+/// an [`Error::Mismatch`] per link. When `path` is a directory, the
+/// error has an [`Error::Failed`] per file. Status information has
+/// then already been printed on `stdout`.
+pub fn check_rustdoc_links(path: &str, pkg_name: &str, pkg_version: &str) -> Result<()> {
+    check_rustdoc_links_with_reporter(path, pkg_name, pkg_version, &mut StdoutReporter)
+}
+
+/// Check links to docs.rs and crates.io in Rust doc comments and
+/// send status information to `reporter`.
+///
+/// This works like [`check_rustdoc_links`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If any link fails the check, an [`Error::Failed`] is returned.
+pub fn check_rustdoc_links_with_reporter(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let files = if Path::new(path).is_dir() {
+        rust_files(Path::new(path))?
+    } else {
+        return check_file_with_reporter(path, pkg_name, pkg_version, reporter);
+    };
+
+    let mut errors = Vec::new();
+    for file in files {
+        let file = file.to_string_lossy();
+        errors.extend(check_file_with_reporter(&file, pkg_name, pkg_version, reporter).err());
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::Failed {
+            path: String::from(path),
+            errors,
+        });
+    }
+    Ok(())
+}
+
+/// Check a single Rust file and send status information to
+/// `reporter`.
+fn check_file_with_reporter(
+    path: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    reporter.check_started(CheckKind::RustdocLinks, path, None);
+    let result = check_file(path, pkg_name, pkg_version, reporter);
+    reporter.check_finished(CheckKind::RustdocLinks, path, &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reporter::Silent;
+    use std::fs;
+
+    fn links(text: &str) -> Vec<(&str, &str)> {
+        find_version_links(text)
+            .iter()
+            .map(|link| (link.name, link.version))
+            .collect()
+    }
+
+    #[test]
+    fn docs_rs_links() {
+        assert_eq!(
+            links("See [Foo](https://docs.rs/foo/0.7.1/foo/struct.Foo.html)."),
+            [("foo", "0.7.1")]
+        );
+        assert_eq!(links("<https://docs.rs/foo/1.2>"), [("foo", "1.2")]);
+        assert_eq!(links("https://docs.rs/crate/foo/1.2.3"), [("foo", "1.2.3")]);
+        assert_eq!(links("https://docs.rs/foo/1.2.3#usage"), [("foo", "1.2.3")]);
+    }
+
+    #[test]
+    fn crates_io_links() {
+        assert_eq!(
+            links("https://crates.io/crates/foo/1.0.0 and https://docs.rs/bar/2.0.0"),
+            [("foo", "1.0.0"), ("bar", "2.0.0")]
+        );
+    }
+
+    #[test]
+    fn unversioned_links() {
+        assert_eq!(links("https://docs.rs/foo/latest/foo/"), []);
+        assert_eq!(links("https://docs.rs/foo/*/foo/"), []);
+        assert_eq!(links("https://docs.rs/foo"), []);
+        assert_eq!(links("https://crates.io/crates/foo"), []);
+        assert_eq!(links("https://mydocs.rs/foo/1.0.0"), []);
+    }
+
+    #[test]
+    fn check_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("nested")).unwrap();
+        fs::write(
+            dir.path().join("lib.rs"),
+            "//! See <https://docs.rs/foo-bar/1.2.3/foo_bar/>.\n\
+             //! Also see <https://docs.rs/other/0.1.0/>.\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("nested/mod.rs"),
+            "/// Like [`Foo`](https://docs.rs/foo_bar/1.1.0/foo_bar/struct.Foo.html).\n\
+             pub struct Bar;\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("README.md"),
+            "https://docs.rs/foo-bar/0.1.0\n",
+        )
+        .unwrap();
+        let dir = dir.path().to_str().unwrap();
+
+        let errors = match check_rustdoc_links_with_reporter(dir, "foo-bar", "1.2.3", &mut Silent) {
+            Err(Error::Failed { errors, .. }) => errors,
+            result => panic!("unexpected result: {result:?}"),
+        };
+        match &errors[..] {
+            [Error::Failed { errors, .. }] => match &errors[..] {
+                [Error::Mismatch {
+                    line,
+                    column,
+                    found,
+                    ..
+                }] => {
+                    assert_eq!((*line, *column), (1, 42));
+                    assert_eq!(found, "1.1.0");
+                }
+                errors => panic!("unexpected errors: {errors:?}"),
+            },
+            errors => panic!("unexpected errors: {errors:?}"),
+        }
+        assert!(check_rustdoc_links_with_reporter(dir, "foo-bar", "1.1.0", &mut Silent).is_err());
+    }
+}
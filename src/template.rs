@@ -0,0 +1,388 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::reporter::{Reporter, StdoutReporter};
+
+/// A calendar date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Date {
+    year: i64,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    /// Find the date `days` days after 1970-01-01.
+    ///
+    /// This is the `civil_from_days` algorithm from
+    /// <http://howardhinnant.github.io/date_algorithms.html>.
+    fn from_days(days: i64) -> Date {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Date { year, month, day }
+    }
+
+    /// Today's date in UTC.
+    ///
+    /// The `SOURCE_DATE_EPOCH` environment variable is used instead of
+    /// the current time if it is set, which makes the checks
+    /// reproducible.
+    fn today() -> Date {
+        let seconds = env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|epoch| epoch.trim().parse::<i64>().ok())
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |duration| duration.as_secs() as i64)
+            });
+        Date::from_days(seconds.div_euclid(86_400))
+    }
+
+    /// Format the date using `%Y`, `%m`, and `%d` in `format` for the
+    /// year, month, and day. Use `%%` for a literal `%`.
+    fn format(&self, format: &str) -> String {
+        let mut result = String::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => result.push_str(&format!("{:04}", self.year)),
+                Some('m') => result.push_str(&format!("{:02}", self.month)),
+                Some('d') => result.push_str(&format!("{:02}", self.day)),
+                Some('%') => result.push('%'),
+                Some(other) => {
+                    result.push('%');
+                    result.push(other);
+                }
+                None => result.push('%'),
+            }
+        }
+        result
+    }
+}
+
+/// Extra placeholders for templates.
+///
+/// The placeholders derived from the package name and version, such
+/// as `{name}` and `{major}`, are always available. Use this to add
+/// your own placeholders, such as `{msrv}` or `{repository}`:
+///
+/// ```rust
+/// use version_sync::Placeholders;
+///
+/// let mut placeholders = Placeholders::new();
+/// placeholders.insert("msrv", "1.70");
+/// assert_eq!(placeholders.get("msrv"), Some("1.70"));
+/// ```
+///
+/// The [`cargo_placeholders`](crate::cargo_placeholders) macro
+/// creates the placeholders from the metadata in `Cargo.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Placeholders {
+    values: BTreeMap<String, String>,
+}
+
+impl Placeholders {
+    /// Create an empty set of placeholders.
+    pub fn new() -> Placeholders {
+        Placeholders::default()
+    }
+
+    /// Add a placeholder `{name}` which expands to `value`.
+    ///
+    /// The built-in placeholders, such as `{name}` and `{version}`,
+    /// cannot be overridden.
+    pub fn insert(&mut self, name: &str, value: &str) {
+        self.values.insert(String::from(name), String::from(value));
+    }
+
+    /// Find the value of the placeholder `{name}`, if any.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+}
+
+/// Options for the checks with a template, such as
+/// [`check_contains_substring_with_options`].
+///
+/// By default, only the built-in placeholders are available and status
+/// information is printed on `stdout`:
+///
+/// ```rust
+/// use version_sync::{Placeholders, TemplateOptions};
+///
+/// let mut placeholders = Placeholders::new();
+/// placeholders.insert("msrv", "1.70");
+/// let options = TemplateOptions::new().placeholders(&placeholders);
+/// ```
+///
+/// [`check_contains_substring_with_options`]: crate::check_contains_substring_with_options
+#[derive(Default)]
+pub struct TemplateOptions<'a> {
+    placeholders: Option<&'a Placeholders>,
+    reporter: Option<&'a mut dyn Reporter>,
+}
+
+impl<'a> TemplateOptions<'a> {
+    /// Create the default options.
+    pub fn new() -> TemplateOptions<'a> {
+        TemplateOptions::default()
+    }
+
+    /// Use the extra `placeholders` in the template.
+    pub fn placeholders(mut self, placeholders: &'a Placeholders) -> TemplateOptions<'a> {
+        self.placeholders = Some(placeholders);
+        self
+    }
+
+    /// Send status information to `reporter` instead of `stdout`.
+    pub fn reporter(mut self, reporter: &'a mut dyn Reporter) -> TemplateOptions<'a> {
+        self.reporter = Some(reporter);
+        self
+    }
+
+    /// Call `check` with the placeholders and the reporter.
+    pub(crate) fn run<T>(self, check: impl FnOnce(&Placeholders, &mut dyn Reporter) -> T) -> T {
+        let empty = Placeholders::new();
+        let placeholders = self.placeholders.unwrap_or(&empty);
+        match self.reporter {
+            Some(reporter) => check(placeholders, reporter),
+            None => check(placeholders, &mut StdoutReporter),
+        }
+    }
+}
+
+impl fmt::Debug for TemplateOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TemplateOptions")
+            .field("placeholders", &self.placeholders)
+            .field("reporter", &self.reporter.as_ref().map(|_| "..."))
+            .finish()
+    }
+}
+
+/// Find the value of `placeholder` for `pkg_name` and `pkg_version`.
+///
+/// The `placeholder` is the text between the braces, such as
+/// `"major"` or `"date:%d.%m.%Y"`. Returns the name of the placeholder
+/// and its value, or `None` for unknown placeholders.
+fn value<'p>(
+    placeholder: &'p str,
+    pkg_name: &str,
+    pkg_version: &str,
+    placeholders: &Placeholders,
+    date: Date,
+) -> Option<(&'p str, String)> {
+    let (name, format) = match placeholder.split_once(':') {
+        Some((name, format)) => (name, Some(format)),
+        None => (placeholder, None),
+    };
+
+    // Split "1.2.3-pre+build" into its parts without requiring a
+    // valid SemVer version.
+    let (rest, build) = pkg_version.split_once('+').unwrap_or((pkg_version, ""));
+    let (core, pre) = rest.split_once('-').unwrap_or((rest, ""));
+    let mut numbers = core.split('.');
+    let major = numbers.next().unwrap_or_default();
+    let minor = numbers.next().unwrap_or("0");
+    let patch = numbers.next().unwrap_or("0");
+
+    let value = match (name, format) {
+        ("date", format) => date.format(format.unwrap_or("%Y-%m-%d")),
+        ("env", Some(var)) => env::var(var).ok()?,
+        (_, Some(_)) => return None,
+        ("name", None) => String::from(pkg_name),
+        ("version", None) => String::from(pkg_version),
+        ("major", None) => String::from(major),
+        ("minor", None) => String::from(minor),
+        ("patch", None) => String::from(patch),
+        ("major_minor", None) => format!("{major}.{minor}"),
+        ("pre", None) => String::from(pre),
+        ("build", None) => String::from(build),
+        ("year", None) => date.format("%Y"),
+        (name, None) => String::from(placeholders.get(name)?),
+    };
+    Some((name, value))
+}
+
+/// Expand the placeholders in `template` using the given `date`.
+fn expand_with_date(
+    template: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    placeholders: &Placeholders,
+    date: Date,
+    mut replace: impl FnMut(&str, &str) -> String,
+) -> String {
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let expanded = after.find('}').and_then(|end| {
+            let (name, value) = value(&after[..end], pkg_name, pkg_version, placeholders, date)?;
+            Some((replace(name, &value), end))
+        });
+        match expanded {
+            Some((expanded, end)) => {
+                result.push_str(&expanded);
+                rest = &after[end + 1..];
+            }
+            None => {
+                // Unknown placeholders, such as the "{2,3}" repetition
+                // in a regular expression, are kept as they are.
+                result.push('{');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Expand the placeholders in `template`.
+///
+/// The placeholders are:
+///
+/// * `{name}`: the package name,
+/// * `{version}`: the full package version,
+/// * `{major}`, `{minor}`, and `{patch}`: the parts of the version,
+/// * `{major_minor}`: the major and minor version, such as `1.4`,
+/// * `{pre}` and `{build}`: the pre-release and build metadata, or
+///   nothing if the version has none,
+/// * `{date}`: today's date as `2024-05-17`, or formatted using `%Y`,
+///   `%m`, and `%d` as in `{date:%d.%m.%Y}`,
+/// * `{year}`: the current year,
+/// * `{env:FOO}`: the value of the environment variable `FOO`.
+///
+/// Any other placeholder is looked up in `placeholders`. The date is
+/// taken from the `SOURCE_DATE_EPOCH` environment variable if it is
+/// set.
+/// The `replace` function is called with the name and value of each
+/// placeholder and returns the text to insert, e.g., the value
+/// escaped for a regular expression. Other text in braces, including
+/// unset environment variables, is left untouched.
+pub fn expand(
+    template: &str,
+    pkg_name: &str,
+    pkg_version: &str,
+    placeholders: &Placeholders,
+    replace: impl FnMut(&str, &str) -> String,
+) -> String {
+    let date = Date::today();
+    expand_with_date(template, pkg_name, pkg_version, placeholders, date, replace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_literally(template: &str, pkg_version: &str) -> String {
+        let mut placeholders = Placeholders::new();
+        placeholders.insert("msrv", "1.70");
+        placeholders.insert("name", "bar");
+        let date = Date::from_days(20_742);
+        expand_with_date(
+            template,
+            "foo",
+            pkg_version,
+            &placeholders,
+            date,
+            |_, value| String::from(value),
+        )
+    }
+
+    #[test]
+    fn dates() {
+        let date = |days| {
+            let date = Date::from_days(days);
+            (date.year, date.month, date.day)
+        };
+        assert_eq!(date(0), (1970, 1, 1));
+        assert_eq!(date(-1), (1969, 12, 31));
+        assert_eq!(date(11_016), (2000, 2, 29));
+        assert_eq!(date(20_742), (2026, 10, 16));
+    }
+
+    #[test]
+    fn version_parts() {
+        assert_eq!(
+            expand_literally("{name} {version}: {major}|{minor}|{patch}", "1.4.2"),
+            "foo 1.4.2: 1|4|2"
+        );
+        assert_eq!(
+            expand_literally("/v{major}/ [{major_minor}]", "1.4.2"),
+            "/v1/ [1.4]"
+        );
+        assert_eq!(
+            expand_literally("{pre} {build}", "1.4.2-rc.1+abc"),
+            "rc.1 abc"
+        );
+        assert_eq!(expand_literally("<{pre}>", "1.4.2"), "<>");
+    }
+
+    #[test]
+    fn date_formats() {
+        assert_eq!(
+            expand_literally("## [{major_minor}] - {date}", "1.4.0"),
+            "## [1.4] - 2026-10-16"
+        );
+        assert_eq!(
+            expand_literally("{date:%d.%m.%Y} (c) {year} 100%%", "1.4.0"),
+            "16.10.2026 (c) 2026 100%%"
+        );
+        assert_eq!(expand_literally("{date:%Y%%}", "1.4.0"), "2026%");
+    }
+
+    #[test]
+    fn unknown_placeholders() {
+        assert_eq!(
+            expand_literally(r"\d{2,3} {unknown} {name:x} {{version}", "1.0.0"),
+            r"\d{2,3} {unknown} {name:x} {1.0.0"
+        );
+    }
+
+    #[test]
+    fn replace_values() {
+        let expanded = expand_with_date(
+            "{name}-{version}",
+            "foo",
+            "1.0.0",
+            &Placeholders::new(),
+            Date::from_days(0),
+            |name, value| format!("<{name}={value}>"),
+        );
+        assert_eq!(expanded, "<name=foo>-<version=1.0.0>");
+    }
+
+    #[test]
+    fn user_defined_placeholders() {
+        assert_eq!(
+            expand_literally("{name} needs Rust {msrv}, not {homepage}", "1.0.0"),
+            "foo needs Rust 1.70, not {homepage}"
+        );
+    }
+
+    #[test]
+    fn environment_variables() {
+        // CARGO is set by Cargo when running the tests.
+        let cargo = env::var("CARGO").unwrap();
+        assert_eq!(
+            expand_literally("{env:CARGO} {env:VERSION_SYNC_UNSET}", "1.0.0"),
+            format!("{cargo} {{env:VERSION_SYNC_UNSET}}")
+        );
+    }
+}
@@ -0,0 +1,662 @@
+#![cfg(feature = "workspace")]
+use std::path::Path;
+
+use toml_edit::{ImDocument, Item};
+
+use crate::error::{Error, Result};
+use crate::helpers::{
+    dependency_tables, line_column, parse_version, read_toml, resolve, string_contents,
+    version_matches_request,
+};
+use crate::manifest::{inherited, Package};
+use crate::reporter::{CheckKind, Reporter, StdoutReporter};
+use crate::{
+    check_html_root_url_with_reporter, check_markdown_deps_with_reporter,
+    fix_html_root_url_with_reporter, fix_markdown_deps_with_reporter,
+};
+
+/// A package in a workspace.
+#[derive(Debug)]
+pub struct Member {
+    /// The directory with the `Cargo.toml` file of the member.
+    pub dir: String,
+    /// The parsed `Cargo.toml` file of the member.
+    pub manifest: ImDocument<String>,
+    /// The name and version of the member.
+    pub package: Package,
+}
+
+/// Return the string values of an array in a manifest.
+fn string_array(path: &str, item: Option<&Item>, key: &str) -> Result<Vec<String>> {
+    let item = match item {
+        Some(item) => item,
+        None => return Ok(Vec::new()),
+    };
+    item.as_array()
+        .and_then(|array| {
+            array
+                .iter()
+                .map(|value| value.as_str().map(String::from))
+                .collect()
+        })
+        .ok_or_else(|| Error::Manifest {
+            path: String::from(path),
+            message: format!("{key} must be an array of strings"),
+        })
+}
+
+/// Find the member directories matching the `members` patterns of
+/// the workspace in `root`, skipping the `exclude` paths.
+fn member_dirs(root: &str, manifest_path: &str, workspace: &Item) -> Result<Vec<String>> {
+    let manifest_error = |message: String| Error::Manifest {
+        path: String::from(manifest_path),
+        message,
+    };
+    let members = string_array(manifest_path, workspace.get("members"), "workspace.members")?;
+    let exclude = string_array(manifest_path, workspace.get("exclude"), "workspace.exclude")?;
+    let is_excluded = |dir: &str| {
+        let dir = Path::new(dir);
+        let relative = dir.strip_prefix(root).unwrap_or(dir);
+        exclude
+            .iter()
+            .any(|excluded| relative.starts_with(excluded))
+    };
+
+    let mut dirs = Vec::new();
+    for pattern in &members {
+        let full_pattern = resolve(root, pattern);
+        if !pattern.contains(['*', '?', '[']) {
+            dirs.push(full_pattern);
+            continue;
+        }
+        let paths = glob::glob(&full_pattern)
+            .map_err(|err| manifest_error(format!("bad member pattern {pattern:?}: {err}")))?;
+        for entry in paths {
+            let path = entry
+                .map_err(|err| manifest_error(format!("bad member pattern {pattern:?}: {err}")))?;
+            // Like Cargo, ignore matches which are not packages.
+            if path.join("Cargo.toml").is_file() {
+                dirs.push(path.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    dirs.retain(|dir| !is_excluded(dir));
+    let mut seen = std::collections::HashSet::new();
+    dirs.retain(|dir| seen.insert(dir.clone()));
+    Ok(dirs)
+}
+
+/// Find all members of the workspace with the root manifest in
+/// `root`. A root package comes first, followed by the members in
+/// the order given by `workspace.members`.
+pub fn workspace_members(root: &str) -> Result<Vec<Member>> {
+    let manifest_path = resolve(root, "Cargo.toml");
+    let document = read_toml(&manifest_path)?;
+    let workspace = document.get("workspace").ok_or_else(|| Error::Manifest {
+        path: manifest_path.clone(),
+        message: String::from("no [workspace] table"),
+    })?;
+
+    let mut dirs = member_dirs(root, &manifest_path, workspace)?;
+    let root_dir = if root.is_empty() { "." } else { root };
+    dirs.retain(|dir| Path::new(dir) != Path::new(root_dir));
+
+    let mut members = Vec::new();
+    if document.get("package").is_some() {
+        let package = Package::from_document(&manifest_path, &document)?;
+        members.push(Member {
+            dir: String::from(root_dir),
+            manifest: document,
+            package,
+        });
+    }
+    for dir in dirs {
+        let path = resolve(&dir, "Cargo.toml");
+        let manifest = read_toml(&path)?;
+        let package = Package::from_document(&path, &manifest)?;
+        members.push(Member {
+            dir,
+            manifest,
+            package,
+        });
+    }
+    Ok(members)
+}
+
+/// Find the README file of a member, if any.
+///
+/// This is the `package.readme` file, with `README.md` as default.
+/// An inherited README path is relative to the workspace `root`.
+fn readme_path(root: &str, root_manifest: &ImDocument<String>, member: &Member) -> Option<String> {
+    match member
+        .manifest
+        .get("package")
+        .and_then(|package| package.get("readme"))
+    {
+        Some(readme) if inherited(readme) => root_manifest
+            .get("workspace")
+            .and_then(|workspace| workspace.get("package"))
+            .and_then(|package| package.get("readme"))
+            .and_then(|readme| readme.as_str())
+            .map(|readme| resolve(root, readme)),
+        Some(readme) => readme.as_str().map(|readme| resolve(&member.dir, readme)),
+        None => Some(resolve(&member.dir, "README.md")).filter(|path| Path::new(path).is_file()),
+    }
+}
+
+/// Find the crate root of a member, if any.
+///
+/// This is the `lib.path` file, with `src/lib.rs` and `src/main.rs`
+/// as defaults.
+fn crate_root_path(member: &Member) -> Option<String> {
+    let lib_path = member
+        .manifest
+        .get("lib")
+        .and_then(|lib| lib.get("path"))
+        .and_then(|path| path.as_str());
+    if let Some(lib_path) = lib_path {
+        return Some(resolve(&member.dir, lib_path));
+    }
+    ["src/lib.rs", "src/main.rs"]
+        .iter()
+        .map(|path| resolve(&member.dir, path))
+        .find(|path| Path::new(path).is_file())
+}
+
+/// Check or fix all members of the workspace in `root`.
+fn run_checks(root: &str, fix: bool, reporter: &mut dyn Reporter) -> Result<()> {
+    let root_manifest = read_toml(&resolve(root, "Cargo.toml"))?;
+    let members = workspace_members(root)?;
+
+    let mut errors = Vec::new();
+    for member in &members {
+        let name = member.package.name.as_str();
+        let version = member.package.version.as_str();
+
+        if let Some(readme) = readme_path(root, &root_manifest, member) {
+            let result = if fix {
+                fix_markdown_deps_with_reporter(&readme, name, version, reporter)
+            } else {
+                check_markdown_deps_with_reporter(&readme, name, version, reporter)
+            };
+            errors.extend(result.err());
+        }
+
+        if let Some(crate_root) = crate_root_path(member) {
+            let result = if fix {
+                fix_html_root_url_with_reporter(&crate_root, name, version, reporter)
+            } else {
+                check_html_root_url_with_reporter(&crate_root, name, version, reporter)
+            };
+            errors.extend(result.err());
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Failed {
+            path: resolve(root, "Cargo.toml"),
+            errors,
+        })
+    }
+}
+
+/// Check the README and crate root of every workspace member.
+///
+/// The workspace root manifest is `root/Cargo.toml`. The members are
+/// found using the `members` and `exclude` keys of its `[workspace]`
+/// table, with support for glob patterns such as `crates/*`. A root
+/// package is checked too.
+///
+/// For each member, the name and version are read from its
+/// `Cargo.toml` file. A `version.workspace = true` version is read
+/// from `[workspace.package]`. The member is then checked with:
+///
+/// * [`check_markdown_deps`] on the `package.readme` file, or on
+///   `README.md` if it exists.
+/// * [`check_html_root_url`] on the `lib.path` file, or on
+///   `src/lib.rs` or `src/main.rs` if they exist.
+///
+/// All members are checked, even if some of them fail.
+///
+/// [`check_markdown_deps`]: crate::check_markdown_deps
+/// [`check_html_root_url`]: crate::check_html_root_url
+///
+/// # Errors
+///
+/// An [`Error::Manifest`] is returned if a manifest is invalid. If any
+/// check fails, an [`Error::Failed`] is returned with the error from
+/// each failed check. Status information has then already been
+/// printed on `stdout`.
+pub fn check_workspace(root: &str) -> Result<()> {
+    check_workspace_with_reporter(root, &mut StdoutReporter)
+}
+
+/// Check all workspace members and send status information to
+/// `reporter`.
+///
+/// This works like [`check_workspace`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If any check fails, an [`Error::Failed`] is returned with the error
+/// from each failed check.
+pub fn check_workspace_with_reporter(root: &str, reporter: &mut dyn Reporter) -> Result<()> {
+    run_checks(root, false, reporter)
+}
+
+/// Update outdated version numbers in all workspace members.
+///
+/// This works like [`check_workspace`], but uses
+/// [`fix_markdown_deps`] and [`fix_html_root_url`] on each member.
+///
+/// [`fix_markdown_deps`]: crate::fix_markdown_deps
+/// [`fix_html_root_url`]: crate::fix_html_root_url
+///
+/// # Errors
+///
+/// If any check fails and cannot be fixed, an [`Error::Failed`] is
+/// returned with the error from each failed check.
+pub fn fix_workspace(root: &str) -> Result<()> {
+    fix_workspace_with_reporter(root, &mut StdoutReporter)
+}
+
+/// Update outdated version numbers in all workspace members and send
+/// status information to `reporter`.
+///
+/// This works like [`fix_workspace`], but nothing is printed on
+/// `stdout`.
+///
+/// # Errors
+///
+/// If any check fails and cannot be fixed, an [`Error::Failed`] is
+/// returned with the error from each failed check.
+pub fn fix_workspace_with_reporter(root: &str, reporter: &mut dyn Reporter) -> Result<()> {
+    run_checks(root, true, reporter)
+}
+
+/// Check the path dependencies on workspace members in the manifest
+/// found in `dir`.
+///
+/// The `members` are the canonical directories of the members with
+/// their versions.
+fn check_path_deps(
+    dir: &str,
+    manifest: &ImDocument<String>,
+    members: &[(std::path::PathBuf, &Package)],
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    let path = resolve(dir, "Cargo.toml");
+    let text = manifest.raw();
+
+    let mut errors = Vec::new();
+    for table in dependency_tables(manifest.as_table()) {
+        let dependencies = match table.as_table_like() {
+            Some(dependencies) => dependencies,
+            None => continue,
+        };
+        for (dep_name, dependency) in dependencies.iter() {
+            let dep_path = match dependency.get("path").and_then(|path| path.as_str()) {
+                Some(dep_path) => Path::new(dir).join(dep_path),
+                None => continue,
+            };
+            let version_item = match dependency.get("version") {
+                Some(version_item) => version_item,
+                None => continue, // Path-only dependencies are not published.
+            };
+            let package = match dep_path.canonicalize().ok().and_then(|dep_path| {
+                members
+                    .iter()
+                    .find(|(member_dir, _)| *member_dir == dep_path)
+                    .map(|(_, package)| *package)
+            }) {
+                Some(package) => package,
+                None => continue,
+            };
+
+            let span = version_item.span().map(|span| string_contents(text, span));
+            let (line, column) = line_column(text, span.as_ref().map_or(0, |span| span.start));
+            let request = version_item.as_str().ok_or_else(|| Error::Manifest {
+                path: path.clone(),
+                message: format!("{dep_name}.version must be a string"),
+            })?;
+            let result = parse_version(&package.version).and_then(|version| {
+                semver::VersionReq::parse(request)
+                    .map_err(|err| format!("could not parse dependency: {err}"))
+                    .and_then(|request| version_matches_request(&version, &request))
+                    .map_err(|err| Error::Mismatch {
+                        path: path.clone(),
+                        line,
+                        column,
+                        found: String::from(request),
+                        expected: package.version.clone(),
+                        message: format!("dependency on {}: {err}", package.name),
+                    })
+            });
+            match result {
+                Ok(()) => reporter.location_ok(&path, line),
+                Err(err) => {
+                    let source = text.lines().nth(line - 1).unwrap_or_default();
+                    reporter.location_mismatch(&path, line, &err, source);
+                    errors.push(err);
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Failed { path, errors })
+    }
+}
+
+/// Check the path dependencies between the members of the workspace
+/// in `root`.
+fn check_all_path_deps(root: &str, reporter: &mut dyn Reporter) -> Result<()> {
+    let root_path = resolve(root, "Cargo.toml");
+    let root_manifest = read_toml(&root_path)?;
+    let members = workspace_members(root)?;
+    let member_dirs = members
+        .iter()
+        .map(|member| {
+            let dir = Path::new(&member.dir)
+                .canonicalize()
+                .map_err(|source| Error::Io {
+                    path: member.dir.clone(),
+                    source,
+                })?;
+            Ok((dir, &member.package))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let root_dir = if root.is_empty() { "." } else { root };
+    let mut manifests: Vec<(&str, &ImDocument<String>)> = members
+        .iter()
+        .map(|member| (member.dir.as_str(), &member.manifest))
+        .collect();
+    // A virtual manifest only has [workspace.dependencies].
+    if !members.iter().any(|member| member.dir == root_dir) {
+        manifests.insert(0, (root_dir, &root_manifest));
+    }
+
+    let mut errors = Vec::new();
+    for (dir, manifest) in manifests {
+        let path = resolve(dir, "Cargo.toml");
+        reporter.check_started(CheckKind::WorkspaceDeps, &path, None);
+        let result = check_path_deps(dir, manifest, &member_dirs, reporter);
+        reporter.check_finished(CheckKind::WorkspaceDeps, &path, &result);
+        errors.extend(result.err());
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Failed {
+            path: root_path,
+            errors,
+        })
+    }
+}
+
+/// Check dependencies between workspace members.
+///
+/// A workspace member often depends on another member with both a
+/// path and a version, such as
+///
+/// ```toml
+/// [dependencies]
+/// foo-core = { path = "../core", version = "0.4" }
+/// ```
+///
+/// The version is used when the crate is published, so it must match
+/// the current version of the member at that path. This function
+/// checks all such dependencies in the `[dependencies]`,
+/// `[dev-dependencies]`, and `[build-dependencies]` tables of every
+/// member, including target-specific tables such as
+/// `[target.'cfg(unix)'.dependencies]`, as well as the
+/// `[workspace.dependencies]` table in `root/Cargo.toml`.
+///
+/// Dependencies without a version, such as `{ path = "../core" }`,
+/// and dependencies on crates outside the workspace are ignored.
+///
+/// # Errors
+///
+/// If any dependency fails the check, an [`Error::Failed`] is returned
+/// with an error per manifest with outdated dependencies. Status
+/// information has then already been printed on `stdout`.
+pub fn check_workspace_path_deps(root: &str) -> Result<()> {
+    check_workspace_path_deps_with_reporter(root, &mut StdoutReporter)
+}
+
+/// Check dependencies between workspace members and send status
+/// information to `reporter`.
+///
+/// This works like [`check_workspace_path_deps`], but nothing is
+/// printed on `stdout`.
+///
+/// # Errors
+///
+/// If any dependency fails the check, an [`Error::Failed`] is returned
+/// with an error per manifest with outdated dependencies.
+pub fn check_workspace_path_deps_with_reporter(
+    root: &str,
+    reporter: &mut dyn Reporter,
+) -> Result<()> {
+    check_all_path_deps(root, reporter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reporter::Silent;
+    use std::fs;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    /// Create a workspace with a root package and two members.
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[package]\nname = 'app'\nversion = '0.1.0'\n\n\
+             [workspace]\nmembers = ['crates/*', 'tools/gen']\nexclude = ['crates/old']\n\n\
+             [workspace.package]\nversion = '2.0.0'\n",
+        );
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = 'core'\nversion.workspace = true\n",
+        );
+        write(
+            root,
+            "crates/core/README.md",
+            "```toml\n[dependencies]\ncore = \"2.0\"\n```\n",
+        );
+        write(
+            root,
+            "crates/core/src/lib.rs",
+            "#![doc(html_root_url = \"https://docs.rs/core/2.0.0\")]\n",
+        );
+        write(
+            root,
+            "crates/old/Cargo.toml",
+            "[package]\nname = 'old'\nversion = '0.0.1'\n",
+        );
+        write(root, "crates/not-a-package/README.md", "");
+        write(
+            root,
+            "tools/gen/Cargo.toml",
+            "[package]\nname = 'gen'\nversion = '0.3.0'\nreadme = 'GEN.md'\n",
+        );
+        write(
+            root,
+            "tools/gen/GEN.md",
+            "```toml\n[dependencies]\ngen = \"0.3\"\n```\n",
+        );
+        dir
+    }
+
+    #[test]
+    fn members() {
+        let dir = workspace();
+        let root = dir.path().to_str().unwrap();
+        let members = workspace_members(root).unwrap();
+        let packages: Vec<_> = members
+            .iter()
+            .map(|member| {
+                (
+                    member.package.name.as_str(),
+                    member.package.version.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            packages,
+            vec![("app", "0.1.0"), ("core", "2.0.0"), ("gen", "0.3.0")]
+        );
+        assert_eq!(members[1].dir, resolve(root, "crates/core"));
+    }
+
+    #[test]
+    fn not_a_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "Cargo.toml",
+            "[package]\nname = 'app'\nversion = '0.1.0'\n",
+        );
+        let root = dir.path().to_str().unwrap();
+        assert_eq!(
+            workspace_members(root)
+                .map(|_| ())
+                .map_err(|err| err.to_string()),
+            Err(format!(
+                "invalid manifest {}: no [workspace] table",
+                resolve(root, "Cargo.toml")
+            ))
+        );
+    }
+
+    #[test]
+    fn check_and_fix() {
+        let dir = workspace();
+        let root = dir.path().to_str().unwrap();
+        assert!(check_workspace_with_reporter(root, &mut Silent).is_ok());
+
+        write(
+            dir.path(),
+            "Cargo.toml",
+            &fs::read_to_string(dir.path().join("Cargo.toml"))
+                .unwrap()
+                .replace("'2.0.0'", "'2.1.0'"),
+        );
+        match check_workspace_with_reporter(root, &mut Silent) {
+            Err(Error::Failed { errors, .. }) => assert_eq!(errors.len(), 2),
+            result => panic!("unexpected result: {result:?}"),
+        }
+
+        assert!(fix_workspace_with_reporter(root, &mut Silent).is_ok());
+        assert!(check_workspace_with_reporter(root, &mut Silent).is_ok());
+        let readme = fs::read_to_string(dir.path().join("crates/core/README.md")).unwrap();
+        assert!(readme.contains("core = \"2.1\""));
+    }
+
+    #[test]
+    fn path_deps() {
+        let dir = workspace();
+        let root = dir.path().to_str().unwrap();
+        write(
+            dir.path(),
+            "tools/gen/Cargo.toml",
+            "[package]\nname = 'gen'\nversion = '0.3.0'\n\n\
+             [dependencies]\ncore = { path = '../../crates/core', version = '2.0' }\n\
+             serde = '1.0'\n\n\
+             [target.'cfg(unix)'.dev-dependencies]\n\
+             core = { path = '../../crates/core', version = '1.9' }\n\n\
+             [build-dependencies]\ncore = { path = '../../crates/core' }\n",
+        );
+        match check_workspace_path_deps_with_reporter(root, &mut Silent) {
+            Err(Error::Failed { errors, .. }) => match &errors[..] {
+                [Error::Failed { path, errors }] => {
+                    assert_eq!(path, &resolve(root, "tools/gen/Cargo.toml"));
+                    match &errors[..] {
+                        [Error::Mismatch {
+                            line,
+                            column,
+                            found,
+                            expected,
+                            message,
+                            ..
+                        }] => {
+                            assert_eq!((*line, *column), (10, 49));
+                            assert_eq!(found, "1.9");
+                            assert_eq!(expected, "2.0.0");
+                            assert_eq!(
+                                message,
+                                "dependency on core: expected major version 2, found 1"
+                            );
+                        }
+                        errors => panic!("unexpected errors: {errors:?}"),
+                    }
+                }
+                errors => panic!("unexpected errors: {errors:?}"),
+            },
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn path_dep_version_not_a_string() {
+        let dir = workspace();
+        let root = dir.path().to_str().unwrap();
+        write(
+            dir.path(),
+            "tools/gen/Cargo.toml",
+            "[package]\nname = 'gen'\nversion = '0.3.0'\n\n\
+             [dependencies]\ncore = { path = '../../crates/core', version = 2 }\n",
+        );
+        match check_workspace_path_deps_with_reporter(root, &mut Silent) {
+            Err(Error::Failed { errors, .. }) => match &errors[..] {
+                [Error::Manifest { path, message }] => {
+                    assert_eq!(path, &resolve(root, "tools/gen/Cargo.toml"));
+                    assert_eq!(message, "core.version must be a string");
+                }
+                errors => panic!("unexpected errors: {errors:?}"),
+            },
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn workspace_dependencies() {
+        let dir = workspace();
+        let root = dir.path().to_str().unwrap();
+        let manifest = fs::read_to_string(dir.path().join("Cargo.toml")).unwrap();
+        write(
+            dir.path(),
+            "Cargo.toml",
+            &format!(
+                "{manifest}\n[workspace.dependencies]\n\
+                 gen = {{ path = 'tools/gen', version = '0.3.0' }}\n\
+                 core = {{ path = 'crates/core', version = '0.2' }}\n"
+            ),
+        );
+        match check_workspace_path_deps_with_reporter(root, &mut Silent) {
+            Err(Error::Failed { errors, .. }) => {
+                assert_eq!(errors.len(), 1);
+                assert!(matches!(&errors[0], Error::Failed { errors, .. } if errors.len() == 1));
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+}
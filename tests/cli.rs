@@ -0,0 +1,169 @@
+#![cfg(feature = "cli")]
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn version_sync(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_version-sync"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn package(readme: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"foobar\"\nversion = \"1.2.3\"\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("README.md"), readme).unwrap();
+    dir
+}
+
+const OUTDATED: &str = "```toml\n[dependencies]\nfoobar = \"1.1\"\n```\n";
+
+#[test]
+fn this_package() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let output = version_sync(dir, &["markdown-deps", "README.md"]);
+    assert_eq!(output.status.code(), Some(0));
+    let output = version_sync(dir, &["html-root-url", "src/lib.rs"]);
+    assert_eq!(output.status.code(), Some(0));
+    let output = version_sync(dir, &["rustdoc-deps", "src/lib.rs"]);
+    assert_eq!(output.status.code(), Some(0));
+    let output = version_sync(dir, &["rustdoc-links", "src"]);
+    assert_eq!(output.status.code(), Some(0));
+    let output = version_sync(dir, &["changelog", "README.md"]);
+    assert_eq!(output.status.code(), Some(0));
+    let output = version_sync(
+        dir,
+        &[
+            "only-contains-regex",
+            "README.md",
+            "docs.rs/{name}/{version}/",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn check_failure() {
+    let dir = package(OUTDATED);
+    let output = version_sync(dir.path(), &["markdown-deps", "README.md"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr, "error: README.md ... found 1 error\n");
+
+    let output = version_sync(
+        dir.path(),
+        &["contains", "README.md", "foobar = \"{version}\""],
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn machine_readable_output() {
+    let dir = package(OUTDATED);
+    let output = version_sync(
+        dir.path(),
+        &["--format", "json", "markdown-deps", "README.md"],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout,
+        "{\"kind\": \"markdown-deps\", \"path\": \"README.md\", \"line\": 3, \
+         \"column\": 11, \"found\": \"1.1\", \"expected\": \"1.2\", \
+         \"status\": \"mismatch\", \"message\": \"expected minor version 2, found 1\"}\n"
+    );
+
+    let output = version_sync(
+        dir.path(),
+        &["--format=sarif", "markdown-deps", "README.md"],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\"version\": \"2.1.0\""));
+    assert!(stdout.contains("\"region\": {\"startLine\": 3, \"startColumn\": 11}"));
+}
+
+#[test]
+fn annotations() {
+    let dir = package(OUTDATED);
+    let output = version_sync(
+        dir.path(),
+        &["--format", "github", "markdown-deps", "README.md"],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Checking code blocks in README.md...\n"));
+    assert!(stdout
+        .ends_with("\n::error file=README.md,line=3,col=11::expected minor version 2, found 1\n"));
+
+    let output = version_sync(
+        dir.path(),
+        &[
+            "--format=gitlab",
+            "--output",
+            "gl-code-quality-report.json",
+            "markdown-deps",
+            "README.md",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let report = fs::read_to_string(dir.path().join("gl-code-quality-report.json")).unwrap();
+    assert!(report.contains("\"check_name\": \"markdown-deps\""));
+    assert!(report.contains("\"location\": {\"path\": \"README.md\", \"lines\": {\"begin\": 3}}"));
+}
+
+#[test]
+fn fix() {
+    let dir = package(OUTDATED);
+    let output = version_sync(dir.path(), &["--fix", "markdown-deps", "README.md"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("README.md")).unwrap(),
+        "```toml\n[dependencies]\nfoobar = \"1.2\"\n```\n"
+    );
+}
+
+#[test]
+fn manifest_path() {
+    let dir = package("Version 1.2.3\n");
+    let readme = dir.path().join("README.md");
+    let manifest = dir.path().join("Cargo.toml");
+    let output = version_sync(
+        Path::new(env!("CARGO_MANIFEST_DIR")),
+        &[
+            "--manifest-path",
+            manifest.to_str().unwrap(),
+            "contains",
+            readme.to_str().unwrap(),
+            "Version {version}",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn usage_errors() {
+    let dir = package("");
+    let output = version_sync(dir.path(), &["frobnicate"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"foobar\"\n",
+    )
+    .unwrap();
+    let output = version_sync(dir.path(), &["markdown-deps", "README.md"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr,
+        "error: invalid manifest Cargo.toml: no string value for package.version\n"
+    );
+}
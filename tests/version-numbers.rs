@@ -4,6 +4,18 @@ fn test_readme_deps() {
     version_sync::assert_markdown_deps_updated!("README.md");
 }
 
+#[test]
+#[cfg(feature = "rustdoc_deps_updated")]
+fn test_rustdoc_deps() {
+    version_sync::assert_rustdoc_deps_updated!("src/lib.rs");
+}
+
+#[test]
+#[cfg(feature = "rustdoc_deps_updated")]
+fn test_rustdoc_links() {
+    version_sync::assert_rustdoc_links_updated!("src");
+}
+
 #[test]
 #[cfg(feature = "contains_regex")]
 fn test_readme_changelog() {
@@ -19,8 +31,36 @@ fn test_readme_links_are_updated() {
     version_sync::assert_only_contains_regex!("README.md", "docs.rs/{name}/{version}/");
 }
 
+#[test]
+#[cfg(feature = "changelog")]
+fn test_readme_release_history() {
+    version_sync::assert_changelog_updated!("README.md");
+}
+
 #[test]
 #[cfg(feature = "html_root_url_updated")]
 fn test_html_root_url() {
     version_sync::assert_html_root_url_updated!("src/lib.rs");
 }
+
+#[test]
+#[cfg(feature = "html_root_url_updated")]
+fn test_deprecated_since() {
+    version_sync::assert_deprecated_since_valid!("src");
+}
+
+#[test]
+#[cfg(feature = "html_root_url_updated")]
+fn test_package_html_root_urls() {
+    version_sync::assert_package_html_root_urls_updated!();
+}
+
+#[test]
+#[cfg(all(feature = "markdown_deps_updated", feature = "html_root_url_updated"))]
+fn test_checks() {
+    version_sync::checks!()
+        .markdown_deps("README.md")
+        .html_root_url("src/lib.rs")
+        .contains_substring("README.md", "### Version {version}")
+        .assert();
+}